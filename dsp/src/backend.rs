@@ -0,0 +1,312 @@
+//! One FFT interface over microfft, CMSIS-DSP and a reference DFT.
+//!
+//! The lab4 examples used to come in pairs, one calling arm_cfft_f32 and one
+//! calling microfft, identical apart from that. Fft is what they have in
+//! common, each backend a zero sized type implementing it for the lengths it
+//! supports:
+//!
+//! - Microfft, radix-2 in pure rust, 2 to 4096
+//! - Cmsis, ARM's arm_cfft_f32 and arm_rfft_fast_f32, 16 to 4096 and 32 to
+//!   4096 for the real FFT. It links ARM's C library so only builds for the
+//!   device, with the `cmsis` feature
+//! - Reference, the direct DFT sum with exact twiddles. N^2 so slow, but
+//!   theres nothing clever in it to get wrong, so its what the others are
+//!   checked against on the host
+//! - CmsisModel, CMSIS-DSP's functions as documented, flags, scaling and
+//!   packing, but worked out with the direct DFT. Its Fft calls the model the
+//!   way Cmsis calls the C, so everything but ARM's arithmetic can be checked
+//!   on the host, see the differential module
+//!
+//! Selected is whichever the `cmsis` or `reference` feature asks for, or
+//! microfft with neither, and fft, ifft and rfft here go through it. So an
+//! example written against them switches backend with a feature flag:
+//!
+//! ```text
+//! cargo run --release --example 4_5_fft_calculations --features cmsis
+//! ```
+//!
+//! Everything else in the crate calls microfft directly whatever is selected.
+
+use crate::complex::from_interleaved_mut;
+use crate::dft::dft_bin;
+use crate::fft::{ComplexBuffer, Normalization};
+use crate::rfft::{RealBuffer, RealSpectrum};
+use crate::Error;
+use microfft::Complex32;
+
+#[cfg(all(feature = "cmsis", feature = "reference"))]
+compile_error!("the cmsis and reference features both pick the FFT backend, turn one off");
+
+/// An FFT of length N
+pub trait Fft<const N: usize> {
+    /// In place forward FFT, unscaled like cfft
+    fn fft(buffer: &mut [Complex32; N]);
+
+    /// In place inverse FFT, conjugating either side of fft like icfft
+    fn ifft(buffer: &mut [Complex32; N], normalization: Normalization) {
+        buffer.iter_mut().for_each(|c| c.im = -c.im);
+        Self::fft(buffer);
+        let scale = normalization.inverse(N);
+        buffer.iter_mut().for_each(|c| {
+            c.re *= scale;
+            c.im *= -scale;
+        });
+    }
+
+    /// In place real FFT, the N/2+1 bins packed into the samples like rfft
+    fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_>;
+}
+
+#[cfg(feature = "cmsis")]
+pub type Selected = Cmsis;
+#[cfg(feature = "reference")]
+pub type Selected = Reference;
+#[cfg(not(any(feature = "cmsis", feature = "reference")))]
+pub type Selected = Microfft;
+
+/// Which backend Selected is, for printing alongside timings
+#[cfg(feature = "cmsis")]
+pub const NAME: &str = "cmsis";
+#[cfg(feature = "reference")]
+pub const NAME: &str = "reference";
+#[cfg(not(any(feature = "cmsis", feature = "reference")))]
+pub const NAME: &str = "microfft";
+
+/// cfft with the selected backend
+pub fn fft<B, const N: usize>(buffer: &mut B) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+    Selected: Fft<N>,
+{
+    let array = buffer.as_array()?;
+    Selected::fft(array);
+    Ok(array)
+}
+
+/// icfft with the selected backend
+pub fn ifft<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+    Selected: Fft<N>,
+{
+    let array = buffer.as_array()?;
+    Selected::ifft(array, normalization);
+    Ok(array)
+}
+
+/// rfft with the selected backend
+pub fn rfft<B, const N: usize>(buffer: &mut B) -> Result<RealSpectrum<'_>, Error>
+where
+    B: RealBuffer<N> + ?Sized,
+    Selected: Fft<N>,
+{
+    Ok(Selected::rfft(buffer.as_array()?))
+}
+
+/// microfft, what cfft and rfft call
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Microfft;
+
+impl<const N: usize> Fft<N> for Microfft {
+    fn fft(buffer: &mut [Complex32; N]) {
+        // an array is always the right length
+        let _ = crate::fft::cfft(buffer).unwrap();
+    }
+
+    fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_> {
+        crate::rfft::rfft(buffer).unwrap()
+    }
+}
+
+/// The DFT straight from its definition
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reference;
+
+impl<const N: usize> Fft<N> for Reference {
+    fn fft(buffer: &mut [Complex32; N]) {
+        let x = *buffer;
+        for (k, y) in buffer.iter_mut().enumerate() {
+            *y = dft_bin(&x, k).unwrap();
+        }
+    }
+
+    fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_> {
+        assert!(N >= 2 && N.is_multiple_of(2), "rfft needs an even length");
+
+        let x = *buffer;
+        let packed = from_interleaved_mut(buffer);
+        for (k, y) in packed.iter_mut().enumerate() {
+            *y = dft_bin(&x, k).unwrap();
+        }
+        // Nyquist rides in the imaginary part of DC
+        packed[0].im = dft_bin(&x, N / 2).unwrap().re;
+        RealSpectrum::from_packed(packed)
+    }
+}
+
+/// CMSIS-DSP without CMSIS-DSP, for the host. Its Fft calls the model the
+/// same way Cmsis calls the C, so a mistake in a flag or the packing shows up
+/// against Reference without a board.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CmsisModel;
+
+impl CmsisModel {
+    /// arm_cfft_f32. Forward is unscaled and ifft scales by 1/N. Without
+    /// bit_reverse the output is left in bit reversed order, CMSIS makes
+    /// putting it back optional.
+    pub fn cfft<const N: usize>(buffer: &mut [Complex32; N], ifft: bool, bit_reverse: bool) {
+        assert!(
+            N.is_power_of_two() && (16..=4096).contains(&N),
+            "CMSIS has no FFT of length {}",
+            N
+        );
+
+        if ifft {
+            buffer.iter_mut().for_each(|c| c.im = -c.im);
+        }
+        Reference::fft(buffer);
+        if ifft {
+            let scale = 1.0 / N as f32;
+            buffer.iter_mut().for_each(|c| {
+                c.re *= scale;
+                c.im *= -scale;
+            });
+        }
+
+        if !bit_reverse {
+            for k in 0..N {
+                let reversed = bit_reverse_index(k, N);
+                if k < reversed {
+                    buffer.swap(k, reversed);
+                }
+            }
+        }
+    }
+
+    /// arm_rfft_fast_f32 forward, unscaled. Output is N/2 complex bins
+    /// interleaved with the real Nyquist bin where DC's imaginary part would
+    /// be, since thats always zero.
+    pub fn rfft_fast<const N: usize>(input: &[f32; N], output: &mut [f32; N]) {
+        assert!(
+            N.is_power_of_two() && (32..=4096).contains(&N),
+            "CMSIS has no real FFT of length {}",
+            N
+        );
+
+        let packed = from_interleaved_mut(output);
+        for (k, y) in packed.iter_mut().enumerate() {
+            *y = dft_bin(input, k).unwrap();
+        }
+        packed[0].im = dft_bin(input, N / 2).unwrap().re;
+    }
+}
+
+impl<const N: usize> Fft<N> for CmsisModel {
+    fn fft(buffer: &mut [Complex32; N]) {
+        Self::cfft(buffer, false, true);
+    }
+
+    fn ifft(buffer: &mut [Complex32; N], normalization: Normalization) {
+        Self::cfft(buffer, true, true);
+        rescale(buffer, normalization);
+    }
+
+    fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_> {
+        let input = *buffer;
+        Self::rfft_fast(&input, buffer);
+        RealSpectrum::from_packed(from_interleaved_mut(buffer))
+    }
+}
+
+/// Where bin k ends up in an FFT that skips the bit reversal, n a power of two
+pub(crate) fn bit_reverse_index(k: usize, n: usize) -> usize {
+    match n {
+        0 | 1 => k,
+        _ => k.reverse_bits() >> (usize::BITS - n.trailing_zeros()),
+    }
+}
+
+/// From CMSIS's 1/N inverse to whatever normalization was asked for
+fn rescale<const N: usize>(buffer: &mut [Complex32; N], normalization: Normalization) {
+    let scale = normalization.inverse(N) * N as f32;
+    if scale != 1.0 {
+        buffer.iter_mut().for_each(|c| *c *= scale);
+    }
+}
+
+#[cfg(feature = "cmsis")]
+pub use self::cmsis::Cmsis;
+
+#[cfg(feature = "cmsis")]
+mod cmsis {
+    use super::{rescale, Fft};
+    use crate::complex::{from_interleaved_mut, Interleaved};
+    use crate::fft::Normalization;
+    use crate::rfft::RealSpectrum;
+    use cmsis_dsp_sys::*;
+    use microfft::Complex32;
+
+    /// ARM's CMSIS-DSP
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Cmsis;
+
+    macro_rules! cmsis {
+        ($($n:literal => $instance:ident,)*) => {
+            $(
+                impl Fft<$n> for Cmsis {
+                    fn fft(buffer: &mut [Complex32; $n]) {
+                        // Complex32 is two f32 side by side in memory, which
+                        // is the interleaved layout CMSIS wants
+                        let interleaved = buffer[..].as_interleaved_mut();
+                        unsafe { arm_cfft_f32(&$instance, interleaved.as_mut_ptr(), 0, 1) };
+                    }
+
+                    fn ifft(buffer: &mut [Complex32; $n], normalization: Normalization) {
+                        // the ifftFlag always scales by 1/N, anything else is
+                        // put right after
+                        let interleaved = buffer[..].as_interleaved_mut();
+                        unsafe { arm_cfft_f32(&$instance, interleaved.as_mut_ptr(), 1, 1) };
+                        rescale(buffer, normalization);
+                    }
+
+                    fn rfft(buffer: &mut [f32; $n]) -> RealSpectrum<'_> {
+                        // arm_rfft_fast_f32 isnt in place and scribbles over
+                        // its input, so it gets a copy
+                        let mut input = *buffer;
+                        let mut instance: arm_rfft_fast_instance_f32 =
+                            unsafe { core::mem::zeroed() };
+                        // theres no 16 point real FFT, init says so rather
+                        // than the transform
+                        let status = unsafe { arm_rfft_fast_init_f32(&mut instance, $n) };
+                        assert!(status == 0, "CMSIS has no real FFT of length {}", $n);
+                        unsafe {
+                            arm_rfft_fast_f32(&mut instance, input.as_mut_ptr(), buffer.as_mut_ptr(), 0);
+                        }
+                        RealSpectrum::from_packed(from_interleaved_mut(buffer))
+                    }
+                }
+            )*
+        };
+    }
+
+    cmsis! {
+        16 => arm_cfft_sR_f32_len16,
+        32 => arm_cfft_sR_f32_len32,
+        64 => arm_cfft_sR_f32_len64,
+        128 => arm_cfft_sR_f32_len128,
+        256 => arm_cfft_sR_f32_len256,
+        512 => arm_cfft_sR_f32_len512,
+        1024 => arm_cfft_sR_f32_len1024,
+        2048 => arm_cfft_sR_f32_len2048,
+        4096 => arm_cfft_sR_f32_len4096,
+    }
+
+    /// CMSIS's C needs a sqrtf to link against, micromath's will do
+    #[no_mangle]
+    pub extern "C" fn sqrtf(x: f32) -> f32 {
+        micromath::F32Ext::sqrt(x)
+    }
+}
@@ -0,0 +1,80 @@
+//! The rest of the complex math for microfft's Complex32.
+//!
+//! microfft pulls in num-complex without std, which leaves us the operators
+//! and conj but none of the float functions, so the examples kept writing out
+//! `(re * re + im * im).sqrt()`. ComplexExt fills those in on top of
+//! micromath on device, or std with the `std` feature.
+//!
+//! Complex32 is repr(C), two f32 side by side, so a slice of them can also be
+//! handed to CMSIS as the interleaved re, im, re, im floats it expects without
+//! copying.
+
+use microfft::Complex32;
+
+#[cfg(not(feature = "std"))]
+use micromath::F32Ext;
+
+pub trait ComplexExt {
+    /// r e^(i theta)
+    fn from_polar(r: f32, theta: f32) -> Self;
+    /// |z|
+    fn magnitude(self) -> f32;
+    /// |z|^2, no sqrt so its cheaper when we only need to compare
+    fn magnitude_squared(self) -> f32;
+    /// angle from the positive real axis in -pi..=pi
+    fn arg(self) -> f32;
+    /// e^z
+    fn exp(self) -> Self;
+}
+
+impl ComplexExt for Complex32 {
+    fn from_polar(r: f32, theta: f32) -> Self {
+        Complex32::new(r * theta.cos(), r * theta.sin())
+    }
+
+    fn magnitude(self) -> f32 {
+        self.magnitude_squared().sqrt()
+    }
+
+    fn magnitude_squared(self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn arg(self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
+    fn exp(self) -> Self {
+        Self::from_polar(self.re.exp(), self.im)
+    }
+}
+
+/// Complex slices as the interleaved floats CMSIS takes
+pub trait Interleaved {
+    fn as_interleaved(&self) -> &[f32];
+    fn as_interleaved_mut(&mut self) -> &mut [f32];
+}
+
+impl Interleaved for [Complex32] {
+    fn as_interleaved(&self) -> &[f32] {
+        // repr(C) with two f32 fields, so same alignment and exactly twice as
+        // many f32 in the same memory
+        unsafe { core::slice::from_raw_parts(self.as_ptr() as *const f32, self.len() * 2) }
+    }
+
+    fn as_interleaved_mut(&mut self) -> &mut [f32] {
+        unsafe { core::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut f32, self.len() * 2) }
+    }
+}
+
+/// The other way, interleaved floats viewed as complex. A trailing odd float
+/// is left out.
+pub fn from_interleaved(floats: &[f32]) -> &[Complex32] {
+    unsafe { core::slice::from_raw_parts(floats.as_ptr() as *const Complex32, floats.len() / 2) }
+}
+
+pub fn from_interleaved_mut(floats: &mut [f32]) -> &mut [Complex32] {
+    unsafe {
+        core::slice::from_raw_parts_mut(floats.as_mut_ptr() as *mut Complex32, floats.len() / 2)
+    }
+}
@@ -0,0 +1,150 @@
+//! Chirp-Z transform, the z transform at M points along a spiral.
+//!
+//! The FFT gives N bins evenly round the whole unit circle, so a 512 point
+//! FFT of the accelerometer at 100Hz is stuck with 0.2Hz bins from DC to
+//! Nyquist. The chirp-Z transform evaluates
+//!
+//! X[k] = sum over n of x[n] z_k^(-n),    z_k = A W^k
+//!
+//! at any M points z_k along a spiral, or just an arc of the unit circle, so
+//! those M points can all be bunched across the few Hz that matter. Its no
+//! sharper than the FFT, the main lobe of a tone is still as wide, but it
+//! samples the DTFT finely enough to see where the peak really is.
+//!
+//! Bluestein's trick writes nk as (n^2 + k^2 - (k - n)^2) / 2, which turns
+//! the sum into a convolution with a chirp, done with FFTs of length L. L has
+//! to be a power of two at least N + M - 1, checked at compile time.
+//!
+//! ```ignore
+//! // 64 points from 2Hz to 4Hz of 512 samples at 100Hz
+//! let czt = Czt::<512, 64, 1024>::frequencies(2.0, 2.0 / 64.0, 100.0);
+//! let spectrum = czt.transform(&samples).unwrap();
+//! ```
+
+use crate::dft::{Sample, Samples};
+use crate::fft::{cfft, icfft, Normalization, Supported};
+use crate::{float, Error};
+use microfft::Complex32;
+
+#[derive(Clone, Debug)]
+pub struct Czt<const N: usize, const M: usize, const L: usize> {
+    /// turns round the circle of the first point and between points
+    start: f64,
+    step: f64,
+    /// A^-n times the chirp, what each sample gets multiplied by going in
+    pre: [Complex32; N],
+    /// FFT of the conjugate chirp the samples get convolved with
+    kernel: [Complex32; L],
+    /// the chirp again for each point coming out
+    post: [Complex32; M],
+}
+
+impl<const N: usize, const M: usize, const L: usize> Czt<N, M, L> {
+    const CHECK: () = assert!(
+        N > 0 && M > 0 && N + M - 1 <= L,
+        "L has to be at least N + M - 1"
+    );
+
+    /// Points z_k = r0 rho^k e^(2 pi i (t0 + k s)), starting at radius r0
+    /// and t0 turns round, each point rho times further out and s turns
+    /// further round. A radius of 1 and a step of 1/N is the DFT.
+    pub fn new(start_radius: f32, start_turns: f64, step_radius: f32, step_turns: f64) -> Self {
+        let () = Self::CHECK;
+        let () = Supported::<L>::CHECK;
+
+        let start_ln = float::ln(start_radius as f64);
+        let step_ln = float::ln(step_radius as f64);
+
+        // c(m) = rho^(-m^2/2) e^(-2 pi i s m^2/2)
+        let chirp = |m: usize, sign: f64| {
+            let half_square = (m * m) as f64 / 2.0;
+            polar(
+                -sign * step_ln * half_square,
+                -sign * step_turns * half_square,
+            )
+        };
+
+        let mut pre = [Complex32::new(0.0, 0.0); N];
+        for (n, pre) in pre.iter_mut().enumerate() {
+            // A^-n split out so its one rounding
+            let a = polar(-start_ln * n as f64, -start_turns * n as f64);
+            *pre = a * chirp(n, 1.0);
+        }
+
+        let mut post = [Complex32::new(0.0, 0.0); M];
+        for (k, post) in post.iter_mut().enumerate() {
+            *post = chirp(k, 1.0);
+        }
+
+        // 1/c(m) for m from -(N-1) to M-1, the negative half wrapped round
+        // to the end so the circular convolution is the linear one for the
+        // first M outputs
+        let mut kernel = [Complex32::new(0.0, 0.0); L];
+        for (m, kernel) in kernel.iter_mut().enumerate().take(M) {
+            *kernel = chirp(m, -1.0);
+        }
+        for m in 1..N {
+            kernel[L - m] = chirp(m, -1.0);
+        }
+        // cant fail, L is checked above
+        let _ = cfft(&mut kernel).unwrap();
+
+        Self {
+            start: start_turns,
+            step: step_turns,
+            pre,
+            kernel,
+            post,
+        }
+    }
+
+    /// M points on the unit circle from start Hz, step Hz apart
+    pub fn frequencies(start: f32, step: f32, sample_rate: f32) -> Self {
+        Self::new(
+            1.0,
+            start as f64 / sample_rate as f64,
+            1.0,
+            step as f64 / sample_rate as f64,
+        )
+    }
+
+    /// Where point k is round the circle in Hz, whatever its radius
+    pub fn frequency(&self, k: usize, sample_rate: f32) -> f32 {
+        ((self.start + k as f64 * self.step) * sample_rate as f64) as f32
+    }
+
+    /// X[k] at every point, unscaled like the DFT
+    pub fn transform<T, S>(&self, input: &S) -> Result<[Complex32; M], Error>
+    where
+        T: Sample,
+        S: Samples<T, N> + ?Sized,
+    {
+        let x = input.as_samples()?;
+
+        let mut y = [Complex32::new(0.0, 0.0); L];
+        y.iter_mut()
+            .zip(x.iter().zip(self.pre.iter()))
+            .for_each(|(y, (x, pre))| *y = x.rotate(*pre));
+
+        cfft(&mut y)?;
+        y.iter_mut()
+            .zip(self.kernel.iter())
+            .for_each(|(y, kernel)| *y *= kernel);
+        icfft(&mut y, Normalization::OneOverN)?;
+
+        let mut output = [Complex32::new(0.0, 0.0); M];
+        output
+            .iter_mut()
+            .zip(y.iter().zip(self.post.iter()))
+            .for_each(|(output, (y, post))| *output = y * post);
+        Ok(output)
+    }
+}
+
+/// e^(ln r + 2 pi i turns), worked out in f64 so a long chirp keeps its
+/// phase
+fn polar(ln_radius: f64, turns: f64) -> Complex32 {
+    let radius = float::exp(ln_radius);
+    let (sin, cos) = float::sin_cos_turns(turns);
+    Complex32::new((radius * cos) as f32, (radius * sin) as f32)
+}
@@ -0,0 +1,156 @@
+//! Discrete cosine and sine transforms.
+//!
+//! The DFT of a block assumes it repeats, so the jump from the last sample
+//! back round to the first smears energy across the whole spectrum. The DCT
+//! treats the block as mirrored instead, no jump, so a smooth signal like an
+//! accelerometer log ends up with nearly all its energy in the first few
+//! coefficients. Keep those, drop the rest and the signal still comes back
+//! close, which is the idea behind JPEG and MP3.
+//!
+//! X[k] = sum over n of x[n] cos(pi k (2n + 1) / 2N)      DCT-II
+//! X[k] = sum over n of x[n] sin(pi (k + 1) (2n + 1) / 2N) DST-II
+//!
+//! Thats half of what scipy calls DCT-II. DCT-III and DST-III are the
+//! inverses, up to the usual scaling:
+//!
+//! - OneOverN, forward unscaled and the inverse gets the samples back
+//! - None, the inverse is N times too big, scipy's DCT-III when paired with
+//!   its DCT-II
+//! - Unitary, the orthonormal DCT, every coefficient scaled so energy is the
+//!   same in both domains, sqrt(1/N) for DC and sqrt(2/N) for the rest
+//!
+//! The DCT is worked out with Makhoul's trick, the even samples followed by
+//! the odd ones reversed through an N point rfft and a twiddle per bin. The
+//! DST is the DCT of the samples with every other one negated, read out
+//! backwards.
+//!
+//! ```ignore
+//! let mut x: [f32; 256] = ...;
+//! dct2(&mut x, Normalization::Unitary).unwrap();
+//! ```
+
+use crate::fft::{icfft, Normalization, Supported};
+use crate::rfft::{rfft, RealBuffer};
+use crate::{float, Error};
+use microfft::Complex32;
+
+/// In place DCT-II
+pub fn dct2<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [f32; N], Error>
+where
+    B: RealBuffer<N> + ?Sized,
+{
+    let () = Supported::<N>::CHECK;
+    let x = buffer.as_array()?;
+
+    // evens forward then odds backward
+    let mut v = [0.0; N];
+    for n in 0..N / 2 {
+        v[n] = x[2 * n];
+        v[N - 1 - n] = x[2 * n + 1];
+    }
+    let spectrum = rfft(&mut v)?;
+
+    for (k, x) in x.iter_mut().enumerate() {
+        // V[k] for k past N/2 is the conjugate of V[N-k]
+        let v = if k <= N / 2 {
+            spectrum.bin(k).unwrap()
+        } else {
+            spectrum.bin(N - k).unwrap().conj()
+        };
+        *x = (quarter_twiddle::<N>(k) * v).re * forward_scale::<N>(k, normalization);
+    }
+
+    Ok(x)
+}
+
+/// In place DCT-III, the inverse of dct2 under the same normalization
+pub fn dct3<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [f32; N], Error>
+where
+    B: RealBuffer<N> + ?Sized,
+{
+    let () = Supported::<N>::CHECK;
+    let x = buffer.as_array()?;
+
+    // back to the unscaled coefficients, then V[k] is
+    // e^(i pi k / 2N) (X[k] - i X[N-k])
+    let unscaled = |k: usize| {
+        if k == N {
+            0.0
+        } else {
+            x[k] / forward_scale::<N>(k, normalization)
+        }
+    };
+    let mut v = [Complex32::new(0.0, 0.0); N];
+    for (k, v) in v.iter_mut().enumerate() {
+        let pair = Complex32::new(unscaled(k), -unscaled(N - k));
+        *v = quarter_twiddle::<N>(k).conj() * pair;
+    }
+
+    let inverse = match normalization {
+        Normalization::None => Normalization::None,
+        Normalization::OneOverN | Normalization::Unitary => Normalization::OneOverN,
+    };
+    let v = icfft(&mut v, inverse)?;
+
+    for n in 0..N / 2 {
+        x[2 * n] = v[n].re;
+        x[2 * n + 1] = v[N - 1 - n].re;
+    }
+
+    Ok(x)
+}
+
+/// In place DST-II
+pub fn dst2<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [f32; N], Error>
+where
+    B: RealBuffer<N> + ?Sized,
+{
+    let x = buffer.as_array()?;
+    alternate(x);
+    let x = dct2(x, normalization)?;
+    x.reverse();
+    Ok(x)
+}
+
+/// In place DST-III, the inverse of dst2 under the same normalization
+pub fn dst3<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [f32; N], Error>
+where
+    B: RealBuffer<N> + ?Sized,
+{
+    let x = buffer.as_array()?;
+    x.reverse();
+    let x = dct3(x, normalization)?;
+    alternate(x);
+    Ok(x)
+}
+
+/// Negate every odd sample
+fn alternate(x: &mut [f32]) {
+    x.iter_mut().skip(1).step_by(2).for_each(|x| *x = -*x);
+}
+
+/// e^(-i pi k / 2N)
+fn quarter_twiddle<const N: usize>(k: usize) -> Complex32 {
+    float::twiddle(k as u64, 4 * N as u64)
+}
+
+/// What dct2 multiplies X[k] by
+fn forward_scale<const N: usize>(k: usize, normalization: Normalization) -> f32 {
+    match normalization {
+        Normalization::Unitary if k == 0 => float::sqrt(1.0 / N as f64) as f32,
+        Normalization::Unitary => float::sqrt(2.0 / N as f64) as f32,
+        Normalization::OneOverN | Normalization::None => 1.0,
+    }
+}
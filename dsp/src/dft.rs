@@ -0,0 +1,146 @@
+//! DFT straight from the definition, for any N.
+//!
+//! X[k] = sum over n of x[n] e^(-2 pi i k n/N)
+//!
+//! The 4_1 and 4_3 examples worked out cos and sin four times per term. Here
+//! the N twiddle factors e^(-2 pi i k/N) are computed by the compiler into a
+//! table in flash and every term is a lookup at kn mod N. Still O(N^2) for the
+//! whole spectrum, but cheap for a handful of bins with dft_bin and its not
+//! stuck to powers of two.
+//!
+//! ```ignore
+//! let x: heapless::Vec<f32, 256> = ...;
+//! let spectrum: heapless::Vec<Complex32, 256> = dsp::dft::dft(&x).unwrap().collect();
+//! ```
+
+use crate::fft::Normalization;
+use crate::{float, Error};
+use core::convert::TryInto;
+use microfft::Complex32;
+
+/// Samples we can take the DFT of, real or complex
+pub trait Sample: Copy {
+    /// self times a twiddle factor
+    fn rotate(self, twiddle: Complex32) -> Complex32;
+}
+
+impl Sample for f32 {
+    /// half the multiplies of promoting to complex first
+    fn rotate(self, twiddle: Complex32) -> Complex32 {
+        Complex32::new(self * twiddle.re, self * twiddle.im)
+    }
+}
+
+impl Sample for Complex32 {
+    fn rotate(self, twiddle: Complex32) -> Complex32 {
+        self * twiddle
+    }
+}
+
+/// Samples that can be viewed as an array of exactly N
+pub trait Samples<T, const N: usize> {
+    fn as_samples(&self) -> Result<&[T; N], Error>;
+}
+
+impl<T, const N: usize> Samples<T, N> for [T; N] {
+    fn as_samples(&self) -> Result<&[T; N], Error> {
+        Ok(self)
+    }
+}
+
+impl<T, const N: usize> Samples<T, N> for heapless::Vec<T, N> {
+    fn as_samples(&self) -> Result<&[T; N], Error> {
+        self[..].try_into().map_err(|_| Error::Length {
+            expected: N,
+            found: self.len(),
+        })
+    }
+}
+
+impl<T, const N: usize> Samples<T, N> for [T] {
+    fn as_samples(&self) -> Result<&[T; N], Error> {
+        self.try_into().map_err(|_| Error::Length {
+            expected: N,
+            found: self.len(),
+        })
+    }
+}
+
+/// Twiddle factors for one N
+pub struct Twiddles<const N: usize>;
+
+impl<const N: usize> Twiddles<N> {
+    /// e^(-2 pi i k/N) for k in 0..N, built at compile time
+    pub const TABLE: &'static [Complex32; N] = &table::<N>();
+}
+
+const fn table<const N: usize>() -> [Complex32; N] {
+    let mut table = [Complex32::new(0.0, 0.0); N];
+    let mut k = 0;
+    while k < N {
+        table[k] = float::twiddle(k as u64, N as u64);
+        k += 1;
+    }
+    table
+}
+
+/// A single bin X[k], k wraps around N
+pub fn dft_bin<T, S, const N: usize>(input: &S, k: usize) -> Result<Complex32, Error>
+where
+    T: Sample,
+    S: Samples<T, N> + ?Sized,
+{
+    Ok(bin(input.as_samples()?, k))
+}
+
+/// Every bin X[0] to X[N-1], each one worked out as its pulled from the
+/// iterator
+pub fn dft<'a, T, S, const N: usize>(
+    input: &'a S,
+) -> Result<impl Iterator<Item = Complex32> + Clone + 'a, Error>
+where
+    T: Sample + 'a,
+    S: Samples<T, N> + ?Sized,
+{
+    let x = input.as_samples()?;
+    Ok((0..N).map(move |k| bin(x, k)))
+}
+
+/// Every sample x[0] to x[N-1] back from a spectrum. dft is unscaled like
+/// cfft so OneOverN gets the original samples back, Unitary expects the
+/// spectrum to have been scaled by 1/sqrt(N) already.
+pub fn idft<'a, S, const N: usize>(
+    input: &'a S,
+    normalization: Normalization,
+) -> Result<impl Iterator<Item = Complex32> + Clone + 'a, Error>
+where
+    S: Samples<Complex32, N> + ?Sized,
+{
+    let x = input.as_samples()?;
+    let scale = normalization.inverse(N);
+
+    // conjugate twiddles are e^(+2 pi i k n/N)
+    Ok((0..N).map(move |n| {
+        let mut sum = Complex32::new(0.0, 0.0);
+        let mut index = 0;
+        for x in x.iter() {
+            sum += x * Twiddles::<N>::TABLE[index].conj();
+            index = (index + n) % N;
+        }
+        sum * scale
+    }))
+}
+
+fn bin<T: Sample, const N: usize>(x: &[T; N], k: usize) -> Complex32 {
+    let table = Twiddles::<N>::TABLE;
+    let k = k % N;
+
+    // kn mod N stepped along instead of multiplied so it cant overflow
+    let mut sum = Complex32::new(0.0, 0.0);
+    let mut index = 0;
+    for x in x.iter() {
+        sum += x.rotate(table[index]);
+        index = (index + k) % N;
+    }
+    sum
+}
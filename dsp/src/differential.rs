@@ -0,0 +1,372 @@
+//! The same input through two FFT backends, and why their outputs differ.
+//!
+//! Differential runs a transform, or the 4_13 style filter of an FFT, a
+//! multiply and an inverse FFT, with a reference backend and a test backend
+//! and keeps both outputs. Its Report has the SNR of one against the other,
+//! the error in every bin, and when theyre too far apart whether its one of
+//! the usual convention mistakes rather than bad arithmetic:
+//!
+//! - bit reversed, the output left in the order a radix-2 or radix-4 works
+//!   in, like arm_cfft_f32 with bitReverseFlag 0
+//! - scaled, out by a constant factor like N or sqrt(N), an inverse that
+//!   normalizes differently
+//!
+//! With no board CMSIS is CmsisModel, which follows what CMSIS documents, so
+//! this runs anywhere the tests do:
+//!
+//! ```ignore
+//! let outputs = Differential::fft::<Reference, CmsisModel>(&x);
+//! let report = outputs.report(80.0);
+//! assert!(report.agrees(), "{}", report);
+//! ```
+//!
+//! On the device with the `cmsis` feature Cmsis can be the test backend
+//! instead, the outputs are arrays so nothing needs allocating.
+
+use crate::backend::{bit_reverse_index, Fft};
+use crate::fft::Normalization;
+use crate::{float, Error};
+use core::fmt;
+use microfft::Complex32;
+
+/// Bins a Report lists before giving up
+const DIFF_ROWS: usize = 8;
+
+/// How close a scale factor has to be to N, 1/N, sqrt(N) or 1/sqrt(N) to be
+/// named as that convention
+const CONVENTION_TOLERANCE: f64 = 0.01;
+
+/// Output of a reference and a test backend for the same input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Differential<const N: usize> {
+    pub reference: [Complex32; N],
+    pub test: [Complex32; N],
+}
+
+impl<const N: usize> Differential<N> {
+    /// Forward FFT of input
+    pub fn fft<R: Fft<N>, T: Fft<N>>(input: &[Complex32; N]) -> Self {
+        let (mut reference, mut test) = (*input, *input);
+        R::fft(&mut reference);
+        T::fft(&mut test);
+        Self { reference, test }
+    }
+
+    /// Inverse FFT of input
+    pub fn ifft<R: Fft<N>, T: Fft<N>>(
+        input: &[Complex32; N],
+        normalization: Normalization,
+    ) -> Self {
+        let (mut reference, mut test) = (*input, *input);
+        R::ifft(&mut reference, normalization);
+        T::ifft(&mut test, normalization);
+        Self { reference, test }
+    }
+
+    /// Real FFT of input, unpacked and filled out to all N bins so its
+    /// compared the same as the others
+    pub fn rfft<R: Fft<N>, T: Fft<N>>(input: &[f32; N]) -> Self {
+        Self {
+            reference: real_spectrum::<R, N>(input),
+            test: real_spectrum::<T, N>(input),
+        }
+    }
+
+    /// Circular convolution of signal and impulse by multiplying their
+    /// spectra, the time domain output
+    pub fn filter<R: Fft<N>, T: Fft<N>>(signal: &[Complex32; N], impulse: &[Complex32; N]) -> Self {
+        Self {
+            reference: filter::<R, N>(signal, impulse),
+            test: filter::<T, N>(signal, impulse),
+        }
+    }
+
+    /// Compared, agreeing if the SNR is at least min_snr dB
+    pub fn report(&self, min_snr: f32) -> Report<'_> {
+        Report::new(&self.reference, &self.test, min_snr).unwrap()
+    }
+}
+
+/// A convention the test output follows that the reference doesnt
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mismatch {
+    /// bin k is where bin bit_reverse(k) should be
+    BitReversed,
+    /// every bin is factor times what it should be
+    Scaled { factor: f32 },
+    /// both at once
+    BitReversedAndScaled { factor: f32 },
+}
+
+/// How far test is from reference, bin by bin
+#[derive(Clone, Copy, Debug)]
+pub struct Report<'a> {
+    reference: &'a [Complex32],
+    test: &'a [Complex32],
+    min_snr: f32,
+    /// dB of reference over the difference, infinite when theres none
+    pub snr: f32,
+    /// largest |test - reference| of any bin
+    pub max_error: f32,
+    /// the bin max_error is in
+    pub worst: usize,
+    /// only when they dont agree, and only if a convention explains it
+    pub mismatch: Option<Mismatch>,
+}
+
+impl<'a> Report<'a> {
+    /// Compare any two equal length outputs, agreeing if the SNR is at least
+    /// min_snr dB
+    pub fn new(
+        reference: &'a [Complex32],
+        test: &'a [Complex32],
+        min_snr: f32,
+    ) -> Result<Self, Error> {
+        if reference.len() != test.len() {
+            return Err(Error::Length {
+                expected: reference.len(),
+                found: test.len(),
+            });
+        }
+
+        let mut report = Self {
+            reference,
+            test,
+            min_snr,
+            snr: snr(reference.iter().copied(), test),
+            max_error: 0.0,
+            worst: 0,
+            mismatch: None,
+        };
+        let (mut worst, mut max_error) = (0, 0.0f32);
+        for (k, error) in report.errors().enumerate() {
+            if !max_error.is_nan() && (error.is_nan() || error > max_error) {
+                worst = k;
+                max_error = error;
+            }
+        }
+        report.worst = worst;
+        report.max_error = max_error;
+        if !report.agrees() {
+            report.mismatch = explain(reference, test, min_snr);
+        }
+        Ok(report)
+    }
+
+    /// Whether the SNR is at least min_snr, never with a NaN
+    pub fn agrees(&self) -> bool {
+        self.snr >= self.min_snr
+    }
+
+    /// |test - reference| of every bin in order
+    pub fn errors(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        self.reference
+            .iter()
+            .zip(self.test.iter())
+            .map(|(r, t)| magnitude(t - r))
+    }
+
+    /// The errors mean square, bins with more than this are listed
+    fn mean_square_error(&self) -> f32 {
+        let total: f64 = self.errors().map(|e| e as f64 * e as f64).sum();
+        (total / self.reference.len().max(1) as f64) as f32
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} bins, snr {:.1}dB against {}dB wanted, max error {:e} in bin {}",
+            self.reference.len(),
+            self.snr,
+            self.min_snr,
+            self.max_error,
+            self.worst
+        )?;
+
+        let n = self.reference.len();
+        match self.mismatch {
+            Some(Mismatch::BitReversed) => write!(f, "\ntest is in bit reversed order")?,
+            Some(Mismatch::Scaled { factor }) => {
+                write!(f, "\ntest is scaled by {}", factor)?;
+                convention(f, factor, n)?;
+            }
+            Some(Mismatch::BitReversedAndScaled { factor }) => {
+                write!(
+                    f,
+                    "\ntest is in bit reversed order and scaled by {}",
+                    factor
+                )?;
+                convention(f, factor, n)?;
+            }
+            None => {}
+        }
+        if self.agrees() {
+            return Ok(());
+        }
+
+        // the bins with more than their share of the error
+        let mean = self.mean_square_error();
+        let mut out = self
+            .reference
+            .iter()
+            .zip(self.test.iter())
+            .zip(self.errors())
+            .enumerate()
+            .filter(|(_, (_, error))| error.is_nan() || error * error > mean);
+
+        write!(
+            f,
+            "\n{:>6} {:>28} {:>28} {:>12}",
+            "bin", "reference", "test", "error"
+        )?;
+        for (k, ((r, t), error)) in out.by_ref().take(DIFF_ROWS) {
+            write!(
+                f,
+                "\n{:>6} {:>13e} {:>13e}i {:>13e} {:>13e}i {:>12.3e}",
+                k, r.re, r.im, t.re, t.im, error
+            )?;
+        }
+        let more = out.count();
+        if more > 0 {
+            write!(f, "\n... and {} more", more)?;
+        }
+        Ok(())
+    }
+}
+
+/// Names a factor thats one of the usual normalizations
+fn convention(f: &mut fmt::Formatter, factor: f32, n: usize) -> fmt::Result {
+    let (factor, n) = (factor as f64, n as f64);
+    let root = float::sqrt(n);
+    let conventions = [
+        (n, "N"),
+        (1.0 / n, "1/N"),
+        (root, "sqrt(N)"),
+        (1.0 / root, "1/sqrt(N)"),
+    ];
+    for &(scale, name) in conventions.iter() {
+        if (factor / scale - 1.0).abs() < CONVENTION_TOLERANCE {
+            return write!(f, ", {} for N={}, a different normalization", name, n);
+        }
+    }
+    Ok(())
+}
+
+/// Try each convention on the reference, the first that brings it within
+/// min_snr of test is the mismatch
+fn explain(reference: &[Complex32], test: &[Complex32], min_snr: f32) -> Option<Mismatch> {
+    let n = reference.len();
+    // bit reversal does nothing to 2 or fewer bins
+    let reversible = n > 2 && n.is_power_of_two();
+
+    for &(reversed, scaled) in [(true, false), (false, true), (true, true)].iter() {
+        if reversed && !reversible {
+            continue;
+        }
+        let reordered = |k: usize| {
+            if reversed {
+                reference[bit_reverse_index(k, n)]
+            } else {
+                reference[k]
+            }
+        };
+
+        let factor = if scaled {
+            match least_squares_scale((0..n).map(reordered), test) {
+                Some(factor) => factor,
+                None => continue,
+            }
+        } else {
+            1.0
+        };
+
+        let candidate = (0..n).map(|k| reordered(k) * factor);
+        if snr(candidate, test) >= min_snr {
+            return Some(match (reversed, scaled) {
+                (true, false) => Mismatch::BitReversed,
+                (false, _) => Mismatch::Scaled { factor },
+                (true, true) => Mismatch::BitReversedAndScaled { factor },
+            });
+        }
+    }
+    None
+}
+
+/// The real factor c that makes c * reference closest to test, None if
+/// reference is all zeros
+fn least_squares_scale<I>(reference: I, test: &[Complex32]) -> Option<f32>
+where
+    I: Iterator<Item = Complex32>,
+{
+    let (mut cross, mut energy) = (0.0f64, 0.0f64);
+    for (r, t) in reference.zip(test.iter()) {
+        cross += r.re as f64 * t.re as f64 + r.im as f64 * t.im as f64;
+        energy += r.re as f64 * r.re as f64 + r.im as f64 * r.im as f64;
+    }
+    if energy == 0.0 {
+        return None;
+    }
+    Some((cross / energy) as f32)
+}
+
+/// Like metrics::snr but over complex bins
+fn snr<I>(reference: I, test: &[Complex32]) -> f32
+where
+    I: Iterator<Item = Complex32>,
+{
+    let (mut error, mut signal) = (0.0f64, 0.0f64);
+    for (r, t) in reference.zip(test.iter()) {
+        let (re, im) = (t.re as f64 - r.re as f64, t.im as f64 - r.im as f64);
+        error += re * re + im * im;
+        signal += r.re as f64 * r.re as f64 + r.im as f64 * r.im as f64;
+    }
+    if error == 0.0 {
+        return f32::INFINITY;
+    }
+    let ratio = signal / error;
+    if ratio.is_nan() {
+        return f32::NAN;
+    }
+    (10.0 * float::ln(ratio) / core::f64::consts::LN_10) as f32
+}
+
+fn magnitude(c: Complex32) -> f32 {
+    let square = c.re as f64 * c.re as f64 + c.im as f64 * c.im as f64;
+    if square.is_finite() {
+        float::sqrt(square) as f32
+    } else {
+        square as f32
+    }
+}
+
+fn real_spectrum<B: Fft<N>, const N: usize>(input: &[f32; N]) -> [Complex32; N] {
+    let mut buffer = *input;
+    let spectrum = B::rfft(&mut buffer);
+
+    // the top half is the bottom half mirrored, X[N-k] = conj(X[k])
+    let mut bins = [Complex32::new(0.0, 0.0); N];
+    for (k, bin) in spectrum.bins().enumerate() {
+        bins[k] = bin;
+        if k > 0 {
+            bins[N - k] = bin.conj();
+        }
+    }
+    bins
+}
+
+fn filter<B: Fft<N>, const N: usize>(
+    signal: &[Complex32; N],
+    impulse: &[Complex32; N],
+) -> [Complex32; N] {
+    let (mut signal, mut impulse) = (*signal, *impulse);
+    B::fft(&mut signal);
+    B::fft(&mut impulse);
+    signal
+        .iter_mut()
+        .zip(impulse.iter())
+        .for_each(|(s, h)| *s *= h);
+    B::ifft(&mut signal, Normalization::OneOverN);
+    signal
+}
@@ -0,0 +1,148 @@
+//! Discrete time Fourier series, putting a periodic signal back together from
+//! its DFT coefficients a harmonic at a time.
+//!
+//! x[n] = 1/N sum over k of X[k] e^(2 pi i k n/N)
+//!
+//! Harmonic k is X[k] and its conjugate partner X[N-k] together, the pair
+//! that makes one real cosine. Keeping the first few harmonics is a low pass
+//! approximation of the signal, and for anything with a jump in it like a
+//! square wave it rings either side of the jump. The ringing gets narrower
+//! with more harmonics but not lower, it settles at about 9% of the jump,
+//! the Gibbs phenomenon.
+//!
+//! The series is a smooth function so it can be evaluated between the
+//! samples too, which is where the overshoot hides once every harmonic is in
+//! and the samples themselves come back exactly.
+//!
+//! ```ignore
+//! let _ = cfft(&mut coefficients).unwrap();
+//! let series = Series::new(&coefficients);
+//! let y: heapless::Vec<f32, 16> = series.synthesize(3, 16).collect();
+//! ```
+
+use crate::float;
+use microfft::Complex32;
+
+/// Fourier series from the N coefficients of an N point DFT, unscaled like
+/// cfft gives them
+#[derive(Clone, Copy, Debug)]
+pub struct Series<'a> {
+    coefficients: &'a [Complex32],
+}
+
+/// How close a number of harmonics gets to the original
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Report {
+    pub harmonics: usize,
+    /// mean squared error at the original samples
+    pub mse: f32,
+    /// how far the series goes past the originals highest or lowest value,
+    /// as a fraction of the originals peak to peak
+    pub overshoot: f32,
+}
+
+impl<'a> Series<'a> {
+    pub fn new(coefficients: &'a [Complex32]) -> Self {
+        Self { coefficients }
+    }
+
+    /// N, samples in one period
+    pub fn period(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// N/2, where every coefficient is in and the samples come back exactly
+    pub fn max_harmonics(&self) -> usize {
+        self.period() / 2
+    }
+
+    /// The series at t samples from the start using DC and the first
+    /// harmonics, more than max_harmonics is the same as max_harmonics
+    pub fn at(&self, t: f32, harmonics: usize) -> f32 {
+        let n = self.period();
+        if n == 0 {
+            return 0.0;
+        }
+        let harmonics = harmonics.min(self.max_harmonics());
+
+        // e^(2 pi i t/N) multiplied up once per harmonic, rather than a sin
+        // and cos each
+        let (sin, cos) = float::sin_cos_turns(t as f64 / n as f64);
+        let step = Complex32::new(cos as f32, sin as f32);
+        let mut rotation = Complex32::new(1.0, 0.0);
+
+        let mut sum = self.coefficients[0].re;
+        for k in 1..=harmonics {
+            rotation *= step;
+            let (x, partner) = (self.coefficients[k], self.coefficients[n - k]);
+
+            // real part of X[k] e^(i theta) + X[N-k] e^(-i theta), or just
+            // the first when N is even and k is Nyquist, its own partner
+            sum += if k == n - k {
+                x.re * rotation.re - x.im * rotation.im
+            } else {
+                (x.re + partner.re) * rotation.re - (x.im - partner.im) * rotation.im
+            };
+        }
+
+        sum / n as f32
+    }
+
+    /// len points evenly spaced over one period. len of N is the series at
+    /// each original sample, more fills in between them.
+    pub fn synthesize(
+        &self,
+        harmonics: usize,
+        len: usize,
+    ) -> impl Iterator<Item = f32> + Clone + 'a {
+        let series = *self;
+        let spacing = self.period() as f32 / len as f32;
+        (0..len).map(move |i| series.at(i as f32 * spacing, harmonics))
+    }
+
+    /// Mean squared error against one period of the original, at however
+    /// many samples it has
+    pub fn mse(&self, harmonics: usize, original: &[f32]) -> f32 {
+        if original.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = self
+            .synthesize(harmonics, original.len())
+            .zip(original.iter())
+            .map(|(y, x)| (y - x) * (y - x))
+            .sum();
+        sum / original.len() as f32
+    }
+
+    /// Gibbs overshoot against one period of the original, evaluated at len
+    /// points so it can see between the samples. 0 if it never goes past
+    /// the original.
+    pub fn overshoot(&self, harmonics: usize, original: &[f32], len: usize) -> f32 {
+        let max = original.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let min = original.iter().copied().fold(f32::INFINITY, f32::min);
+        let range = max - min;
+        if range.is_nan() || range <= 0.0 {
+            return 0.0;
+        }
+
+        let (low, high) = self
+            .synthesize(harmonics, len)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), y| {
+                (low.min(y), high.max(y))
+            });
+        ((high - max).max(min - low) / range).max(0.0)
+    }
+
+    /// MSE and overshoot for every harmonic count from 0 to max_harmonics
+    pub fn convergence<'b>(
+        &'b self,
+        original: &'b [f32],
+        len: usize,
+    ) -> impl Iterator<Item = Report> + 'b {
+        (0..=self.max_harmonics()).map(move |harmonics| Report {
+            harmonics,
+            mse: self.mse(harmonics, original),
+            overshoot: self.overshoot(harmonics, original, len),
+        })
+    }
+}
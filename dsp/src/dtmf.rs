@@ -0,0 +1,310 @@
+//! DTMF, the tones a phone keypad makes, and decoding them with Goertzel.
+//!
+//! Each key is a row tone plus a column tone:
+//!
+//! |        | 1209 | 1336 | 1477 | 1633 |
+//! |--------|------|------|------|------|
+//! | 697    | 1    | 2    | 3    | A    |
+//! | 770    | 4    | 5    | 6    | B    |
+//! | 852    | 7    | 8    | 9    | C    |
+//! | 941    | *    | 0    | #    | D    |
+//!
+//! Theres only eight frequencies to listen for so a Goertzel bank is much
+//! cheaper than an FFT. A block counts as a key when:
+//!
+//! - theres enough energy in it to not be silence
+//! - one row and one column tone stand out from the rest of their group
+//! - those two tones are most of the blocks energy, so speech and noise with
+//!   a bit of energy in the right places dont count
+//! - the two tones are close enough in level, the twist. The column tone is
+//!   allowed to be further below the row tone than above it since phone
+//!   lines lose more at higher frequencies.
+//!
+//! ```ignore
+//! let mut decoder = Decoder::new(8000.0, 205, Thresholds::default());
+//! for x in samples {
+//!     if let Some(key) = decoder.push(x) {
+//!         rprintln!("{}", key.to_char());
+//!     }
+//! }
+//! ```
+
+use crate::float;
+use crate::goertzel::Bank;
+
+/// Row tones in Hz, top to bottom
+pub const ROWS: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+/// Column tones in Hz, left to right
+pub const COLUMNS: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    One,
+    Two,
+    Three,
+    A,
+    Four,
+    Five,
+    Six,
+    B,
+    Seven,
+    Eight,
+    Nine,
+    C,
+    Star,
+    Zero,
+    Hash,
+    D,
+}
+
+const KEYPAD: [[Key; 4]; 4] = [
+    [Key::One, Key::Two, Key::Three, Key::A],
+    [Key::Four, Key::Five, Key::Six, Key::B],
+    [Key::Seven, Key::Eight, Key::Nine, Key::C],
+    [Key::Star, Key::Zero, Key::Hash, Key::D],
+];
+
+const CHARACTERS: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+
+impl Key {
+    pub fn from_position(row: usize, column: usize) -> Option<Key> {
+        KEYPAD.get(row)?.get(column).copied()
+    }
+
+    /// Row and column on the keypad
+    pub fn position(self) -> (usize, usize) {
+        let index = self as usize;
+        (index / 4, index % 4)
+    }
+
+    /// Row and column tones in Hz
+    pub fn frequencies(self) -> (f32, f32) {
+        let (row, column) = self.position();
+        (ROWS[row], COLUMNS[column])
+    }
+
+    pub fn from_char(c: char) -> Option<Key> {
+        let c = c.to_ascii_uppercase();
+        (0..16)
+            .map(|i| (i / 4, i % 4))
+            .find(|&(row, column)| CHARACTERS[row][column] == c)
+            .and_then(|(row, column)| Key::from_position(row, column))
+    }
+
+    pub fn to_char(self) -> char {
+        let (row, column) = self.position();
+        CHARACTERS[row][column]
+    }
+
+    /// Samples of the keys tone forever, each of the two tones at amplitude
+    pub fn tone(self, sample_rate: f32, amplitude: f32) -> Tone {
+        let (row, column) = self.frequencies();
+        Tone {
+            row: row as f64 / sample_rate as f64,
+            column: column as f64 / sample_rate as f64,
+            amplitude,
+            n: 0,
+        }
+    }
+}
+
+/// Iterator of a keys samples, from Key::tone
+#[derive(Clone, Debug)]
+pub struct Tone {
+    /// turns per sample of each tone
+    row: f64,
+    column: f64,
+    amplitude: f32,
+    n: u64,
+}
+
+impl Iterator for Tone {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let n = self.n as f64;
+        self.n += 1;
+        let (row, _) = float::sin_cos_turns(self.row * n);
+        let (column, _) = float::sin_cos_turns(self.column * n);
+        Some(self.amplitude * (row + column) as f32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    /// mean square of the block below which its silence, in the input
+    /// units squared
+    pub min_energy: f32,
+    /// share of the blocks energy that has to be in the row and column
+    /// tones, 0 to 1
+    pub min_tone_ratio: f32,
+    /// how far the column tone can be below the row tone in dB
+    pub max_normal_twist_db: f32,
+    /// how far the column tone can be above the row tone in dB
+    pub max_reverse_twist_db: f32,
+    /// how far the strongest tone in each group has to be above the next
+    /// strongest in dB
+    pub min_group_margin_db: f32,
+}
+
+impl Default for Thresholds {
+    /// Twist limits from ITU-T Q.24, the rest tuned on the tests noisy
+    /// sequences
+    fn default() -> Self {
+        Self {
+            min_energy: 1e-4,
+            min_tone_ratio: 0.5,
+            max_normal_twist_db: 8.0,
+            max_reverse_twist_db: 4.0,
+            min_group_margin_db: 6.0,
+        }
+    }
+}
+
+/// Goertzel bank over the eight tones, rows then columns
+fn bank(sample_rate: f32) -> Bank<8> {
+    let mut frequencies = [0.0; 8];
+    frequencies[..4].copy_from_slice(&ROWS);
+    frequencies[4..].copy_from_slice(&COLUMNS);
+    Bank::new(frequencies, sample_rate)
+}
+
+/// The key in one block of samples, if there is one. Blocks of around 205
+/// samples at 8kHz are enough to tell the tones apart.
+pub fn detect(block: &[f32], sample_rate: f32, thresholds: &Thresholds) -> Option<Key> {
+    let mut bank = bank(sample_rate);
+    bank.process(block);
+    let energy = block.iter().map(|x| x * x).sum();
+    classify(&bank.powers(), energy, block.len(), thresholds)
+}
+
+/// powers from the bank, energy the sum of the blocks samples squared
+fn classify(powers: &[f32; 8], energy: f32, len: usize, thresholds: &Thresholds) -> Option<Key> {
+    if len == 0 || energy / (len as f32) < thresholds.min_energy {
+        return None;
+    }
+
+    let margin = ratio(thresholds.min_group_margin_db);
+    let (row, row_power) = strongest(&powers[..4], margin)?;
+    let (column, column_power) = strongest(&powers[4..], margin)?;
+
+    if row_power > column_power * ratio(thresholds.max_normal_twist_db)
+        || column_power > row_power * ratio(thresholds.max_reverse_twist_db)
+    {
+        return None;
+    }
+
+    // a tone of amplitude a over the whole block has |X|^2 = (a len / 2)^2
+    // and puts len a^2 / 2 into the energy
+    let tone_energy = 2.0 * (row_power + column_power) / len as f32;
+    if tone_energy < thresholds.min_tone_ratio * energy {
+        return None;
+    }
+
+    Key::from_position(row, column)
+}
+
+/// Index and power of the strongest tone if its margin times stronger than
+/// the others
+fn strongest(powers: &[f32], margin: f32) -> Option<(usize, f32)> {
+    let (index, &power) = powers
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+
+    let clear = powers
+        .iter()
+        .enumerate()
+        .all(|(i, &p)| i == index || power >= p * margin);
+    if clear {
+        Some((index, power))
+    } else {
+        None
+    }
+}
+
+/// dB to a power ratio
+fn ratio(db: f32) -> f32 {
+    float::exp(db as f64 * core::f64::consts::LN_10 / 10.0) as f32
+}
+
+/// Decodes a stream of samples a block at a time. A key is reported once
+/// when its seen in two blocks in a row, and again only after a block
+/// without it, so holding a key down doesnt repeat it.
+#[derive(Clone, Debug)]
+pub struct Decoder {
+    bank: Bank<8>,
+    block_len: usize,
+    thresholds: Thresholds,
+    energy: f32,
+    count: usize,
+    previous: Option<Key>,
+    reported: Option<Key>,
+}
+
+impl Decoder {
+    pub fn new(sample_rate: f32, block_len: usize, thresholds: Thresholds) -> Self {
+        assert!(block_len > 0, "block_len must be at least one sample");
+
+        Self {
+            bank: bank(sample_rate),
+            block_len,
+            thresholds,
+            energy: 0.0,
+            count: 0,
+            previous: None,
+            reported: None,
+        }
+    }
+
+    /// Returns a key on the sample that finishes the block confirming it
+    pub fn push(&mut self, x: f32) -> Option<Key> {
+        self.bank.push(x);
+        self.energy += x * x;
+        self.count += 1;
+        if self.count < self.block_len {
+            return None;
+        }
+
+        let key = classify(
+            &self.bank.powers(),
+            self.energy,
+            self.block_len,
+            &self.thresholds,
+        );
+        self.bank.reset();
+        self.energy = 0.0;
+        self.count = 0;
+
+        let confirmed = key.is_some() && key == self.previous && key != self.reported;
+        self.previous = key;
+        if key.is_none() {
+            self.reported = None;
+        }
+        if confirmed {
+            self.reported = key;
+            key
+        } else {
+            None
+        }
+    }
+
+    /// Feed a block of samples, reporting keys as they're confirmed
+    pub fn process<'a>(&'a mut self, samples: &'a [f32]) -> impl Iterator<Item = Key> + 'a {
+        samples.iter().filter_map(move |&x| self.push(x))
+    }
+
+    /// Forget any block in progress and the keys seen so far
+    pub fn reset(&mut self) {
+        self.bank.reset();
+        self.energy = 0.0;
+        self.count = 0;
+        self.previous = None;
+        self.reported = None;
+    }
+}
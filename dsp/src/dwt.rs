@@ -0,0 +1,724 @@
+//! Discrete wavelet transform.
+//!
+//! Where the FFT says which frequencies are in a block but not when, a
+//! wavelet transform splits the signal with a pair of half band filters, a
+//! low pass for the approximation and a high pass for the detail, each kept
+//! at every other sample. Doing it again on the approximation halves the band
+//! again, so after a few levels the slow drift is in a handful of coarse
+//! coefficients and a sudden bump shows up as a few big details right where
+//! it happened.
+//!
+//! cA[o] = sum over j of lo[j] x[2o + 1 - j]
+//! cD[o] = sum over j of hi[j] x[2o + 1 - j]
+//!
+//! The filters are the orthogonal families from pywt, Haar, Daubechies db2 to
+//! db8 and the symlets sym2 to sym8, the nearly symmetric Daubechies. The
+//! filters hang off the ends of the block so the samples past the ends come
+//! from an Extension. Every one but Periodization gives (N + L - 1) / 2
+//! coefficients per band, the same as pywt, and Periodization wraps round to
+//! give just half the samples. Either way the original comes back exactly.
+//!
+//! Small wavelet coefficients are mostly noise, so zeroing or shrinking the
+//! details below a threshold and transforming back is a cheap denoiser that
+//! keeps edges sharp, unlike a low pass filter.
+//!
+//! ```ignore
+//! let mut decomposition: Decomposition<300> =
+//!     Decomposition::new(&signal, Wavelet::Db4, Extension::Symmetric, 3).unwrap();
+//! decomposition.denoise(Thresholding::Soft);
+//! decomposition.reconstruct(&mut signal).unwrap();
+//! ```
+
+use crate::{float, Error};
+
+/// Most levels a Decomposition keeps track of, a 65536 sample signal with
+/// Haar
+pub const MAX_LEVELS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wavelet {
+    Haar,
+    Db2,
+    Db3,
+    Db4,
+    Db5,
+    Db6,
+    Db7,
+    Db8,
+    Sym2,
+    Sym3,
+    Sym4,
+    Sym5,
+    Sym6,
+    Sym7,
+    Sym8,
+}
+
+impl Wavelet {
+    /// Decomposition low pass filter, in pywt's dec_lo order
+    pub const fn filter(self) -> &'static [f32] {
+        match self {
+            Wavelet::Haar => &HAAR,
+            Wavelet::Db2 => &DB2,
+            Wavelet::Db3 => &DB3,
+            Wavelet::Db4 => &DB4,
+            Wavelet::Db5 => &DB5,
+            Wavelet::Db6 => &DB6,
+            Wavelet::Db7 => &DB7,
+            Wavelet::Db8 => &DB8,
+            Wavelet::Sym2 => &SYM2,
+            Wavelet::Sym3 => &SYM3,
+            Wavelet::Sym4 => &SYM4,
+            Wavelet::Sym5 => &SYM5,
+            Wavelet::Sym6 => &SYM6,
+            Wavelet::Sym7 => &SYM7,
+            Wavelet::Sym8 => &SYM8,
+        }
+    }
+
+    /// Filter length L, twice the number of vanishing moments
+    pub const fn filter_len(self) -> usize {
+        self.filter().len()
+    }
+
+    /// Tap j of the low pass decomposition filter
+    pub fn low(self, j: usize) -> f32 {
+        self.filter()[j]
+    }
+
+    /// Tap j of the high pass decomposition filter, the quadrature mirror of
+    /// the low pass, low pass backwards with every other sign flipped
+    pub fn high(self, j: usize) -> f32 {
+        let l = self.filter_len();
+        let tap = self.filter()[l - 1 - j];
+        if (l - 1 - j).is_multiple_of(2) {
+            tap
+        } else {
+            -tap
+        }
+    }
+
+    /// Deepest level worth going to for a signal of len samples, past it the
+    /// filter is longer than the approximation being split, same as pywt's
+    /// dwt_max_level
+    pub fn max_level(self, len: usize) -> usize {
+        let l = self.filter_len();
+        if len < l - 1 {
+            return 0;
+        }
+        // floor(log2(len / (l - 1)))
+        let mut level = 0;
+        while (len / (l - 1)) >> (level + 1) > 0 {
+            level += 1;
+        }
+        level
+    }
+}
+
+/// What the filters see past either end of the signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extension {
+    /// ... 0 0 | a b c d | 0 0 ...
+    Zero,
+    /// ... a a | a b c d | d d ...
+    Constant,
+    /// ... b a | a b c d | d c ..., mirrored with the end sample repeated
+    Symmetric,
+    /// ... c b | a b c d | c b ..., mirrored about the end sample
+    Reflect,
+    /// ... c d | a b c d | a b ...
+    Periodic,
+    /// Periodic but only half the samples in each band, an odd length has
+    /// its last sample repeated to make it even
+    Periodization,
+}
+
+impl Extension {
+    /// Coefficients in each band from a signal of len samples
+    pub const fn coefficients(self, len: usize, wavelet: Wavelet) -> usize {
+        match self {
+            Extension::Periodization => len.div_ceil(2),
+            _ => (len + wavelet.filter_len() - 1) / 2,
+        }
+    }
+
+    /// Sample i of the extended signal, i can be off either end
+    fn sample(self, signal: &[f32], i: isize) -> f32 {
+        let n = signal.len() as isize;
+        if n == 0 {
+            return 0.0;
+        }
+        if (0..n).contains(&i) {
+            return signal[i as usize];
+        }
+        let i = match self {
+            Extension::Zero => return 0.0,
+            Extension::Constant => i.clamp(0, n - 1),
+            Extension::Symmetric => {
+                let i = i.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+            Extension::Reflect if n == 1 => 0,
+            Extension::Reflect => {
+                let i = i.rem_euclid(2 * n - 2);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 2 - i
+                }
+            }
+            Extension::Periodic => i.rem_euclid(n),
+            Extension::Periodization => {
+                // past the repeated last sample of an odd length
+                let i = i.rem_euclid(n + n % 2);
+                i.min(n - 1)
+            }
+        };
+        signal[i as usize]
+    }
+}
+
+/// How Decomposition::threshold treats the details
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Thresholding {
+    /// anything under the threshold zeroed, the rest left alone
+    Hard,
+    /// anything under the threshold zeroed, the rest pulled towards zero by
+    /// it, no jump at the threshold so less ringing
+    Soft,
+}
+
+impl Thresholding {
+    pub fn apply(self, x: f32, threshold: f32) -> f32 {
+        if x.abs() <= threshold {
+            return 0.0;
+        }
+        match self {
+            Thresholding::Hard => x,
+            Thresholding::Soft if x > 0.0 => x - threshold,
+            Thresholding::Soft => x + threshold,
+        }
+    }
+}
+
+/// One level of the forward transform, an iterator of (approximation,
+/// detail) pairs
+#[derive(Clone, Debug)]
+pub struct Analysis<'a> {
+    signal: &'a [f32],
+    wavelet: Wavelet,
+    extension: Extension,
+    o: usize,
+    len: usize,
+}
+
+/// Split the signal into one level of approximation and detail
+pub fn dwt(signal: &[f32], wavelet: Wavelet, extension: Extension) -> Analysis<'_> {
+    Analysis {
+        signal,
+        wavelet,
+        extension,
+        o: 0,
+        len: extension.coefficients(signal.len(), wavelet),
+    }
+}
+
+impl Iterator for Analysis<'_> {
+    type Item = (f32, f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.o >= self.len {
+            return None;
+        }
+        let start = 2 * self.o as isize + 1;
+        self.o += 1;
+
+        Some(
+            (0..self.wavelet.filter_len()).fold((0.0, 0.0), |(a, d), j| {
+                let x = self.extension.sample(self.signal, start - j as isize);
+                (a + self.wavelet.low(j) * x, d + self.wavelet.high(j) * x)
+            }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.o;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Analysis<'_> {}
+
+/// One level of the inverse transform, an iterator of samples
+#[derive(Clone, Debug)]
+pub struct Synthesis<'a> {
+    approximation: &'a [f32],
+    detail: &'a [f32],
+    wavelet: Wavelet,
+    extension: Extension,
+    n: usize,
+    len: usize,
+}
+
+/// Put len samples back together from one level of approximation and
+/// detail. len is the length of the signal that went into dwt, the
+/// coefficient count alone cant tell odd from even.
+pub fn idwt<'a>(
+    approximation: &'a [f32],
+    detail: &'a [f32],
+    wavelet: Wavelet,
+    extension: Extension,
+    len: usize,
+) -> Result<Synthesis<'a>, Error> {
+    let expected = extension.coefficients(len, wavelet);
+    for band in [approximation, detail] {
+        if band.len() != expected {
+            return Err(Error::Length {
+                expected,
+                found: band.len(),
+            });
+        }
+    }
+
+    Ok(Synthesis {
+        approximation,
+        detail,
+        wavelet,
+        extension,
+        n: 0,
+        len,
+    })
+}
+
+impl Iterator for Synthesis<'_> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n >= self.len {
+            return None;
+        }
+        let n = self.n;
+        self.n += 1;
+
+        // the transpose of dwt, every coefficient whose filter covered x[n],
+        // that is 2o + 1 - j == n
+        let bands = self.approximation.len();
+        let sum = (0..self.wavelet.filter_len())
+            .filter(|j| (n + j + 1).is_multiple_of(2))
+            .filter_map(|j| {
+                let twice = n + j - 1;
+                let o = if self.extension == Extension::Periodization {
+                    // j can wrap more than once round a short signal
+                    Some((twice % (2 * bands)) / 2)
+                } else {
+                    Some(twice / 2).filter(|&o| o < bands)
+                };
+                o.map(|o| {
+                    self.wavelet.low(j) * self.approximation[o]
+                        + self.wavelet.high(j) * self.detail[o]
+                })
+            })
+            .sum();
+        Some(sum)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.n;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Synthesis<'_> {}
+
+/// A signal taken down several levels, stored pywt's wavedec way round in
+/// one fixed buffer of C coefficients
+///
+/// [ cA_J | cD_J | cD_J-1 | ... | cD_1 ]
+///
+/// Level 1 is the finest detail, the top half of the band, and level J the
+/// coarsest.
+#[derive(Clone, Debug)]
+pub struct Decomposition<const C: usize> {
+    coefficients: [f32; C],
+    /// the signal length then the approximation length at each level, the
+    /// details at a level are the same length as the approximation
+    lengths: [usize; MAX_LEVELS + 1],
+    levels: usize,
+    wavelet: Wavelet,
+    extension: Extension,
+}
+
+/// Coefficients a levels deep decomposition of len samples needs, what C
+/// has to be at least
+pub const fn decomposition_len(
+    len: usize,
+    wavelet: Wavelet,
+    extension: Extension,
+    levels: usize,
+) -> usize {
+    let mut total = 0;
+    let mut approximation = len;
+    let mut level = 0;
+    while level < levels {
+        approximation = extension.coefficients(approximation, wavelet);
+        total += approximation;
+        level += 1;
+    }
+    total + if levels == 0 { len } else { approximation }
+}
+
+impl<const C: usize> Decomposition<C> {
+    /// Transform the signal levels deep. Errors if C isnt enough for all
+    /// the coefficients, levels past MAX_LEVELS panic.
+    pub fn new(
+        signal: &[f32],
+        wavelet: Wavelet,
+        extension: Extension,
+        levels: usize,
+    ) -> Result<Self, Error> {
+        assert!(levels <= MAX_LEVELS, "at most {} levels", MAX_LEVELS);
+
+        let expected = decomposition_len(signal.len(), wavelet, extension, levels);
+        if expected > C {
+            return Err(Error::Length { expected, found: C });
+        }
+
+        let mut lengths = [0; MAX_LEVELS + 1];
+        lengths[0] = signal.len();
+        for level in 1..=levels {
+            lengths[level] = extension.coefficients(lengths[level - 1], wavelet);
+        }
+
+        let mut decomposition = Self {
+            coefficients: [0.0; C],
+            lengths,
+            levels,
+            wavelet,
+            extension,
+        };
+
+        // the approximation being split, every one is shorter than the
+        // signal except the first few of a very short signal, and those
+        // still fit in C
+        let mut approximation = [0.0; C];
+        approximation[..signal.len()].copy_from_slice(signal);
+
+        for level in 1..=levels {
+            let len = lengths[level];
+            let input = &approximation[..lengths[level - 1]];
+            let start = decomposition.detail_start(level);
+
+            // the new approximation parks at the front, theres always room
+            // before this levels details for it
+            let (front, back) = decomposition.coefficients.split_at_mut(start);
+            for (o, (a, d)) in dwt(input, wavelet, extension).enumerate() {
+                front[o] = a;
+                back[o] = d;
+            }
+            approximation[..len].copy_from_slice(&front[..len]);
+        }
+
+        if levels == 0 {
+            decomposition.coefficients[..signal.len()].copy_from_slice(signal);
+        }
+
+        Ok(decomposition)
+    }
+
+    pub fn wavelet(&self) -> Wavelet {
+        self.wavelet
+    }
+
+    pub fn extension(&self) -> Extension {
+        self.extension
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Length of the original signal
+    pub fn signal_len(&self) -> usize {
+        self.lengths[0]
+    }
+
+    /// Every coefficient in wavedec order
+    pub fn coefficients(&self) -> &[f32] {
+        let len = decomposition_len(self.lengths[0], self.wavelet, self.extension, self.levels);
+        &self.coefficients[..len]
+    }
+
+    /// The coarsest approximation, cA_J
+    pub fn approximation(&self) -> &[f32] {
+        &self.coefficients[..self.lengths[self.levels]]
+    }
+
+    pub fn approximation_mut(&mut self) -> &mut [f32] {
+        &mut self.coefficients[..self.lengths[self.levels]]
+    }
+
+    /// The details at a level, 1 the finest up to levels the coarsest. Panics
+    /// outside those.
+    pub fn detail(&self, level: usize) -> &[f32] {
+        let start = self.detail_start(level);
+        &self.coefficients[start..start + self.lengths[level]]
+    }
+
+    pub fn detail_mut(&mut self, level: usize) -> &mut [f32] {
+        let start = self.detail_start(level);
+        &mut self.coefficients[start..start + self.lengths[level]]
+    }
+
+    fn detail_start(&self, level: usize) -> usize {
+        assert!(
+            (1..=self.levels).contains(&level),
+            "level {} of {}",
+            level,
+            self.levels
+        );
+        self.lengths[self.levels] + self.lengths[level + 1..=self.levels].iter().sum::<usize>()
+    }
+
+    /// Transform back into output, which has to be as long as the original
+    /// signal
+    pub fn reconstruct(&self, output: &mut [f32]) -> Result<(), Error> {
+        if output.len() != self.signal_len() {
+            return Err(Error::Length {
+                expected: self.signal_len(),
+                found: output.len(),
+            });
+        }
+        if self.levels == 0 {
+            output.copy_from_slice(self.approximation());
+            return Ok(());
+        }
+
+        let mut approximation = [0.0; C];
+        approximation[..self.lengths[self.levels]].copy_from_slice(self.approximation());
+
+        for level in (1..=self.levels).rev() {
+            let len = self.lengths[level - 1];
+            let synthesis = idwt(
+                &approximation[..self.lengths[level]],
+                self.detail(level),
+                self.wavelet,
+                self.extension,
+                len,
+            )?;
+            output[..len]
+                .iter_mut()
+                .zip(synthesis)
+                .for_each(|(y, x)| *y = x);
+            approximation[..len].copy_from_slice(&output[..len]);
+        }
+
+        Ok(())
+    }
+
+    /// Threshold every detail coefficient, the approximation is left alone
+    pub fn threshold(&mut self, threshold: f32, thresholding: Thresholding) {
+        let start = self.lengths[self.levels];
+        let len = self.coefficients().len();
+        self.coefficients[start..len]
+            .iter_mut()
+            .for_each(|x| *x = thresholding.apply(*x, threshold));
+    }
+
+    /// Donoho and Johnstone's universal threshold, sigma sqrt(2 ln N). The
+    /// noise sigma is estimated from the finest details, which are nearly all
+    /// noise, as their median absolute value over 0.6745.
+    pub fn universal_threshold(&self) -> f32 {
+        if self.levels == 0 || self.signal_len() < 2 {
+            return 0.0;
+        }
+        let finest = self.detail(1);
+
+        let mut magnitudes = [0.0f32; C];
+        let magnitudes = &mut magnitudes[..finest.len()];
+        magnitudes
+            .iter_mut()
+            .zip(finest)
+            .for_each(|(m, x)| *m = x.abs());
+        magnitudes.sort_unstable_by(f32::total_cmp);
+
+        let middle = magnitudes.len() / 2;
+        let median = if magnitudes.len().is_multiple_of(2) {
+            (magnitudes[middle - 1] + magnitudes[middle]) / 2.0
+        } else {
+            magnitudes[middle]
+        };
+
+        let sigma = median as f64 / 0.6745;
+        (sigma * float::sqrt(2.0 * float::ln(self.signal_len() as f64))) as f32
+    }
+
+    /// Threshold the details at the universal threshold
+    pub fn denoise(&mut self, thresholding: Thresholding) {
+        let threshold = self.universal_threshold();
+        self.threshold(threshold, thresholding);
+    }
+}
+
+const HAAR: [f32; 2] = [0.70710677, 0.70710677];
+const DB2: [f32; 4] = [-0.12940952, 0.22414387, 0.8365163, 0.4829629];
+const DB3: [f32; 6] = [
+    0.035226292,
+    -0.08544128,
+    -0.13501102,
+    0.4598775,
+    0.8068915,
+    0.33267055,
+];
+const DB4: [f32; 8] = [
+    -0.010597402,
+    0.03288301,
+    0.030841382,
+    -0.18703481,
+    -0.02798377,
+    0.6308808,
+    0.71484657,
+    0.23037781,
+];
+const DB5: [f32; 10] = [
+    0.0033357253,
+    -0.012580752,
+    -0.00624149,
+    0.0775715,
+    -0.03224487,
+    -0.2422949,
+    0.13842815,
+    0.72430853,
+    0.60382927,
+    0.1601024,
+];
+const DB6: [f32; 12] = [
+    -0.0010773011,
+    0.0047772575,
+    0.0005538422,
+    -0.03158204,
+    0.027522866,
+    0.097501606,
+    -0.12976687,
+    -0.2262647,
+    0.31525035,
+    0.7511339,
+    0.4946239,
+    0.11154074,
+];
+const DB7: [f32; 14] = [
+    0.0003537138,
+    -0.0018016407,
+    0.00042957797,
+    0.0125509986,
+    -0.016574542,
+    -0.038029937,
+    0.08061261,
+    0.07130922,
+    -0.22403618,
+    -0.143906,
+    0.4697823,
+    0.7291321,
+    0.39653932,
+    0.077852054,
+];
+const DB8: [f32; 16] = [
+    -0.00011747678,
+    0.0006754494,
+    -0.00039174037,
+    -0.004870353,
+    0.008746094,
+    0.0139810279,
+    -0.044088254,
+    -0.0173693,
+    0.12874743,
+    0.00047248457,
+    -0.28401554,
+    -0.015829105,
+    0.5853547,
+    0.67563074,
+    0.3128716,
+    0.05441584,
+];
+const SYM2: [f32; 4] = [-0.12940952, 0.22414387, 0.8365163, 0.4829629];
+const SYM3: [f32; 6] = [
+    0.035226292,
+    -0.08544128,
+    -0.13501102,
+    0.4598775,
+    0.8068915,
+    0.33267055,
+];
+const SYM4: [f32; 8] = [
+    -0.075765715,
+    -0.029635528,
+    0.49761867,
+    0.8037388,
+    0.2978578,
+    -0.099219544,
+    -0.012603967,
+    0.0322231,
+];
+const SYM5: [f32; 10] = [
+    0.027333068,
+    0.02951949,
+    -0.03913425,
+    0.19939753,
+    0.7234077,
+    0.63397896,
+    0.016602106,
+    -0.17532809,
+    -0.021101834,
+    0.019538883,
+];
+const SYM6: [f32; 12] = [
+    0.015404109,
+    0.003490712,
+    -0.117990111,
+    -0.048311743,
+    0.49105594,
+    0.78764114,
+    0.33792942,
+    -0.07263752,
+    -0.021060292,
+    0.0447249,
+    0.0017677118,
+    -0.0078007083,
+];
+const SYM7: [f32; 14] = [
+    0.0026818146,
+    -0.0010473849,
+    -0.012636303,
+    0.030515513,
+    0.06789269,
+    -0.049552835,
+    0.017441256,
+    0.53610192,
+    0.76776432,
+    0.28862963,
+    -0.14004724,
+    -0.10780824,
+    0.0040102449,
+    0.010268177,
+];
+const SYM8: [f32; 16] = [
+    -0.003382416,
+    -0.00054213233,
+    0.031695088,
+    0.0076074873,
+    -0.14329424,
+    -0.06127336,
+    0.48135965,
+    0.77718575,
+    0.3644419,
+    -0.05194584,
+    -0.02721903,
+    0.04913718,
+    0.003808752,
+    -0.014952258,
+    -0.00030292053,
+    0.0018899504,
+];
@@ -0,0 +1,193 @@
+//! Safe wrappers around microfft.
+//!
+//! microfft only accepts arrays, which used to mean casting our heapless Vecs
+//! with `&mut *(v.as_mut_ptr() as *mut [Complex32; N])` in every example. That
+//! cast is unsound if the Vec isnt full. Instead anything implementing
+//! ComplexBuffer is checked to be exactly N long and handed over as an array,
+//! and the matching `cfft_N` is picked at compile time from N.
+//!
+//! ```ignore
+//! let mut dtfsecoef: heapless::Vec<Complex32, 16> = ...;
+//! let _ = dsp::fft::cfft(&mut dtfsecoef).unwrap();
+//! ```
+//!
+//! microfft has no inverse so icfft is built from the forward transform, with
+//! the 1/N split between the two directions chosen by Normalization.
+
+use crate::{float, Error};
+use core::convert::TryInto;
+use microfft::{complex, Complex32};
+
+/// Complex samples that can be viewed as an array of exactly N
+pub trait ComplexBuffer<const N: usize> {
+    fn as_array(&mut self) -> Result<&mut [Complex32; N], Error>;
+}
+
+impl<const N: usize> ComplexBuffer<N> for [Complex32; N] {
+    fn as_array(&mut self) -> Result<&mut [Complex32; N], Error> {
+        Ok(self)
+    }
+}
+
+impl<const N: usize> ComplexBuffer<N> for heapless::Vec<Complex32, N> {
+    fn as_array(&mut self) -> Result<&mut [Complex32; N], Error> {
+        let found = self.len();
+        (&mut self[..])
+            .try_into()
+            .map_err(|_| Error::Length { expected: N, found })
+    }
+}
+
+impl<const N: usize> ComplexBuffer<N> for [Complex32] {
+    fn as_array(&mut self) -> Result<&mut [Complex32; N], Error> {
+        let found = self.len();
+        self.try_into()
+            .map_err(|_| Error::Length { expected: N, found })
+    }
+}
+
+/// Lengths microfft has an implementation for, checked at compile time
+pub(crate) struct Supported<const N: usize>;
+
+impl<const N: usize> Supported<N> {
+    pub(crate) const CHECK: () = assert!(
+        N.is_power_of_two() && N >= 2 && N <= 4096,
+        "microfft only supports power of two lengths from 2 to 4096"
+    );
+}
+
+/// In place Radix-2 FFT. Arrays and heapless Vecs know their N, slices need it
+/// spelled out `cfft::<_, 512>(&mut slice)` and are checked at runtime.
+///
+/// Like microfft it re-returns our array in case we were going to chain
+/// calls.
+pub fn cfft<B, const N: usize>(buffer: &mut B) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+{
+    let () = Supported::<N>::CHECK;
+
+    let array = buffer.as_array()?;
+    dispatch(array);
+    Ok(array)
+}
+
+/// How the 1/N a forward and inverse transform pair needs is split between
+/// them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    /// forward unscaled and inverse scaled by 1/N, same as CMSIS arm_cfft_f32
+    /// with the ifftFlag set
+    OneOverN,
+    /// both directions scaled by 1/sqrt(N) so energy is the same in both
+    /// domains
+    Unitary,
+    /// neither direction scaled so a round trip comes back N times larger
+    None,
+}
+
+impl Normalization {
+    pub(crate) fn forward(self, n: usize) -> f32 {
+        match self {
+            Normalization::Unitary => one_over_sqrt(n),
+            Normalization::OneOverN | Normalization::None => 1.0,
+        }
+    }
+
+    pub(crate) fn inverse(self, n: usize) -> f32 {
+        match self {
+            Normalization::OneOverN => 1.0 / n as f32,
+            Normalization::Unitary => one_over_sqrt(n),
+            Normalization::None => 1.0,
+        }
+    }
+}
+
+fn one_over_sqrt(n: usize) -> f32 {
+    (1.0 / float::sqrt(n as f64)) as f32
+}
+
+/// cfft scaled to pair with icfft under the same normalization. Only Unitary
+/// actually scales the forward direction.
+pub fn cfft_normalized<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+{
+    let array = cfft(buffer)?;
+
+    let scale = normalization.forward(N);
+    if scale != 1.0 {
+        array.iter_mut().for_each(|c| *c *= scale);
+    }
+
+    Ok(array)
+}
+
+/// In place inverse FFT. The inverse DFT is the same as the DFT but with the
+/// opposite sign in the exponent, so conjugating before and after the forward
+/// transform gets us there.
+pub fn icfft<B, const N: usize>(
+    buffer: &mut B,
+    normalization: Normalization,
+) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+{
+    let array = buffer.as_array()?;
+    array.iter_mut().for_each(|c| c.im = -c.im);
+
+    let array = cfft(array)?;
+
+    let scale = normalization.inverse(N);
+    array.iter_mut().for_each(|c| {
+        c.re *= scale;
+        c.im *= -scale;
+    });
+
+    Ok(array)
+}
+
+/// icfft for a spectrum that came from a real signal, the imaginary parts
+/// left are only rounding error so just the real parts are returned
+pub fn icfft_real<'a, B, const N: usize>(
+    buffer: &'a mut B,
+    normalization: Normalization,
+) -> Result<impl Iterator<Item = f32> + Clone + 'a, Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+{
+    Ok(icfft(buffer, normalization)?.iter().map(|c| c.re))
+}
+
+macro_rules! dispatch {
+    ($($n:literal => $cfft:path,)*) => {
+        fn dispatch<const N: usize>(array: &mut [Complex32; N]) {
+            // N is known at compile time so only one arm survives and the
+            // conversion cant fail
+            match N {
+                $($n => {
+                    let _ = $cfft((&mut array[..]).try_into().unwrap());
+                })*
+                _ => unreachable!(),
+            }
+        }
+    };
+}
+
+dispatch! {
+    2 => complex::cfft_2,
+    4 => complex::cfft_4,
+    8 => complex::cfft_8,
+    16 => complex::cfft_16,
+    32 => complex::cfft_32,
+    64 => complex::cfft_64,
+    128 => complex::cfft_128,
+    256 => complex::cfft_256,
+    512 => complex::cfft_512,
+    1024 => complex::cfft_1024,
+    2048 => complex::cfft_2048,
+    4096 => complex::cfft_4096,
+}
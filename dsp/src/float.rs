@@ -0,0 +1,139 @@
+//! Accurate float helpers for building tables.
+//!
+//! On device sin, cos and sqrt come from micromath which is only good to a
+//! couple of decimal places, fine for a plot but not for twiddle factors that
+//! get multiplied in thousands of times. These work in f64 with nothing but
+//! core so tables come out right to the last bit of an f32 either way, and
+//! twiddle is a const fn so tables can be built by the compiler.
+
+use microfft::Complex32;
+
+/// e^(-2 pi i k/n), the kth of n twiddle factors
+pub(crate) const fn twiddle(k: u64, n: u64) -> Complex32 {
+    let k = k % n;
+
+    // split the angle into whole quarter turns and whats left over so the
+    // series only ever sees 0..pi/4
+    let quarter = 4 * k / n;
+    let rem = 4 * k - quarter * n;
+    let (sin, cos) = if 2 * rem <= n {
+        sin_cos(rem as f64 / n as f64 * core::f64::consts::FRAC_PI_2)
+    } else {
+        let (sin, cos) = sin_cos((n - rem) as f64 / n as f64 * core::f64::consts::FRAC_PI_2);
+        (cos, sin)
+    };
+    let (sin, cos) = rotate(quarter, sin, cos);
+
+    Complex32::new(cos as f32, -sin as f32)
+}
+
+/// sin and cos of an angle given in turns, 1.0 is all the way round
+pub(crate) fn sin_cos_turns(turns: f64) -> (f64, f64) {
+    let mut turns = turns - (turns as i64) as f64;
+    if turns < 0.0 {
+        turns += 1.0;
+    }
+
+    let quarters = 4.0 * turns;
+    let quarter = quarters as u64;
+    let rem = quarters - quarter as f64;
+    let (sin, cos) = if rem <= 0.5 {
+        sin_cos(rem * core::f64::consts::FRAC_PI_2)
+    } else {
+        let (sin, cos) = sin_cos((1.0 - rem) * core::f64::consts::FRAC_PI_2);
+        (cos, sin)
+    };
+    rotate(quarter, sin, cos)
+}
+
+/// sin and cos of an angle a whole number of quarter turns further round
+const fn rotate(quarter: u64, sin: f64, cos: f64) -> (f64, f64) {
+    match quarter % 4 {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    }
+}
+
+/// Taylor series, plenty for |x| <= pi/4
+const fn sin_cos(x: f64) -> (f64, f64) {
+    let x2 = x * x;
+    let mut sin = 0.0;
+    let mut cos = 0.0;
+    // highest term first, 1/(2k+1)! and 1/(2k)!
+    let mut k = 9;
+    while k > 0 {
+        k -= 1;
+        let k = k as f64;
+        sin = 1.0 - sin * x2 / ((2.0 * k + 2.0) * (2.0 * k + 3.0));
+        cos = 1.0 - cos * x2 / ((2.0 * k + 1.0) * (2.0 * k + 2.0));
+    }
+    (sin * x, cos)
+}
+
+/// Newton's method from above, stops once it cant get any smaller
+pub(crate) fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let mut y = if x > 1.0 { x } else { 1.0 };
+    loop {
+        let next = 0.5 * (y + x / y);
+        if next >= y {
+            return y;
+        }
+        y = next;
+    }
+}
+
+/// e^x, split into 2^k e^r so the series only sees |r| <= ln(2)/2
+pub(crate) fn exp(x: f64) -> f64 {
+    if x < -700.0 {
+        return 0.0;
+    }
+    if x > 700.0 {
+        return f64::INFINITY;
+    }
+
+    let k = x / core::f64::consts::LN_2;
+    let k = if k < 0.0 { k - 0.5 } else { k + 0.5 } as i64;
+    let r = x - k as f64 * core::f64::consts::LN_2;
+
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for n in 1..20 {
+        term *= r / n as f64;
+        sum += term;
+    }
+
+    sum * f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+/// Natural log, the exponent bits give us the powers of two and the series
+/// in s = (m-1)/(m+1) handles the mantissa m
+pub(crate) fn ln(x: f64) -> f64 {
+    if x <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if m > core::f64::consts::SQRT_2 {
+        m /= 2.0;
+        exponent += 1;
+    }
+
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let mut sum = 0.0;
+    let mut power = s;
+    for k in 0..20 {
+        sum += power / (2 * k + 1) as f64;
+        power *= s2;
+    }
+
+    exponent as f64 * core::f64::consts::LN_2 + 2.0 * sum
+}
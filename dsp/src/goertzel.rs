@@ -0,0 +1,145 @@
+//! Single DFT bins with the Goertzel algorithm.
+//!
+//! When only a handful of frequencies matter, like the two tones of a DTMF
+//! key, a whole FFT is mostly wasted. Goertzel gets one bin from a second
+//! order resonator, one multiply and two adds per sample, a sample at a time
+//! as they arrive so nothing needs buffering.
+//!
+//! s[n] = x[n] + 2 cos(w) s[n-1] - s[n-2]
+//!
+//! After N samples the DTFT of those samples at w comes out of the last two
+//! values of s. w doesnt have to land on a bin of an N point DFT, so
+//! with_frequency is the generalized Goertzel of Sysel and Rajmic, "Goertzel
+//! algorithm generalized to non-integer multiples of fundamental frequency",
+//! EURASIP 2012, phase and all.
+//!
+//! ```ignore
+//! let mut goertzel = Goertzel::with_frequency(697.0, 8000.0);
+//! goertzel.process(&block);
+//! let power = goertzel.power();
+//! ```
+
+use crate::float;
+use microfft::Complex32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Goertzel {
+    /// frequency in turns per sample, w / 2 pi
+    turns: f64,
+    /// 2 cos(w)
+    coefficient: f32,
+    /// e^(-i w)
+    rotation: Complex32,
+    s1: f32,
+    s2: f32,
+    count: usize,
+}
+
+impl Goertzel {
+    /// Bin k of an N point DFT, matches X[k] of the FFT once N samples are
+    /// in
+    pub fn new(bin: usize, len: usize) -> Self {
+        Self::with_turns(bin as f64 / len as f64)
+    }
+
+    /// Any frequency in Hz, it doesnt have to be a whole number of cycles per
+    /// block
+    pub fn with_frequency(frequency: f32, sample_rate: f32) -> Self {
+        Self::with_turns(frequency as f64 / sample_rate as f64)
+    }
+
+    fn with_turns(turns: f64) -> Self {
+        let (sin, cos) = float::sin_cos_turns(turns);
+        Self {
+            turns,
+            coefficient: (2.0 * cos) as f32,
+            rotation: Complex32::new(cos as f32, -sin as f32),
+            s1: 0.0,
+            s2: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn push(&mut self, x: f32) {
+        let s = x + self.coefficient * self.s1 - self.s2;
+        self.s2 = self.s1;
+        self.s1 = s;
+        self.count += 1;
+    }
+
+    pub fn process(&mut self, samples: &[f32]) {
+        samples.iter().for_each(|&x| self.push(x));
+    }
+
+    /// Start a new block
+    pub fn reset(&mut self) {
+        self.s1 = 0.0;
+        self.s2 = 0.0;
+        self.count = 0;
+    }
+
+    /// Samples pushed since the last reset
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// |X|^2 of the samples so far, cheaper than bin since it doesnt need
+    /// the phase. Near DC and Nyquist s1 and s2 grow large and nearly cancel,
+    /// so small bins there lose a few digits.
+    pub fn power(&self) -> f32 {
+        self.s1 * self.s1 + self.s2 * self.s2 - self.coefficient * self.s1 * self.s2
+    }
+
+    /// X at this frequency, sum of x[n] e^(-i w n) over the samples so far
+    pub fn bin(&self) -> Complex32 {
+        // s1 - e^(-iw) s2 is the sum with the phase measured from the last
+        // sample, turn it back to be measured from the first
+        let y = Complex32::new(self.s1, 0.0) - self.rotation * self.s2;
+        let turns = -self.turns * self.count.saturating_sub(1) as f64;
+        let (sin, cos) = float::sin_cos_turns(turns);
+        y * Complex32::new(cos as f32, sin as f32)
+    }
+}
+
+/// Goertzel detectors for K frequencies all fed the same samples
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bank<const K: usize> {
+    detectors: [Goertzel; K],
+}
+
+impl<const K: usize> Bank<K> {
+    pub fn new(frequencies: [f32; K], sample_rate: f32) -> Self {
+        let mut detectors = [Goertzel::with_turns(0.0); K];
+        detectors
+            .iter_mut()
+            .zip(frequencies.iter())
+            .for_each(|(d, &f)| *d = Goertzel::with_frequency(f, sample_rate));
+        Self { detectors }
+    }
+
+    pub fn push(&mut self, x: f32) {
+        self.detectors.iter_mut().for_each(|d| d.push(x));
+    }
+
+    pub fn process(&mut self, samples: &[f32]) {
+        samples.iter().for_each(|&x| self.push(x));
+    }
+
+    pub fn reset(&mut self) {
+        self.detectors.iter_mut().for_each(|d| d.reset());
+    }
+
+    /// |X|^2 at each frequency, in the order they were given
+    pub fn powers(&self) -> [f32; K] {
+        let mut powers = [0.0; K];
+        powers
+            .iter_mut()
+            .zip(self.detectors.iter())
+            .for_each(|(p, d)| *p = d.power());
+        powers
+    }
+
+    pub fn detectors(&self) -> &[Goertzel; K] {
+        &self.detectors
+    }
+}
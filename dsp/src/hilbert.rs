@@ -0,0 +1,209 @@
+//! Hilbert transform and the analytic signal.
+//!
+//! A real signal has every frequency twice, once positive and once
+//! negative. Throwing the negative half away leaves the analytic signal
+//!
+//! z[n] = x[n] + i H{x}[n]
+//!
+//! where H is the Hilbert transform, x shifted a quarter turn at every
+//! frequency. For a tone a cos(phi[n]) thats a e^(i phi[n]), so how loud it
+//! is and where it is in its cycle can be read off sample by sample:
+//!
+//! - the envelope a is |z|
+//! - the instantaneous phase phi is arg z, unwrapped so it keeps counting
+//!   past pi
+//! - the instantaneous frequency is how fast phi turns, which for the chirp
+//!   in 4_10 is a straight line from DC to Nyquist where the spectrogram
+//!   only shows it a frame at a time
+//!
+//! analytic does it to a whole block with an FFT, zeroing the negative bins
+//! and doubling the positive ones. The block is treated as periodic so the
+//! ends are a bit off when it isnt. Hilbert does it a sample at a time with
+//! an FIR filter, every sample coming out (TAPS-1)/2 late so the real part is
+//! delayed to match. Its an ideal quarter turn only away from DC and Nyquist,
+//! so more taps pushes how low it works down.
+//!
+//! ```ignore
+//! analytic(&mut buffer).unwrap();
+//! let frequency: heapless::Vec<f32, 1023> =
+//!     instantaneous_frequency(buffer.iter().copied(), 100.0).collect();
+//! ```
+
+use crate::complex::ComplexExt;
+use crate::fft::{cfft, icfft, ComplexBuffer, Normalization, Supported};
+use crate::window::{Symmetry, Window};
+use crate::Error;
+use core::f32::consts::PI;
+use microfft::Complex32;
+
+/// In place analytic signal of the real parts, the imaginary parts going in
+/// are ignored and come out as the Hilbert transform
+pub fn analytic<B, const N: usize>(buffer: &mut B) -> Result<&mut [Complex32; N], Error>
+where
+    B: ComplexBuffer<N> + ?Sized,
+{
+    let () = Supported::<N>::CHECK;
+
+    let array = buffer.as_array()?;
+    array.iter_mut().for_each(|c| c.im = 0.0);
+    let array = cfft(array)?;
+
+    // DC and Nyquist are their own negative frequencies so are left alone,
+    // the rest of the positive half doubled to make up for the negative half
+    // going
+    for (k, c) in array.iter_mut().enumerate().skip(1) {
+        if k < N / 2 {
+            *c *= 2.0;
+        } else if k > N / 2 {
+            *c = Complex32::new(0.0, 0.0);
+        }
+    }
+
+    icfft(array, Normalization::OneOverN)
+}
+
+/// Streaming FIR Hilbert transformer
+#[derive(Clone, Debug)]
+pub struct Hilbert<const TAPS: usize> {
+    /// taps at odd distances from the middle, the even ones are all 0. Tap
+    /// j is 2j + 1 samples from the middle and is negated on the far side,
+    /// so only the first quarter or so is used.
+    taps: [f32; TAPS],
+    /// the last TAPS samples, the oldest at position
+    history: [f32; TAPS],
+    position: usize,
+}
+
+impl<const TAPS: usize> Hilbert<TAPS> {
+    /// Ideal Hilbert taps 2/(pi m) at odd m from the middle tapered by
+    /// window. TAPS has to be odd so theres a middle sample to line the real
+    /// part up with.
+    pub fn new(window: Window) -> Self {
+        assert!(TAPS % 2 == 1, "TAPS has to be odd");
+
+        let middle = TAPS / 2;
+        let mut taps = [0.0; TAPS];
+        for (j, tap) in taps.iter_mut().enumerate().take(middle.div_ceil(2)) {
+            let m = 2 * j + 1;
+            let w = window.coefficient(middle + m, TAPS, Symmetry::Symmetric);
+            *tap = 2.0 / (PI * m as f32) * w;
+        }
+
+        Self {
+            taps,
+            history: [0.0; TAPS],
+            position: 0,
+        }
+    }
+
+    /// Samples every output is behind its input
+    pub fn delay(&self) -> usize {
+        TAPS / 2
+    }
+
+    /// Add a sample, returns the analytic signal delay samples ago. The
+    /// first TAPS outputs are still filling up.
+    pub fn push(&mut self, x: f32) -> Complex32 {
+        self.history[self.position] = x;
+        self.position = (self.position + 1) % TAPS;
+
+        // newest sample is at position - 1, the middle delay before that
+        let at = |back: usize| self.history[(self.position + 2 * TAPS - 1 - back) % TAPS];
+        let middle = TAPS / 2;
+
+        let mut sum = 0.0;
+        for (j, tap) in self.taps.iter().enumerate().take(middle.div_ceil(2)) {
+            let m = 2 * j + 1;
+            // h[-m] is -h[m], so older minus newer
+            sum += tap * (at(middle + m) - at(middle - m));
+        }
+
+        Complex32::new(at(middle), sum)
+    }
+
+    /// push every sample and write each output, input and output the same
+    /// length
+    pub fn process(&mut self, input: &[f32], output: &mut [Complex32]) -> Result<(), Error> {
+        if input.len() != output.len() {
+            return Err(Error::Length {
+                expected: input.len(),
+                found: output.len(),
+            });
+        }
+        for (x, y) in input.iter().zip(output.iter_mut()) {
+            *y = self.push(*x);
+        }
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.history = [0.0; TAPS];
+        self.position = 0;
+    }
+}
+
+/// Keeps a phase counting past pi rather than jumping back to -pi
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Unwrap {
+    previous: Option<f32>,
+    /// whole turns added so far, in radians
+    offset: f32,
+}
+
+impl Unwrap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Next phase in -pi..=pi, returns it with however many turns it takes to
+    /// be within pi of the last one
+    pub fn push(&mut self, phase: f32) -> f32 {
+        if let Some(previous) = self.previous {
+            let step = phase - previous;
+            if step > PI {
+                self.offset -= 2.0 * PI;
+            } else if step < -PI {
+                self.offset += 2.0 * PI;
+            }
+        }
+        self.previous = Some(phase);
+        phase + self.offset
+    }
+}
+
+/// |z| of each sample
+pub fn envelope<I>(analytic: I) -> impl Iterator<Item = f32> + Clone
+where
+    I: IntoIterator<Item = Complex32>,
+    I::IntoIter: Clone,
+{
+    analytic.into_iter().map(|z| z.magnitude())
+}
+
+/// Unwrapped arg z of each sample in radians
+pub fn instantaneous_phase<I>(analytic: I) -> impl Iterator<Item = f32> + Clone
+where
+    I: IntoIterator<Item = Complex32>,
+    I::IntoIter: Clone,
+{
+    let mut unwrap = Unwrap::new();
+    analytic.into_iter().map(move |z| unwrap.push(z.arg()))
+}
+
+/// How fast the phase turns between each pair of samples in Hz, one fewer
+/// than the samples. Its the angle of z[n] conj(z[n-1]) rather than the
+/// difference of the unwrapped phase so nothing adds up.
+pub fn instantaneous_frequency<I>(
+    analytic: I,
+    sample_rate: f32,
+) -> impl Iterator<Item = f32> + Clone
+where
+    I: IntoIterator<Item = Complex32>,
+    I::IntoIter: Clone,
+{
+    let analytic = analytic.into_iter();
+    analytic
+        .clone()
+        .zip(analytic.skip(1))
+        .map(move |(previous, z)| (z * previous.conj()).arg() * sample_rate / (2.0 * PI))
+}
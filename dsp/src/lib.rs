@@ -0,0 +1,38 @@
+//! Signal processing shared between the lab firmware and the native examples.
+//!
+//! Everything in here has to run on the device so it stays no_std and never
+//! allocates, buffers are arrays, slices or heapless Vecs owned by the caller.
+//! Float functions come from micromath unless the `std` feature is on, so
+//! expect a little more rounding on device than in the native examples.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod backend;
+pub mod complex;
+pub mod czt;
+pub mod dct;
+pub mod dft;
+pub mod differential;
+pub mod dtfse;
+pub mod dtmf;
+pub mod dwt;
+pub mod fft;
+mod float;
+pub mod goertzel;
+pub mod hilbert;
+pub mod metrics;
+pub mod peaks;
+pub mod plan;
+pub mod psd;
+pub mod rfft;
+pub mod sdft;
+pub mod stft;
+pub mod window;
+pub mod zoom;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// the buffer isnt the length the transform was asked for, most likely a
+    /// heapless Vec that hasnt been filled
+    Length { expected: usize, found: usize },
+}
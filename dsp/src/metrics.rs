@@ -0,0 +1,380 @@
+//! How far one sequence is from another.
+//!
+//! Everything takes a reference, what the answer should be, and a test
+//! sequence to hold up against it, the native output against the device, f32
+//! against fixed point, microfft against CMSIS. Sums are done in f64 so a long
+//! sequence doesnt lose the small differences being measured.
+//!
+//! - mse and rmse, the average size of the error
+//! - snr, reference energy over error energy in dB. With the test sequence
+//!   the quantized reference thats the SQNR, roughly 6dB per bit kept
+//! - max_absolute_error and max_relative_error, the worst single value
+//! - ulps, how many f32s apart two values are, so 1 is as close as two
+//!   different floats can be whatever their size
+//! - correlation, whether they go up and down together regardless of scale
+//!
+//! The assert functions are for tests, on failure they panic with every
+//! metric and the first values that are out, rather than just the worst one.
+//!
+//! ```ignore
+//! let snr = metrics::snr(&native, &device).unwrap();
+//! metrics::assert_close(&expected, &found, 1e-4);
+//! ```
+
+use crate::{float, Error};
+use core::fmt;
+
+/// Different values that are out of tolerance an assert lists before
+/// giving up
+const DIFF_ROWS: usize = 8;
+
+/// Every metric at once, printed by the asserts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    pub len: usize,
+    pub mse: f32,
+    pub rmse: f32,
+    /// dB, infinite when theres no error at all
+    pub snr: f32,
+    pub max_absolute: f32,
+    /// where max_absolute is
+    pub worst: usize,
+    pub max_relative: f32,
+    pub max_ulps: u32,
+    /// NaN if either is constant
+    pub correlation: f32,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} values, mse {:e}, rmse {:e}, snr {:.1}dB, max error {:e} at {}, \
+             max relative {:e}, max ulps {}, correlation {}",
+            self.len,
+            self.mse,
+            self.rmse,
+            self.snr,
+            self.max_absolute,
+            self.worst,
+            self.max_relative,
+            self.max_ulps,
+            self.correlation
+        )
+    }
+}
+
+/// Every metric of test against reference
+pub fn compare(reference: &[f32], test: &[f32]) -> Result<Comparison, Error> {
+    let (worst, max_absolute) = worst(reference, test)?;
+    let mse = mse(reference, test)?;
+    Ok(Comparison {
+        len: reference.len(),
+        mse,
+        rmse: root(mse as f64),
+        snr: snr(reference, test)?,
+        max_absolute,
+        worst,
+        max_relative: max_relative_error(reference, test)?,
+        max_ulps: max_ulps(reference, test)?,
+        correlation: correlation(reference, test)?,
+    })
+}
+
+/// Mean squared error, 0 for empty sequences
+pub fn mse(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    let (error, _) = energies(reference, test)?;
+    if reference.is_empty() {
+        return Ok(0.0);
+    }
+    Ok((error / reference.len() as f64) as f32)
+}
+
+/// Root mean squared error, in the same units as the sequences
+pub fn rmse(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    Ok(root(mse(reference, test)? as f64))
+}
+
+/// 10 log10 of reference energy over error energy. Infinite when theyre the
+/// same, SQNR when test is reference quantized.
+pub fn snr(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    let (error, signal) = energies(reference, test)?;
+    if error == 0.0 {
+        return Ok(f32::INFINITY);
+    }
+    let ratio = signal / error;
+    if ratio.is_nan() {
+        return Ok(f32::NAN);
+    }
+    Ok((10.0 * float::ln(ratio) / core::f64::consts::LN_10) as f32)
+}
+
+/// Largest |test - reference|, NaN if any value is
+pub fn max_absolute_error(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    Ok(worst(reference, test)?.1)
+}
+
+/// Largest |test - reference| / |reference|. A zero reference only matches
+/// a zero, anything else is infinitely far off.
+pub fn max_relative_error(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    check(reference, test)?;
+    let mut max = 0.0f32;
+    for (&r, &t) in reference.iter().zip(test.iter()) {
+        let error = if r == 0.0 {
+            if t == 0.0 {
+                0.0
+            } else {
+                f32::INFINITY
+            }
+        } else {
+            ((t as f64 - r as f64).abs() / (r as f64).abs()) as f32
+        };
+        max = larger(max, error);
+    }
+    Ok(max)
+}
+
+/// Floats between a and b, 0 if theyre equal including 0 and -0, u32::MAX
+/// if either is NaN
+pub fn ulps(a: f32, b: f32) -> u32 {
+    if a.is_nan() || b.is_nan() {
+        return u32::MAX;
+    }
+    (ordered(a) - ordered(b)).unsigned_abs() as u32
+}
+
+/// Largest ulps between each pair of values
+pub fn max_ulps(reference: &[f32], test: &[f32]) -> Result<u32, Error> {
+    check(reference, test)?;
+    Ok(reference
+        .iter()
+        .zip(test.iter())
+        .map(|(&r, &t)| ulps(r, t))
+        .max()
+        .unwrap_or(0))
+}
+
+/// Pearson correlation coefficient, 1 when test is reference scaled up and
+/// shifted, -1 when its upside down. NaN if either is constant, theres
+/// nothing to correlate.
+pub fn correlation(reference: &[f32], test: &[f32]) -> Result<f32, Error> {
+    check(reference, test)?;
+    let len = reference.len() as f64;
+    let mean = |x: &[f32]| x.iter().map(|&x| x as f64).sum::<f64>() / len;
+    let (mean_r, mean_t) = (mean(reference), mean(test));
+
+    let (mut rr, mut tt, mut rt) = (0.0, 0.0, 0.0);
+    for (&r, &t) in reference.iter().zip(test.iter()) {
+        let (r, t) = (r as f64 - mean_r, t as f64 - mean_t);
+        rr += r * r;
+        tt += t * t;
+        rt += r * t;
+    }
+    if rr == 0.0 || tt == 0.0 {
+        return Ok(f32::NAN);
+    }
+    Ok((rt / root(rr * tt) as f64) as f32)
+}
+
+/// Panics unless every value of test is within tolerance of reference
+#[track_caller]
+pub fn assert_close(reference: &[f32], test: &[f32], tolerance: f32) {
+    let comparison = compare_or_panic(reference, test);
+    if comparison.max_absolute.is_nan() || comparison.max_absolute > tolerance {
+        panic!(
+            "not within {:e}\n{}\n{}",
+            tolerance,
+            comparison,
+            Diff::new(reference, test, tolerance)
+        );
+    }
+}
+
+/// Panics unless test is at least min dB of SNR from reference
+#[track_caller]
+pub fn assert_snr(reference: &[f32], test: &[f32], min: f32) {
+    let comparison = compare_or_panic(reference, test);
+    if comparison.snr.is_nan() || comparison.snr < min {
+        panic!(
+            "snr under {}dB\n{}\n{}",
+            min,
+            comparison,
+            Diff::new(reference, test, 0.0)
+        );
+    }
+}
+
+/// Panics unless every value of test is within max ulps of reference
+#[track_caller]
+pub fn assert_ulps(reference: &[f32], test: &[f32], max: u32) {
+    let comparison = compare_or_panic(reference, test);
+    if comparison.max_ulps > max {
+        panic!(
+            "not within {} ulps\n{}\n{}",
+            max,
+            comparison,
+            Diff::new(reference, test, 0.0).ulps(max)
+        );
+    }
+}
+
+/// The values of test that are out a row each, the first few of them, with
+/// how far out they are. Usually comes from an assert but can be printed by
+/// itself.
+#[derive(Clone, Copy, Debug)]
+pub struct Diff<'a> {
+    reference: &'a [f32],
+    test: &'a [f32],
+    tolerance: f32,
+    /// out by ulps rather than tolerance
+    max_ulps: Option<u32>,
+}
+
+impl<'a> Diff<'a> {
+    /// Values more than tolerance apart, so 0 for every value that isnt
+    /// exactly the same
+    pub fn new(reference: &'a [f32], test: &'a [f32], tolerance: f32) -> Self {
+        Self {
+            reference,
+            test,
+            tolerance,
+            max_ulps: None,
+        }
+    }
+
+    /// Values more than max ulps apart instead
+    pub fn ulps(self, max: u32) -> Self {
+        Self {
+            max_ulps: Some(max),
+            ..self
+        }
+    }
+
+    fn out(&self, r: f32, t: f32) -> bool {
+        match self.max_ulps {
+            Some(max) => ulps(r, t) > max,
+            None => {
+                let difference = (t - r).abs();
+                difference.is_nan() || difference > self.tolerance
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = self
+            .reference
+            .iter()
+            .zip(self.test.iter())
+            .enumerate()
+            .filter(|(_, (&r, &t))| self.out(r, t));
+
+        write!(
+            f,
+            "{:>6} {:>14} {:>14} {:>12} {:>10}",
+            "index", "reference", "test", "difference", "ulps"
+        )?;
+        for (i, (&r, &t)) in out.by_ref().take(DIFF_ROWS) {
+            write!(
+                f,
+                "\n{:>6} {:>14e} {:>14e} {:>12.3e} {:>10}",
+                i,
+                r,
+                t,
+                t - r,
+                ulps(r, t)
+            )?;
+        }
+        let more = out.count();
+        if more > 0 {
+            write!(f, "\n... and {} more", more)?;
+        }
+        if self.reference.len() != self.test.len() {
+            write!(
+                f,
+                "\nlengths differ, {} against {}",
+                self.reference.len(),
+                self.test.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[track_caller]
+fn compare_or_panic(reference: &[f32], test: &[f32]) -> Comparison {
+    match compare(reference, test) {
+        Ok(comparison) => comparison,
+        Err(_) => panic!(
+            "reference has {} values but test has {}",
+            reference.len(),
+            test.len()
+        ),
+    }
+}
+
+fn check(reference: &[f32], test: &[f32]) -> Result<(), Error> {
+    if reference.len() != test.len() {
+        return Err(Error::Length {
+            expected: reference.len(),
+            found: test.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Sum of the squared errors and of the squared reference
+fn energies(reference: &[f32], test: &[f32]) -> Result<(f64, f64), Error> {
+    check(reference, test)?;
+    Ok(reference
+        .iter()
+        .zip(test.iter())
+        .fold((0.0, 0.0), |(error, signal), (&r, &t)| {
+            let (r, t) = (r as f64, t as f64);
+            (error + (t - r) * (t - r), signal + r * r)
+        }))
+}
+
+/// Index and size of the largest absolute error, the first NaN if theres one
+fn worst(reference: &[f32], test: &[f32]) -> Result<(usize, f32), Error> {
+    check(reference, test)?;
+    let mut worst = (0, 0.0f32);
+    for (i, (&r, &t)) in reference.iter().zip(test.iter()).enumerate() {
+        let error = (t as f64 - r as f64).abs() as f32;
+        if !worst.1.is_nan() && (error.is_nan() || error > worst.1) {
+            worst = (i, error);
+        }
+    }
+    Ok(worst)
+}
+
+/// max that keeps a NaN rather than throwing it away like f32::max
+fn larger(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else {
+        a.max(b)
+    }
+}
+
+/// sqrt that leaves NaN and infinity alone, float::sqrt never finishes
+/// on them
+fn root(x: f64) -> f32 {
+    if x.is_finite() {
+        float::sqrt(x) as f32
+    } else {
+        x as f32
+    }
+}
+
+/// f32 bits as an integer that counts up through the floats from -inf to
+/// inf, with 0 and -0 both 0
+fn ordered(x: f32) -> i64 {
+    let bits = x.to_bits() as i32;
+    if bits < 0 {
+        i32::MIN as i64 - bits as i64
+    } else {
+        bits as i64
+    }
+}
@@ -0,0 +1,243 @@
+//! Picking peaks out of a spectrum.
+//!
+//! Peaks are bins higher than the bin before and at least as high as the one
+//! after, so the first and last bin never count. Thats DC and Nyquist for a
+//! real spectrum, which is what we want anyway with gravity sitting in DC on
+//! the accelerometer. They're taken highest first, skipping any too close to
+//! one already taken, until there are K.
+//!
+//! A tone thats between two bins shows up in both, so the bin alone is only
+//! good to half a bin. Fitting a curve through the peak and its neighbours
+//! gets most of the rest of the way:
+//!
+//! - Parabolic fits a parabola to the magnitudes, works with any window
+//! - Gaussian fits a parabola to the log magnitudes, which is exact for a
+//!   Gaussian window and close for Hann and friends
+//! - Quinn's second estimator uses the complex bins, so needs the spectrum
+//!   and not just magnitudes, and is near exact for a tone under a
+//!   rectangular window
+//!
+//! ```ignore
+//! let peaks: heapless::Vec<Peak, 3> = find(&mag, &Options::default());
+//! rprintln!("{}Hz", peaks[0].frequency(SAMPLE_RATE / N as f32));
+//! ```
+
+use crate::complex::ComplexExt;
+use microfft::Complex32;
+
+#[cfg(not(feature = "std"))]
+use micromath::F32Ext;
+
+/// A bin of a spectrum, a magnitude or the complex value itself
+pub trait Bin: Copy {
+    fn magnitude(self) -> f32;
+    /// the complex value if theres phase to go with the magnitude
+    fn complex(self) -> Option<Complex32>;
+}
+
+impl Bin for f32 {
+    fn magnitude(self) -> f32 {
+        self
+    }
+
+    fn complex(self) -> Option<Complex32> {
+        None
+    }
+}
+
+impl Bin for Complex32 {
+    fn magnitude(self) -> f32 {
+        ComplexExt::magnitude(self)
+    }
+
+    fn complex(self) -> Option<Complex32> {
+        Some(self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// just the bin
+    None,
+    Parabolic,
+    Gaussian,
+    /// needs complex bins, on magnitudes it falls back to Parabolic
+    Quinn,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    /// peaks closer than this many bins to a higher peak are dropped, 1
+    /// keeps everything
+    pub min_separation: usize,
+    /// smallest magnitude a peak can have
+    pub threshold: Option<f32>,
+    /// smallest height a peak can stand above the higher of the lowest points
+    /// either side of it, before reaching something taller or the end
+    pub prominence: Option<f32>,
+    pub interpolation: Interpolation,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            min_separation: 1,
+            threshold: None,
+            prominence: None,
+            interpolation: Interpolation::Parabolic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Peak {
+    pub bin: usize,
+    /// how far from bin in fractions of a bin the interpolation puts the
+    /// true peak
+    pub offset: f32,
+    /// interpolated height, or the bins magnitude for None and Quinn
+    pub magnitude: f32,
+    pub prominence: f32,
+}
+
+impl Peak {
+    /// Position in fractional bins
+    pub fn position(&self) -> f32 {
+        self.bin as f32 + self.offset
+    }
+
+    /// Position in Hz given the Hz between bins, sample rate over FFT length
+    pub fn frequency(&self, resolution: f32) -> f32 {
+        self.position() * resolution
+    }
+}
+
+/// The K highest peaks that pass the options, highest first
+pub fn find<T: Bin, const K: usize>(bins: &[T], options: &Options) -> heapless::Vec<Peak, K> {
+    let mut peaks: heapless::Vec<Peak, K> = heapless::Vec::new();
+
+    // no room to keep every candidate without alloc so pick the highest
+    // remaining one each time round, K passes over the spectrum
+    while !peaks.is_full() {
+        let mut best: Option<(usize, f32, f32)> = None;
+
+        for k in 1..bins.len().saturating_sub(1) {
+            let magnitude = bins[k].magnitude();
+            if !is_local_max(bins, k)
+                || best.is_some_and(|(_, m, _)| magnitude <= m)
+                || options.threshold.is_some_and(|t| magnitude < t)
+                || peaks
+                    .iter()
+                    .any(|p| p.bin.abs_diff(k) < options.min_separation.max(1))
+            {
+                continue;
+            }
+
+            let prominence = prominence(bins, k);
+            if options.prominence.is_some_and(|p| prominence < p) {
+                continue;
+            }
+
+            best = Some((k, magnitude, prominence));
+        }
+
+        let (bin, magnitude, prominence) = match best {
+            Some(best) => best,
+            None => break,
+        };
+        let (offset, magnitude) = interpolate(bins, bin, magnitude, options.interpolation);
+        peaks
+            .push(Peak {
+                bin,
+                offset,
+                magnitude,
+                prominence,
+            })
+            .ok();
+    }
+
+    peaks
+}
+
+fn is_local_max<T: Bin>(bins: &[T], k: usize) -> bool {
+    let magnitude = bins[k].magnitude();
+    magnitude > bins[k - 1].magnitude() && magnitude >= bins[k + 1].magnitude()
+}
+
+/// Same definition as scipy's find_peaks
+fn prominence<T: Bin>(bins: &[T], k: usize) -> f32 {
+    let peak = bins[k].magnitude();
+    let lowest = |range: &mut dyn Iterator<Item = usize>| {
+        range
+            .map(|i| bins[i].magnitude())
+            .take_while(|&m| m <= peak)
+            .fold(peak, f32::min)
+    };
+
+    let left = lowest(&mut (0..k).rev());
+    let right = lowest(&mut (k + 1..bins.len()));
+    peak - left.max(right)
+}
+
+/// Offset from bin k and height of the peak
+fn interpolate<T: Bin>(
+    bins: &[T],
+    k: usize,
+    magnitude: f32,
+    interpolation: Interpolation,
+) -> (f32, f32) {
+    let (a, b, c) = (bins[k - 1], bins[k], bins[k + 1]);
+
+    match interpolation {
+        Interpolation::None => (0.0, magnitude),
+        Interpolation::Parabolic => parabolic(a.magnitude(), b.magnitude(), c.magnitude()),
+        Interpolation::Gaussian => {
+            let (a, b, c) = (a.magnitude(), b.magnitude(), c.magnitude());
+            if a <= 0.0 || c <= 0.0 {
+                // a neighbour right on zero, cant take its log
+                return parabolic(a, b, c);
+            }
+            let (offset, height) = parabolic(a.ln(), b.ln(), c.ln());
+            (offset, height.exp())
+        }
+        Interpolation::Quinn => match (a.complex(), b.complex(), c.complex()) {
+            (Some(a), Some(b), Some(c)) => (quinn(a, b, c), magnitude),
+            _ => parabolic(a.magnitude(), b.magnitude(), c.magnitude()),
+        },
+    }
+}
+
+/// Vertex of the parabola through (-1, a), (0, b), (1, c)
+fn parabolic(a: f32, b: f32, c: f32) -> (f32, f32) {
+    let denominator = a - 2.0 * b + c;
+    if denominator == 0.0 {
+        return (0.0, b);
+    }
+    let offset = 0.5 * (a - c) / denominator;
+    (offset, b - 0.25 * (a - c) * offset)
+}
+
+/// Quinn's second estimator, from "Estimation of frequency, amplitude and
+/// phase from the DFT of a time series", IEEE Trans. Signal Processing 1997
+fn quinn(before: Complex32, peak: Complex32, after: Complex32) -> f32 {
+    let power = peak.magnitude_squared();
+    if power == 0.0 {
+        return 0.0;
+    }
+    // real part of the neighbour over the peak
+    let ratio = |x: Complex32| (x.re * peak.re + x.im * peak.im) / power;
+
+    let ap = ratio(after);
+    let am = ratio(before);
+    let dp = -ap / (1.0 - ap);
+    let dm = am / (1.0 - am);
+
+    (dp + dm) / 2.0 + tau(dp * dp) - tau(dm * dm)
+}
+
+fn tau(x: f32) -> f32 {
+    const SQRT_6: f32 = 2.449_489_7;
+    const SQRT_2_3: f32 = 0.816_496_6;
+    0.25 * (3.0 * x * x + 6.0 * x + 1.0).ln()
+        - SQRT_6 / 24.0 * ((x + 1.0 - SQRT_2_3) / (x + 1.0 + SQRT_2_3)).ln()
+}
@@ -0,0 +1,340 @@
+//! FFT of any length.
+//!
+//! microfft and CMSIS only do powers of two, but lab1 captures 1000
+//! accelerometer samples and lab5 captures 100. Lengths made of 2s, 3s and 5s
+//! are done with a mixed radix decimation in time FFT. Anything else, primes
+//! especially, goes through Bluestein's algorithm which rewrites the DFT as a
+//! convolution with a chirp and does that convolution with a power of two FFT
+//! at least 2N-1 long.
+//!
+//! Nothing is allocated. Plan::new works out the twiddle factors once into
+//! storage the caller hands it, and every transform borrows the callers
+//! scratch, so the sizes are known up front.
+//!
+//! ```ignore
+//! let mut storage = [Complex32::new(0.0, 0.0); 1000];
+//! let mut scratch = [Complex32::new(0.0, 0.0); 1000];
+//! let plan = Plan::new(1000, &mut storage, &mut scratch).unwrap();
+//! plan.fft(&mut samples, &mut scratch).unwrap();
+//! ```
+
+use crate::fft::Normalization;
+use crate::{float, Error};
+use microfft::Complex32;
+
+/// 2^32 samples wont fit anywhere anyway
+const MAX_FACTORS: usize = 32;
+
+/// A precomputed FFT of one particular length
+#[derive(Debug)]
+pub struct Plan<'a> {
+    kind: Kind<'a>,
+}
+
+#[derive(Debug)]
+enum Kind<'a> {
+    MixedRadix(MixedRadix<'a>),
+    Bluestein {
+        /// e^(-pi i n^2/N) for n in 0..N
+        chirp: &'a [Complex32],
+        /// FFT of the conjugate chirp wrapped around to length M, already
+        /// scaled by 1/M for the inverse
+        filter: &'a [Complex32],
+        /// power of two FFT of length M for the convolution
+        inner: MixedRadix<'a>,
+    },
+}
+
+impl<'a> Plan<'a> {
+    /// Complex values of storage Plan::new needs for a length
+    pub fn storage_len(len: usize) -> usize {
+        if is_smooth(len) {
+            len
+        } else {
+            len + 2 * bluestein_len(len)
+        }
+    }
+
+    /// Complex values of scratch Plan::new and the transforms need for a length
+    pub fn scratch_len(len: usize) -> usize {
+        if is_smooth(len) {
+            len
+        } else {
+            2 * bluestein_len(len)
+        }
+    }
+
+    /// Work out the twiddle factors for a length into storage. Scratch is only
+    /// borrowed while Bluestein's filter is transformed.
+    pub fn new(
+        len: usize,
+        storage: &'a mut [Complex32],
+        scratch: &mut [Complex32],
+    ) -> Result<Self, Error> {
+        check_len(Self::storage_len(len), storage.len())?;
+        check_len(Self::scratch_len(len), scratch.len())?;
+
+        if is_smooth(len) {
+            let inner = MixedRadix::new(len, storage);
+            return Ok(Self {
+                kind: Kind::MixedRadix(inner),
+            });
+        }
+
+        let m = bluestein_len(len);
+        let (chirp, storage) = storage.split_at_mut(len);
+        let (filter, storage) = storage.split_at_mut(m);
+        let inner = MixedRadix::new(m, &mut storage[..m]);
+
+        // n^2 mod 2N keeps the angle small so it stays exact for big n
+        let double = 2 * len as u64;
+        for (n, c) in chirp.iter_mut().enumerate() {
+            let n = n as u64;
+            *c = float::twiddle(n * n % double, double);
+        }
+
+        // the convolution wraps around so the conjugate chirp goes in at both
+        // ends, everything between is zero
+        let zero = Complex32::new(0.0, 0.0);
+        filter.iter_mut().for_each(|f| *f = zero);
+        filter[0] = chirp[0].conj();
+        for n in 1..len {
+            filter[n] = chirp[n].conj();
+            filter[m - n] = chirp[n].conj();
+        }
+        inner.process(filter, scratch);
+        let scale = 1.0 / m as f32;
+        filter.iter_mut().for_each(|f| *f *= scale);
+
+        Ok(Self {
+            kind: Kind::Bluestein {
+                chirp,
+                filter,
+                inner,
+            },
+        })
+    }
+
+    /// The length this plan transforms
+    pub fn len(&self) -> usize {
+        match &self.kind {
+            Kind::MixedRadix(inner) => inner.len(),
+            Kind::Bluestein { chirp, .. } => chirp.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// In place forward FFT, unscaled like cfft
+    pub fn fft(&self, buffer: &mut [Complex32], scratch: &mut [Complex32]) -> Result<(), Error> {
+        if buffer.len() != self.len() {
+            return Err(Error::Length {
+                expected: self.len(),
+                found: buffer.len(),
+            });
+        }
+        check_len(Self::scratch_len(self.len()), scratch.len())?;
+
+        match &self.kind {
+            Kind::MixedRadix(inner) => inner.process(buffer, scratch),
+            Kind::Bluestein {
+                chirp,
+                filter,
+                inner,
+            } => {
+                let m = filter.len();
+                let (a, scratch) = scratch.split_at_mut(m);
+
+                let zero = Complex32::new(0.0, 0.0);
+                a.iter_mut().for_each(|a| *a = zero);
+                a.iter_mut()
+                    .zip(buffer.iter().zip(chirp.iter()))
+                    .for_each(|(a, (x, c))| *a = x * c);
+
+                // convolve with the filter, inverse by conjugating around the
+                // forward transform and the 1/M is already in the filter
+                inner.process(a, scratch);
+                a.iter_mut()
+                    .zip(filter.iter())
+                    .for_each(|(a, f)| *a = (*a * f).conj());
+                inner.process(a, scratch);
+
+                buffer
+                    .iter_mut()
+                    .zip(a.iter().zip(chirp.iter()))
+                    .for_each(|(x, (a, c))| *x = a.conj() * c);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// fft scaled to pair with ifft under the same normalization
+    pub fn fft_normalized(
+        &self,
+        buffer: &mut [Complex32],
+        scratch: &mut [Complex32],
+        normalization: Normalization,
+    ) -> Result<(), Error> {
+        self.fft(buffer, scratch)?;
+
+        let scale = normalization.forward(self.len());
+        if scale != 1.0 {
+            buffer.iter_mut().for_each(|c| *c *= scale);
+        }
+
+        Ok(())
+    }
+
+    /// In place inverse FFT, same conjugate trick as icfft
+    pub fn ifft(
+        &self,
+        buffer: &mut [Complex32],
+        scratch: &mut [Complex32],
+        normalization: Normalization,
+    ) -> Result<(), Error> {
+        if buffer.len() != self.len() {
+            return Err(Error::Length {
+                expected: self.len(),
+                found: buffer.len(),
+            });
+        }
+        // checked before conjugating so an error leaves the buffer alone
+        check_len(Self::scratch_len(self.len()), scratch.len())?;
+
+        buffer.iter_mut().for_each(|c| c.im = -c.im);
+        self.fft(buffer, scratch)?;
+
+        let scale = normalization.inverse(self.len());
+        buffer.iter_mut().for_each(|c| {
+            c.re *= scale;
+            c.im *= -scale;
+        });
+
+        Ok(())
+    }
+}
+
+/// Mixed radix 2, 3 and 5 FFT
+#[derive(Debug)]
+struct MixedRadix<'a> {
+    factors: heapless::Vec<usize, MAX_FACTORS>,
+    /// e^(-2 pi i k/N) for k in 0..N
+    twiddles: &'a [Complex32],
+}
+
+impl<'a> MixedRadix<'a> {
+    fn new(len: usize, storage: &'a mut [Complex32]) -> Self {
+        let twiddles = &mut storage[..len];
+        for (k, t) in twiddles.iter_mut().enumerate() {
+            *t = float::twiddle(k as u64, len as u64);
+        }
+
+        let (factors, _) = factorize(len);
+
+        Self { factors, twiddles }
+    }
+
+    fn len(&self) -> usize {
+        self.twiddles.len()
+    }
+
+    fn process(&self, buffer: &mut [Complex32], scratch: &mut [Complex32]) {
+        let n = self.len();
+        if n <= 1 {
+            return;
+        }
+
+        let input = &mut scratch[..n];
+        input.copy_from_slice(buffer);
+        self.work(buffer, input, 1, &self.factors);
+    }
+
+    /// Decimation in time. out is split into p sub-FFTs of every pth input
+    /// sample, which are then combined with p point butterflies. stride is how
+    /// far apart this levels input samples are, and also N/(p*m) which is the
+    /// step through the twiddle table.
+    fn work(&self, out: &mut [Complex32], input: &[Complex32], stride: usize, factors: &[usize]) {
+        let p = factors[0];
+        let m = out.len() / p;
+
+        if m == 1 {
+            for (q, o) in out.iter_mut().enumerate() {
+                *o = input[q * stride];
+            }
+        } else {
+            for (q, chunk) in out.chunks_exact_mut(m).enumerate() {
+                self.work(chunk, &input[q * stride..], stride * p, &factors[1..]);
+            }
+        }
+
+        match p {
+            2 => self.butterfly2(out, m, stride),
+            _ => self.butterfly(out, p, m, stride),
+        }
+    }
+
+    fn butterfly2(&self, out: &mut [Complex32], m: usize, stride: usize) {
+        let (first, second) = out.split_at_mut(m);
+        for (k, (a, b)) in first.iter_mut().zip(second.iter_mut()).enumerate() {
+            let t = *b * self.twiddles[k * stride];
+            *b = *a - t;
+            *a += t;
+        }
+    }
+
+    /// Plain p point DFT per bin, p is at most 5 so the table lookups are
+    /// cheap enough
+    fn butterfly(&self, out: &mut [Complex32], p: usize, m: usize, stride: usize) {
+        let n = self.len();
+        let mut t = [Complex32::new(0.0, 0.0); 5];
+
+        for k in 0..m {
+            for (q, t) in t.iter_mut().enumerate().take(p) {
+                *t = out[q * m + k] * self.twiddles[q * k * stride];
+            }
+
+            for s in 0..p {
+                let mut sum = t[0];
+                for (q, t) in t.iter().enumerate().take(p).skip(1) {
+                    sum += t * self.twiddles[q * s * m * stride % n];
+                }
+                out[s * m + k] = sum;
+            }
+        }
+    }
+}
+
+/// Split off the 2s, 3s and 5s, whats left over is 1 if that was all of it
+fn factorize(len: usize) -> (heapless::Vec<usize, MAX_FACTORS>, usize) {
+    let mut factors = heapless::Vec::new();
+    let mut rest = len;
+    for p in [2, 3, 5] {
+        while rest > 1 && rest.is_multiple_of(p) {
+            factors.push(p).unwrap();
+            rest /= p;
+        }
+    }
+    (factors, rest)
+}
+
+/// Only 2s, 3s and 5s in it
+fn is_smooth(len: usize) -> bool {
+    factorize(len).1 <= 1
+}
+
+/// Bluesteins convolution length, a power of two at least 2N-1
+fn bluestein_len(len: usize) -> usize {
+    (2 * len - 1).next_power_of_two()
+}
+
+/// storage and scratch can be bigger than needed, just not smaller
+fn check_len(expected: usize, found: usize) -> Result<(), Error> {
+    if found < expected {
+        Err(Error::Length { expected, found })
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+//! Power spectral density estimates.
+//!
+//! A single FFT magnitude is a noisy estimate of a spectrum, it doesnt get
+//! any less noisy with more samples, just finer. And its in units of
+//! whatever the FFT length and window happened to make it. These scale
+//! |X[k]|^2 into power per Hz, so an accelerometer in g gives g^2/Hz and the
+//! area under the PSD is the signals mean square whatever the FFT length,
+//! window or sample rate.
+//!
+//! - periodogram, one rectangular windowed segment
+//! - modified periodogram, one segment with a window to keep leakage down
+//! - Welch, windowed segments overlapping by hop averaged together, which
+//!   trades frequency resolution for a less noisy estimate
+//!
+//! They're all the same accumulator, segments go in one at a time or as a
+//! whole signal, or streamed through push a block at a time as they come off
+//! the accelerometer.
+//!
+//! ```ignore
+//! let mut psd = Psd::<256>::welch(Window::Hann, 128, 100.0, Sides::One);
+//! psd.push(&block);
+//! for (f, p) in psd.frequencies().zip(psd.psd()) { ... }
+//! ```
+
+use crate::rfft::rfft;
+use crate::window::{Symmetry, Window};
+use crate::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sides {
+    /// DC to Nyquist, N/2+1 bins, with the negative frequencies folded in by
+    /// doubling everything but DC and Nyquist. What you want for real signals.
+    One,
+    /// all N bins in FFT order, the negative frequencies from N/2 on
+    Two,
+}
+
+/// Averaged PSD from N point segments
+#[derive(Clone, Debug)]
+pub struct Psd<const N: usize> {
+    window: [f32; N],
+    hop: usize,
+    sample_rate: f32,
+    sides: Sides,
+    /// |X[k]|^2 summed over every segment so far, N/2+1 used one sided
+    sum: [f32; N],
+    segments: usize,
+    /// samples pushed but not yet a whole segment
+    pending: [f32; N],
+    filled: usize,
+}
+
+impl<const N: usize> Psd<N> {
+    /// Segments every hop samples, hop from 1 to N. Usually half the
+    /// segment, or less for windows that taper a lot.
+    pub fn welch(window: Window, hop: usize, sample_rate: f32, sides: Sides) -> Self {
+        assert!(hop > 0 && hop <= N, "hop must be from 1 to N samples");
+
+        let mut coefficients = [0.0; N];
+        coefficients
+            .iter_mut()
+            .zip(window.coefficients(N, Symmetry::Periodic))
+            .for_each(|(c, w)| *c = w);
+
+        Self {
+            window: coefficients,
+            hop,
+            sample_rate,
+            sides,
+            sum: [0.0; N],
+            segments: 0,
+            pending: [0.0; N],
+            filled: 0,
+        }
+    }
+
+    /// Windowed segments that dont overlap, one segment is the modified
+    /// periodogram
+    pub fn modified_periodogram(window: Window, sample_rate: f32, sides: Sides) -> Self {
+        Self::welch(window, N, sample_rate, sides)
+    }
+
+    /// No window and no overlap, one segment is the plain periodogram
+    pub fn periodogram(sample_rate: f32, sides: Sides) -> Self {
+        Self::welch(Window::Rectangular, N, sample_rate, sides)
+    }
+
+    /// Add one segment of exactly N samples
+    pub fn add_segment(&mut self, segment: &[f32]) -> Result<(), Error> {
+        if segment.len() != N {
+            return Err(Error::Length {
+                expected: N,
+                found: segment.len(),
+            });
+        }
+
+        let mut windowed = [0.0; N];
+        windowed
+            .iter_mut()
+            .zip(segment.iter().zip(self.window.iter()))
+            .for_each(|(y, (x, w))| *y = x * w);
+
+        let spectrum = rfft(&mut windowed)?;
+        self.sum
+            .iter_mut()
+            .zip(spectrum.power())
+            .for_each(|(sum, p)| *sum += p);
+        self.segments += 1;
+
+        Ok(())
+    }
+
+    /// Add every whole segment of a signal, starting every hop samples.
+    /// Returns how many were added.
+    pub fn add_signal(&mut self, signal: &[f32]) -> usize {
+        let mut added = 0;
+        let mut start = 0;
+        while start + N <= signal.len() {
+            self.add_segment(&signal[start..start + N]).unwrap();
+            start += self.hop;
+            added += 1;
+        }
+        added
+    }
+
+    /// Stream samples in as they arrive. Segments overlap across calls the
+    /// same as within one, so pushing a signal in blocks of any size adds
+    /// the same segments as add_signal. Returns how many were added.
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        let mut added = 0;
+        for &x in samples {
+            self.pending[self.filled] = x;
+            self.filled += 1;
+
+            if self.filled == N {
+                let segment = self.pending;
+                self.add_segment(&segment).unwrap();
+                added += 1;
+
+                // keep the overlap for the next segment
+                self.pending.copy_within(self.hop.., 0);
+                self.filled = N - self.hop;
+            }
+        }
+        added
+    }
+
+    /// Start averaging again, pushed samples waiting for a segment are
+    /// dropped too
+    pub fn reset(&mut self) {
+        self.sum = [0.0; N];
+        self.segments = 0;
+        self.filled = 0;
+    }
+
+    /// Segments averaged so far
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// Bins in the estimate, N/2+1 one sided or N two sided
+    pub fn len(&self) -> usize {
+        match self.sides {
+            Sides::One => N / 2 + 1,
+            Sides::Two => N,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Hz between bins
+    pub fn resolution(&self) -> f32 {
+        self.sample_rate / N as f32
+    }
+
+    /// Frequency of each bin in Hz, negative from N/2 on when two sided
+    pub fn frequencies(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        (0..self.len()).map(move |k| {
+            let k = match self.sides {
+                Sides::Two if k >= N / 2 => k as f32 - N as f32,
+                _ => k as f32,
+            };
+            k * self.resolution()
+        })
+    }
+
+    /// Power per Hz in each bin, in the input units squared. All zeros
+    /// until a segment has been added.
+    pub fn psd(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        // |X|^2 / (fs sum w^2) is the two sided density of one segment
+        let sum_squares: f32 = self.window.iter().map(|w| w * w).sum();
+        let scale = match self.segments {
+            0 => 0.0,
+            segments => 1.0 / (self.sample_rate * sum_squares * segments as f32),
+        };
+
+        (0..self.len()).map(move |k| {
+            // rfft only has DC to Nyquist, the rest mirror back
+            let power = if k > N / 2 {
+                self.sum[N - k]
+            } else {
+                self.sum[k]
+            };
+
+            match self.sides {
+                Sides::One if k != 0 && k != N / 2 => 2.0 * power * scale,
+                _ => power * scale,
+            }
+        })
+    }
+}
@@ -0,0 +1,151 @@
+//! FFT of real signals.
+//!
+//! Sensor samples are real, so interleaving them with zeros for a complex FFT
+//! spends half the work and memory computing the negative frequencies, which
+//! are just the conjugates of the positive ones. rfft runs microfft's real
+//! transform in place on the N samples and hands back the N/2+1 bins from DC
+//! to Nyquist.
+//!
+//! Both microfft and CMSIS arm_rfft_fast_f32 pack those bins into N/2 complex
+//! values. DC and Nyquist are always real, so the Nyquist value rides in the
+//! imaginary part of the DC bin:
+//!
+//! ```text
+//! packed[0]      = (X[0].re, X[N/2].re)
+//! packed[k]      = X[k]    for 1 <= k < N/2
+//! ```
+//!
+//! RealSpectrum unpacks that so nobody has to remember.
+
+use crate::complex::ComplexExt;
+use crate::fft::Supported;
+use crate::Error;
+use core::convert::TryInto;
+use microfft::{real, Complex32};
+
+/// Real samples that can be viewed as an array of exactly N
+pub trait RealBuffer<const N: usize> {
+    fn as_array(&mut self) -> Result<&mut [f32; N], Error>;
+}
+
+impl<const N: usize> RealBuffer<N> for [f32; N] {
+    fn as_array(&mut self) -> Result<&mut [f32; N], Error> {
+        Ok(self)
+    }
+}
+
+impl<const N: usize> RealBuffer<N> for heapless::Vec<f32, N> {
+    fn as_array(&mut self) -> Result<&mut [f32; N], Error> {
+        let found = self.len();
+        (&mut self[..])
+            .try_into()
+            .map_err(|_| Error::Length { expected: N, found })
+    }
+}
+
+impl<const N: usize> RealBuffer<N> for [f32] {
+    fn as_array(&mut self) -> Result<&mut [f32; N], Error> {
+        let found = self.len();
+        self.try_into()
+            .map_err(|_| Error::Length { expected: N, found })
+    }
+}
+
+/// In place real FFT. The samples are overwritten by the packed spectrum so
+/// the buffer is borrowed for as long as the spectrum is around.
+pub fn rfft<B, const N: usize>(buffer: &mut B) -> Result<RealSpectrum<'_>, Error>
+where
+    B: RealBuffer<N> + ?Sized,
+{
+    let () = Supported::<N>::CHECK;
+
+    let array = buffer.as_array()?;
+    Ok(RealSpectrum::from_packed(dispatch(array)))
+}
+
+/// The N/2+1 bins of a real signals spectrum, stored packed in N/2 complex
+/// values
+#[derive(Debug)]
+pub struct RealSpectrum<'a> {
+    packed: &'a mut [Complex32],
+}
+
+impl<'a> RealSpectrum<'a> {
+    /// Wrap a spectrum packed the microfft and CMSIS way, like the output of
+    /// arm_rfft_fast_f32 once its viewed as complex
+    pub fn from_packed(packed: &'a mut [Complex32]) -> Self {
+        Self { packed }
+    }
+
+    /// Number of bins, N/2+1
+    pub fn len(&self) -> usize {
+        self.packed.len() + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Bin k for k in 0..=N/2
+    pub fn bin(&self, k: usize) -> Option<Complex32> {
+        let half = self.packed.len();
+        match k {
+            0 => Some(Complex32::new(self.packed[0].re, 0.0)),
+            k if k == half => Some(Complex32::new(self.packed[0].im, 0.0)),
+            k => self.packed.get(k).copied(),
+        }
+    }
+
+    /// All N/2+1 bins from DC to Nyquist
+    pub fn bins(&self) -> impl Iterator<Item = Complex32> + Clone + '_ {
+        (0..self.len()).map(move |k| self.bin(k).unwrap())
+    }
+
+    /// |X[k]| for every bin
+    pub fn magnitude(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        self.bins().map(|c| c.magnitude())
+    }
+
+    /// |X[k]|^2 for every bin, cheaper than magnitude when we only need to
+    /// compare
+    pub fn power(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        self.bins().map(|c| c.magnitude_squared())
+    }
+
+    /// The raw packed values with Nyquist in packed[0].im
+    pub fn packed(&self) -> &[Complex32] {
+        self.packed
+    }
+
+    pub fn packed_mut(&mut self) -> &mut [Complex32] {
+        self.packed
+    }
+}
+
+macro_rules! dispatch {
+    ($($n:literal => $rfft:path,)*) => {
+        fn dispatch<const N: usize>(array: &mut [f32; N]) -> &mut [Complex32] {
+            // N is known at compile time so only one arm survives and the
+            // conversion cant fail
+            match N {
+                $($n => $rfft((&mut array[..]).try_into().unwrap()),)*
+                _ => unreachable!(),
+            }
+        }
+    };
+}
+
+dispatch! {
+    2 => real::rfft_2,
+    4 => real::rfft_4,
+    8 => real::rfft_8,
+    16 => real::rfft_16,
+    32 => real::rfft_32,
+    64 => real::rfft_64,
+    128 => real::rfft_128,
+    256 => real::rfft_256,
+    512 => real::rfft_512,
+    1024 => real::rfft_1024,
+    2048 => real::rfft_2048,
+    4096 => real::rfft_4096,
+}
@@ -0,0 +1,217 @@
+//! Sliding DFT, a few bins of an N point DFT updated every sample.
+//!
+//! The STFT only has something new to say once a hop of samples has come in.
+//! The DFT of the last N samples only changes by the sample coming in and the
+//! one dropping out though, so each bin can be kept up to date in O(1) per
+//! sample instead:
+//!
+//! X_k(n) = e^(2 pi i k/N) (X_k(n-1) + x[n] - x[n-N])
+//!
+//! Thats the textbook sliding DFT. The twiddle factor gets rounded, so its
+//! magnitude isnt quite 1 and every sample the error is multiplied in again.
+//! Damping by r a little under 1 keeps it from growing without bound, at the
+//! cost of the bins no longer being quite the DFT.
+//!
+//! The modulated SDFT of Duda, "Accurate, Guaranteed Stable, Sliding Discrete
+//! Fourier Transform", IEEE Signal Processing Magazine 2010, gets around it
+//! by never multiplying the running sum by anything. Each sample goes in
+//! already rotated by an exact twiddle from the table, and the rotation to
+//! the window start happens on the way out, so rounding only adds up like a
+//! random walk.
+//!
+//! Windowing the samples would mean redoing the whole sum every time. Windows
+//! that are a sum of cosines can be applied to the DFT instead, Hann is
+//! 0.5 X[k] - 0.25 (X[k-1] + X[k+1]), so neighbouring bins are tracked too
+//! when the window needs them.
+//!
+//! ```ignore
+//! let mut sdft = Sdft::<256, 2>::new([10, 26], Window::Hann, Method::Modulated);
+//! for x in samples {
+//!     sdft.push(x);
+//!     let spectrum = sdft.spectrum();
+//! }
+//! ```
+
+use crate::dft::Twiddles;
+use crate::window::Window;
+use microfft::Complex32;
+
+#[cfg(not(feature = "std"))]
+use micromath::F32Ext;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// the textbook recurrence damped by r from 0 to 1, 1 is exact until
+    /// rounding builds up
+    Sliding { damping: f32 },
+    /// the mSDFT, accurate and stable without damping
+    Modulated,
+}
+
+/// Most terms of a cosine sum window, the flat top's five
+const MAX_TERMS: usize = 5;
+/// Bins tracked around each requested one, k-4 to k+4
+const SPAN: usize = 2 * MAX_TERMS - 1;
+const CENTRE: usize = MAX_TERMS - 1;
+
+#[derive(Clone, Debug)]
+pub struct Sdft<const N: usize, const K: usize> {
+    bins: [usize; K],
+    method: Method,
+    /// what each pair of neighbours j bins away gets multiplied by, the
+    /// window term with its sign and halved past j = 0
+    weights: [f32; MAX_TERMS],
+    terms: usize,
+    /// running sum for each requested bin and its neighbours, k + j at
+    /// CENTRE + j
+    sums: [[Complex32; SPAN]; K],
+    /// the last N samples, the oldest at position
+    history: [f32; N],
+    position: usize,
+    /// samples pushed, up to N
+    filled: usize,
+}
+
+impl<const N: usize, const K: usize> Sdft<N, K> {
+    /// Track bins of an N point DFT, bins wrap around N. The window has to
+    /// be a sum of cosines, Rectangular, Hann, Hamming, Blackman,
+    /// BlackmanHarris or FlatTop, and is the periodic one.
+    pub fn new(bins: [usize; K], window: Window, method: Method) -> Self {
+        let terms = window
+            .cosine_terms()
+            .expect("sliding DFT windows must be a sum of cosines");
+        if let Method::Sliding { damping } = method {
+            assert!(
+                damping > 0.0 && damping <= 1.0,
+                "damping must be from 0 to 1"
+            );
+        }
+
+        let mut weights = [0.0; MAX_TERMS];
+        for (j, (weight, &a)) in weights.iter_mut().zip(terms.iter()).enumerate() {
+            // x[n] cos(2 pi j n/N) is half of X[k-j] plus half of X[k+j]
+            let a = if j == 0 { a } else { a / 2.0 };
+            *weight = if j % 2 == 1 { -a } else { a } as f32;
+        }
+
+        let mut wrapped = [0; K];
+        wrapped
+            .iter_mut()
+            .zip(bins.iter())
+            .for_each(|(w, &k)| *w = k % N);
+
+        Self {
+            bins: wrapped,
+            method,
+            weights,
+            terms: terms.len(),
+            sums: [[Complex32::new(0.0, 0.0); SPAN]; K],
+            history: [0.0; N],
+            position: 0,
+            filled: 0,
+        }
+    }
+
+    /// The bins being tracked
+    pub fn bins(&self) -> &[usize; K] {
+        &self.bins
+    }
+
+    /// Slide the window along by one sample
+    pub fn push(&mut self, x: f32) {
+        let table = Twiddles::<N>::TABLE;
+        let oldest = self.history[self.position];
+        self.history[self.position] = x;
+        // the sample leaving has been damped N times since it came in
+        let leaving = match self.method {
+            Method::Sliding { damping } => oldest * damping.powi(N as i32),
+            Method::Modulated => oldest,
+        };
+
+        for (&k, sums) in self.bins.iter().zip(self.sums.iter_mut()) {
+            let used = CENTRE + 1 - self.terms..CENTRE + self.terms;
+            for (j, sum) in sums.iter_mut().enumerate().take(used.end).skip(used.start) {
+                let bin = neighbour::<N>(k, j);
+
+                match self.method {
+                    Method::Sliding { damping } => {
+                        // e^(2 pi i k/N) is the conjugate of the tables
+                        // e^(-2 pi i k/N)
+                        let rotation = table[bin].conj() * damping;
+                        *sum = (*sum + (x - leaving)) * rotation;
+                    }
+                    Method::Modulated => {
+                        // e^(-2 pi i k n/N) with n counted mod N, exact from
+                        // the table however long it runs
+                        let twiddle = table[bin * self.position % N];
+                        *sum += twiddle * (x - leaving);
+                    }
+                }
+            }
+        }
+
+        self.position = (self.position + 1) % N;
+        self.filled = (self.filled + 1).min(N);
+    }
+
+    pub fn process(&mut self, samples: &[f32]) {
+        samples.iter().for_each(|&x| self.push(x));
+    }
+
+    /// Whether N samples have been pushed, before that the window is padded
+    /// with zeros at the start
+    pub fn is_full(&self) -> bool {
+        self.filled == N
+    }
+
+    /// Windowed X[k] of the last N samples for the ith requested bin, the
+    /// same as an FFT of them oldest first
+    pub fn bin(&self, i: usize) -> Complex32 {
+        let k = self.bins[i];
+        let sums = &self.sums[i];
+        let unwindowed = |j: usize| self.unwindowed(k, j, sums[j]);
+
+        let mut x = unwindowed(CENTRE) * self.weights[0];
+        for offset in 1..self.terms {
+            x += (unwindowed(CENTRE - offset) + unwindowed(CENTRE + offset)) * self.weights[offset];
+        }
+        x
+    }
+
+    /// Every requested bin, in the order they were given
+    pub fn spectrum(&self) -> [Complex32; K] {
+        let mut spectrum = [Complex32::new(0.0, 0.0); K];
+        spectrum
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = self.bin(i));
+        spectrum
+    }
+
+    /// Back to an empty window of zeros
+    pub fn reset(&mut self) {
+        self.sums = [[Complex32::new(0.0, 0.0); SPAN]; K];
+        self.history = [0.0; N];
+        self.position = 0;
+        self.filled = 0;
+    }
+
+    /// X[k + j - CENTRE] without the window
+    fn unwindowed(&self, k: usize, j: usize, sum: Complex32) -> Complex32 {
+        match self.method {
+            Method::Sliding { .. } => sum,
+            Method::Modulated => {
+                // the sum is rotated as if the first sample ever was the
+                // oldest in the window, turn it to where the window starts
+                // now, which is position since its already moved on
+                let bin = neighbour::<N>(k, j);
+                sum * Twiddles::<N>::TABLE[bin * self.position % N].conj()
+            }
+        }
+    }
+}
+
+/// Bin k + j - CENTRE wrapped around N
+fn neighbour<const N: usize>(k: usize, j: usize) -> usize {
+    (k + CENTRE * N + j - CENTRE) % N
+}
@@ -0,0 +1,205 @@
+//! Short time Fourier transform and its inverse.
+//!
+//! The signal is cut into frames window_len long starting every hop samples,
+//! each frame is multiplied by the window, zero padded out to N and
+//! transformed. Frames are handed back whole, phase and all, so they can be
+//! modified and put back together with istft.
+//!
+//! istft is weighted overlap-add. Each frame is inverse transformed, windowed
+//! again and added in at its offset, then every sample is divided by the sum
+//! of w^2 over the frames that covered it. That undoes the windowing for any
+//! window and hop as long as that sum is never zero, so an unmodified STFT
+//! always comes back exactly. Once frames have been changed though, the
+//! changes get weighted unevenly unless the overlapping w^2 add up to a
+//! constant, which is the COLA condition Stft::cola checks.
+//!
+//! ```ignore
+//! let stft = Stft::<16>::new(Window::Hamming, 16, 8).unwrap();
+//! let frames: heapless::Vec<[Complex32; 16], 127> = stft.frames(&chirp).collect();
+//! let written = stft.istft(frames.iter().copied(), &mut output).unwrap();
+//! ```
+
+use crate::fft::{cfft, icfft, Normalization};
+use crate::window::{Symmetry, Window};
+use crate::Error;
+use microfft::Complex32;
+
+/// STFT with an N point FFT
+#[derive(Clone, Debug)]
+pub struct Stft<const N: usize> {
+    /// the window zero padded out to N
+    window: [f32; N],
+    window_len: usize,
+    hop: usize,
+}
+
+impl<const N: usize> Stft<N> {
+    /// Frames window_len long every hop samples, window_len can be anything
+    /// up to N and the rest of the FFT is zero padded. The window is the
+    /// periodic one, like spectral analysis wants.
+    pub fn new(window: Window, window_len: usize, hop: usize) -> Result<Self, Error> {
+        if window_len > N || window_len == 0 {
+            return Err(Error::Length {
+                expected: N,
+                found: window_len,
+            });
+        }
+        assert!(hop > 0, "hop must be at least one sample");
+
+        let mut padded = [0.0; N];
+        padded
+            .iter_mut()
+            .zip(window.coefficients(window_len, Symmetry::Periodic))
+            .for_each(|(p, w)| *p = w);
+
+        Ok(Self {
+            window: padded,
+            window_len,
+            hop,
+        })
+    }
+
+    pub fn window_len(&self) -> usize {
+        self.window_len
+    }
+
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// The window coefficients, without the zero padding
+    pub fn window(&self) -> &[f32] {
+        &self.window[..self.window_len]
+    }
+
+    /// How many whole frames fit in a signal len long, a partial frame at the
+    /// end is left out
+    pub fn frame_count(&self, len: usize) -> usize {
+        if len < self.window_len {
+            0
+        } else {
+            (len - self.window_len) / self.hop + 1
+        }
+    }
+
+    /// Samples istft writes for count frames
+    pub fn output_len(&self, count: usize) -> usize {
+        match count {
+            0 => 0,
+            count => (count - 1) * self.hop + self.window_len,
+        }
+    }
+
+    /// Spectrum of the frame starting at sample start
+    pub fn frame(&self, signal: &[f32], start: usize) -> Result<[Complex32; N], Error> {
+        let samples = signal
+            .get(start..start + self.window_len)
+            .ok_or(Error::Length {
+                expected: start + self.window_len,
+                found: signal.len(),
+            })?;
+
+        let mut frame = [Complex32::new(0.0, 0.0); N];
+        frame
+            .iter_mut()
+            .zip(samples.iter().zip(self.window.iter()))
+            .for_each(|(c, (x, w))| c.re = x * w);
+
+        cfft(&mut frame)?;
+        Ok(frame)
+    }
+
+    /// Spectrum of every whole frame in the signal, in order
+    pub fn frames<'a>(&'a self, signal: &'a [f32]) -> impl Iterator<Item = [Complex32; N]> + 'a {
+        (0..self.frame_count(signal.len())).map(move |m| self.frame(signal, m * self.hop).unwrap())
+    }
+
+    /// Weighted overlap-add of frames back into output, returns how many
+    /// samples were written. Output has to be at least
+    /// output_len(number of frames) long, anything after that is zeroed.
+    /// Samples no frame covered with a nonzero window, like the first sample
+    /// under a Hann window, come back as zero.
+    pub fn istft<I>(&self, frames: I, output: &mut [f32]) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = [Complex32; N]>,
+    {
+        output.iter_mut().for_each(|y| *y = 0.0);
+        let found = output.len();
+
+        let mut count = 0;
+        for mut frame in frames {
+            let start = count * self.hop;
+            let out = output
+                .get_mut(start..start + self.window_len)
+                .ok_or(Error::Length {
+                    expected: start + self.window_len,
+                    found,
+                })?;
+
+            let samples = icfft(&mut frame, Normalization::OneOverN)?;
+            out.iter_mut()
+                .zip(samples.iter().zip(self.window.iter()))
+                .for_each(|(y, (x, w))| *y += x.re * w);
+
+            count += 1;
+        }
+
+        let written = self.output_len(count);
+        for (n, y) in output[..written].iter_mut().enumerate() {
+            let norm = self.overlap(n, count);
+            *y = if norm > 1e-10 { *y / norm } else { 0.0 };
+        }
+
+        Ok(written)
+    }
+
+    /// Sum of w^2 at sample n over the first count frames
+    fn overlap(&self, n: usize, count: usize) -> f32 {
+        // frames from first to last start at or before n and end after it
+        let last = (n / self.hop).min(count.saturating_sub(1));
+        let first = (n + 1).saturating_sub(self.window_len).div_ceil(self.hop);
+
+        (first..=last)
+            .map(|m| self.window[n - m * self.hop])
+            .map(|w| w * w)
+            .sum()
+    }
+
+    /// How far the overlapping w^2 are from adding up to a constant, over one
+    /// hop in the middle of a long signal
+    pub fn cola(&self) -> Cola {
+        let (min, max) = (0..self.hop)
+            .map(|n| {
+                (n..self.window_len)
+                    .step_by(self.hop)
+                    .map(|i| self.window[i] * self.window[i])
+                    .sum::<f32>()
+            })
+            .fold((f32::INFINITY, 0.0f32), |(min, max), sum| {
+                (min.min(sum), max.max(sum))
+            });
+
+        Cola { min, max }
+    }
+}
+
+/// Smallest and largest sum of overlapping w^2 across a hop
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cola {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Cola {
+    /// The sum is constant to within f32 rounding, so modified frames are
+    /// weighted evenly when overlap-added
+    pub fn holds(self) -> bool {
+        self.max - self.min <= self.max * 1e-4
+    }
+
+    /// The sum is never zero so istft can undo the window at all, even if
+    /// COLA doesnt hold
+    pub fn invertible(self) -> bool {
+        self.min > 1e-10
+    }
+}
@@ -0,0 +1,257 @@
+//! Window functions and what they do to a spectrum.
+//!
+//! Symmetric windows go to zero (or their minimum) at both ends and are what
+//! you want for FIR filter design. Periodic windows are the symmetric window
+//! one sample longer with the last sample dropped, which is what the STFT and
+//! spectral analysis want since the DFT treats the frame as one period. The
+//! Hamming window in 4_10 and 4_11 is the periodic one.
+//!
+//! Coefficients are worked out in f64 so theyre the same on device as on the
+//! host even though micromath's cos is only good to a few decimal places.
+//!
+//! The metrics follow Harris, "On the Use of Windows for Harmonic Analysis
+//! with the Discrete Fourier Transform", Proc. IEEE 1978.
+
+use crate::float;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    /// 0.54 - 0.46 cos
+    Hamming,
+    /// three term 0.42, 0.5, 0.08
+    Blackman,
+    /// four term -92 dB Blackman-Harris
+    BlackmanHarris,
+    /// five term flat top, same coefficients as Matlab's flattopwin
+    FlatTop,
+    /// I0(beta sqrt(1 - x^2)) / I0(beta), bigger beta trades a wider main
+    /// lobe for lower sidelobes. Harris' Kaiser-Bessel alpha is beta/pi.
+    Kaiser {
+        beta: f32,
+    },
+    /// flat in the middle with alpha of the length tapered by half a cosine
+    /// at the ends, 0 is rectangular and 1 is Hann
+    Tukey {
+        alpha: f32,
+    },
+    /// e^(-(alpha x)^2 / 2) with x from -1 to 1, so alpha is half the length
+    /// over the standard deviation like Harris uses
+    Gaussian {
+        alpha: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    /// w[0] == w[len-1], for filter design
+    Symmetric,
+    /// one period of a window repeating every len samples, for spectral
+    /// analysis
+    Periodic,
+}
+
+/// How a window changes what a DFT of the windowed signal shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metrics {
+    /// mean of the window, how much a tone right on a bin gets scaled by
+    pub coherent_gain: f32,
+    /// equivalent noise bandwidth in bins, how much more noise each bin
+    /// picks up than with a rectangular window
+    pub enbw: f32,
+    /// dB lost by a tone halfway between two bins, positive
+    pub scalloping_loss: f32,
+    /// highest sidelobe relative to the main lobe in dB, negative
+    pub sidelobe_level: f32,
+}
+
+impl Window {
+    /// w[n] of a window len long
+    pub fn coefficient(self, n: usize, len: usize, symmetry: Symmetry) -> f32 {
+        // position across the window from 0 to 1, periodic windows never get
+        // to 1
+        let span = match symmetry {
+            Symmetry::Symmetric => len.saturating_sub(1),
+            Symmetry::Periodic => len,
+        };
+        if span == 0 {
+            return 1.0;
+        }
+        let n = n as u64;
+        let span = span as u64;
+        let x = n as f64 / span as f64;
+
+        let w = match self {
+            Window::Rectangular
+            | Window::Hann
+            | Window::Hamming
+            | Window::Blackman
+            | Window::BlackmanHarris
+            | Window::FlatTop => cosine_sum(self.cosine_terms().unwrap(), n, span),
+            Window::Kaiser { beta } => {
+                let beta = beta as f64;
+                let r = 2.0 * x - 1.0;
+                bessel_i0(beta * float::sqrt(1.0 - r * r)) / bessel_i0(beta)
+            }
+            Window::Tukey { alpha } => {
+                let alpha = alpha as f64;
+                // distance in from the nearest end, the taper is alpha/2 wide
+                // at each
+                let edge = if x < 0.5 { x } else { 1.0 - x };
+                if alpha <= 0.0 || edge >= alpha / 2.0 {
+                    1.0
+                } else {
+                    0.5 * (1.0 - float::sin_cos_turns(edge / alpha).1)
+                }
+            }
+            Window::Gaussian { alpha } => {
+                let r = alpha as f64 * (2.0 * x - 1.0);
+                float::exp(-0.5 * r * r)
+            }
+        };
+
+        w as f32
+    }
+
+    /// a0, a1, a2... of a0 - a1 cos(2 pi n/len) + a2 cos(4 pi n/len) - ...
+    /// for the windows that are a sum of cosines, None for the rest. Those
+    /// can be applied after the DFT instead of before, as a few neighbouring
+    /// bins added together.
+    pub fn cosine_terms(self) -> Option<&'static [f64]> {
+        match self {
+            Window::Rectangular => Some(&[1.0]),
+            Window::Hann => Some(&[0.5, 0.5]),
+            Window::Hamming => Some(&[0.54, 0.46]),
+            Window::Blackman => Some(&[0.42, 0.5, 0.08]),
+            Window::BlackmanHarris => Some(&[0.35875, 0.48829, 0.14128, 0.01168]),
+            Window::FlatTop => Some(&[
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_158,
+                0.083_578_947,
+                0.006_947_368,
+            ]),
+            Window::Kaiser { .. } | Window::Tukey { .. } | Window::Gaussian { .. } => None,
+        }
+    }
+
+    /// w[0] to w[len-1]
+    pub fn coefficients(self, len: usize, symmetry: Symmetry) -> impl Iterator<Item = f32> + Clone {
+        (0..len).map(move |n| self.coefficient(n, len, symmetry))
+    }
+
+    /// Multiply samples by the window in place, the window is as long as the
+    /// slice
+    pub fn apply(self, samples: &mut [f32], symmetry: Symmetry) {
+        let len = samples.len();
+        samples
+            .iter_mut()
+            .zip(self.coefficients(len, symmetry))
+            .for_each(|(x, w)| *x *= w);
+    }
+
+    /// Coherent gain, ENBW, scalloping loss and sidelobe level of this window
+    /// at a length. Sidelobes are found by stepping through the DTFT of the
+    /// window at 1/32 of a bin so this is O(len^2), work it out once rather
+    /// than in a loop.
+    pub fn metrics(self, len: usize, symmetry: Symmetry) -> Metrics {
+        let (sum, sum_squares) =
+            self.coefficients(len, symmetry)
+                .fold((0.0, 0.0), |(sum, sum_squares), w| {
+                    let w = w as f64;
+                    (sum + w, sum_squares + w * w)
+                });
+        let n = len as f64;
+
+        let peak = self.response(len, symmetry, 0.0);
+        let half_bin = self.response(len, symmetry, 0.5);
+
+        Metrics {
+            coherent_gain: (sum / n) as f32,
+            enbw: (n * sum_squares / (sum * sum)) as f32,
+            scalloping_loss: -db(half_bin / peak),
+            sidelobe_level: db(self.highest_sidelobe(len, symmetry) / peak),
+        }
+    }
+
+    /// |W| at a frequency in bins
+    fn response(self, len: usize, symmetry: Symmetry, bins: f64) -> f64 {
+        // e^(-i w n) stepped along by multiplying, its all f64 so the drift
+        // over a few thousand samples doesnt matter
+        let (sin, cos) = float::sin_cos_turns(bins / len as f64);
+        let (mut re, mut im) = (1.0, 0.0);
+        let (mut sum_re, mut sum_im) = (0.0, 0.0);
+
+        for w in self.coefficients(len, symmetry) {
+            let w = w as f64;
+            sum_re += w * re;
+            sum_im += w * im;
+            let next = re * cos + im * sin;
+            im = im * cos - re * sin;
+            re = next;
+        }
+
+        float::sqrt(sum_re * sum_re + sum_im * sum_im)
+    }
+
+    /// Walk out from DC past the first dip below half the peak, thats the end
+    /// of the main lobe, and take the biggest response from there to Nyquist.
+    /// The half peak check stops the flat top's ripple counting as a null.
+    fn highest_sidelobe(self, len: usize, symmetry: Symmetry) -> f64 {
+        const STEPS_PER_BIN: usize = 32;
+
+        let peak = self.response(len, symmetry, 0.0);
+        let mut previous = peak;
+        let mut main_lobe = true;
+        let mut highest = 0.0;
+
+        for step in 1..=len * STEPS_PER_BIN / 2 {
+            let response = self.response(len, symmetry, step as f64 / STEPS_PER_BIN as f64);
+            if main_lobe {
+                if response > previous && previous < peak / 2.0 {
+                    main_lobe = false;
+                    highest = response;
+                }
+            } else if response > highest {
+                highest = response;
+            }
+            previous = response;
+        }
+
+        highest
+    }
+}
+
+/// a0 - a1 cos(2 pi n/span) + a2 cos(4 pi n/span) - ...
+fn cosine_sum(a: &[f64], n: u64, span: u64) -> f64 {
+    a.iter()
+        .enumerate()
+        .map(|(k, a)| {
+            let cos = float::twiddle(k as u64 * n, span).re as f64;
+            if k & 1 == 1 {
+                -a * cos
+            } else {
+                a * cos
+            }
+        })
+        .sum()
+}
+
+/// Modified Bessel function of the first kind, order zero, from its series
+fn bessel_i0(x: f64) -> f64 {
+    let half = x / 2.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-17 {
+        term *= (half / k) * (half / k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+fn db(ratio: f64) -> f32 {
+    (20.0 * float::ln(ratio) / core::f64::consts::LN_10) as f32
+}
@@ -0,0 +1,191 @@
+//! Zoom FFT, a narrow band in fine detail without a huge FFT.
+//!
+//! Finer bins need a longer record, 4096 samples for 100Hz / 4096. Rather
+//! than holding all of them for a 4096 point FFT, the band of interest is
+//! mixed down to DC, low pass filtered and only every Dth sample kept, then K
+//! of those go through a K point FFT. The K bins cover fs/D around the centre
+//! with the resolution of a K*D point FFT, and only K complex samples and
+//! the filter history are ever in memory.
+//!
+//! Samples stream in with push, and the filter output is only worked out on
+//! the samples that are kept. Mixing before the filter is the same as mixing
+//! after it with the filter taps shifted up to the centre, so the mixer is
+//! just one exact rotation per kept sample.
+//!
+//! The low pass is a windowed sinc cut off at the edge of the decimated band,
+//! so the middle of the K bins is flat but the outer few roll off and pick
+//! up some aliasing. Zoom a bit wider than needed and ignore the edges.
+//!
+//! ```ignore
+//! // 3Hz give or take 1.5Hz at 100Hz, in 0.024Hz bins
+//! let mut zoom = Zoom::<128, 129>::new(3.0, 100.0, 32, Window::Blackman);
+//! while !zoom.push(next_sample()) {}
+//! let spectrum = zoom.spectrum().unwrap();
+//! ```
+
+use crate::fft::{cfft, Supported};
+use crate::window::{Symmetry, Window};
+use crate::{float, Error};
+use microfft::Complex32;
+
+#[derive(Clone, Debug)]
+pub struct Zoom<const K: usize, const TAPS: usize> {
+    /// turns per sample of the centre frequency
+    centre: f64,
+    sample_rate: f32,
+    decimation: usize,
+    /// low pass shifted up to the centre
+    taps: [Complex32; TAPS],
+    /// the last TAPS samples, the oldest at position
+    history: [f32; TAPS],
+    position: usize,
+    /// samples pushed
+    count: usize,
+    decimated: heapless::Vec<Complex32, K>,
+}
+
+impl<const K: usize, const TAPS: usize> Zoom<K, TAPS> {
+    /// K bins centred on centre Hz spanning sample_rate / decimation, with a
+    /// TAPS long low pass tapered by window. Panics if the decimation is 0
+    /// or TAPS is.
+    pub fn new(centre: f32, sample_rate: f32, decimation: usize, window: Window) -> Self {
+        let () = Supported::<K>::CHECK;
+        assert!(decimation > 0, "decimation has to be at least 1");
+        assert!(TAPS > 0, "the filter needs at least one tap");
+
+        let centre = centre as f64 / sample_rate as f64;
+        let cutoff = 0.5 / decimation as f64;
+        let middle = (TAPS - 1) as f64 / 2.0;
+
+        let mut low = [0.0f64; TAPS];
+        for (t, (low, w)) in low
+            .iter_mut()
+            .zip(window.coefficients(TAPS, Symmetry::Symmetric))
+            .enumerate()
+        {
+            let x = t as f64 - middle;
+            let sinc = if x == 0.0 {
+                2.0 * cutoff
+            } else {
+                float::sin_cos_turns(cutoff * x).0 / (core::f64::consts::PI * x)
+            };
+            *low = sinc * w as f64;
+        }
+        // unity gain at the centre
+        let gain: f64 = low.iter().sum();
+
+        let mut taps = [Complex32::new(0.0, 0.0); TAPS];
+        for (t, (tap, low)) in taps.iter_mut().zip(low.iter()).enumerate() {
+            let (sin, cos) = float::sin_cos_turns(centre * t as f64);
+            let low = low / gain;
+            *tap = Complex32::new((low * cos) as f32, (low * sin) as f32);
+        }
+
+        Self {
+            centre,
+            sample_rate,
+            decimation,
+            taps,
+            history: [0.0; TAPS],
+            position: 0,
+            count: 0,
+            decimated: heapless::Vec::new(),
+        }
+    }
+
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    /// Samples it takes to fill, the filter has to fill up before the first
+    /// one is kept
+    pub fn samples_needed(&self) -> usize {
+        TAPS + (K - 1) * self.decimation
+    }
+
+    /// Bin spacing in Hz, the same as a K*D point FFT
+    pub fn resolution(&self) -> f32 {
+        self.sample_rate / (K * self.decimation) as f32
+    }
+
+    /// Frequency of spectrum bin k in Hz, k = K/2 is the centre
+    pub fn frequency(&self, k: usize) -> f32 {
+        (self.centre * self.sample_rate as f64) as f32
+            + (k as f32 - (K / 2) as f32) * self.resolution()
+    }
+
+    /// Add a sample, returns whether K decimated samples are in. Once they
+    /// are more samples are ignored until reset.
+    pub fn push(&mut self, x: f32) -> bool {
+        if self.is_full() {
+            return true;
+        }
+
+        self.history[self.position] = x;
+        self.position = (self.position + 1) % TAPS;
+        let n = self.count;
+        self.count += 1;
+
+        if n + 1 >= TAPS && (n + 1 - TAPS).is_multiple_of(self.decimation) {
+            // newest sample times the first tap back to the oldest
+            let mut sum = Complex32::new(0.0, 0.0);
+            for (t, tap) in self.taps.iter().enumerate() {
+                let x = self.history[(self.position + TAPS - 1 - t) % TAPS];
+                sum += tap * x;
+            }
+
+            // then the mixer, e^(-2 pi i c n), counted from the first kept
+            // sample so the phase is relative to the start of the record
+            let since = (n + 1 - TAPS) as f64;
+            let (sin, cos) = float::sin_cos_turns(self.centre * since);
+            let _ = self
+                .decimated
+                .push(sum * Complex32::new(cos as f32, -sin as f32));
+        }
+
+        self.is_full()
+    }
+
+    /// push every sample, returns how many were used before it was full
+    pub fn process(&mut self, samples: &[f32]) -> usize {
+        let mut used = 0;
+        for &x in samples {
+            if self.is_full() {
+                break;
+            }
+            self.push(x);
+            used += 1;
+        }
+        used
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.decimated.is_full()
+    }
+
+    /// The K bins lowest frequency first, scaled by the decimation so they
+    /// compare with a K*D point DFT of the samples from (TAPS-1)/2 on, where
+    /// the filter delay lines them up. Errors until its full.
+    pub fn spectrum(&self) -> Result<[Complex32; K], Error> {
+        let mut bins = [Complex32::new(0.0, 0.0); K];
+        let found = self.decimated.len();
+        if found != K {
+            return Err(Error::Length { expected: K, found });
+        }
+        bins.copy_from_slice(&self.decimated);
+        cfft(&mut bins)?;
+
+        // negative frequencies from the top half round to the front
+        bins.rotate_right(K / 2);
+        let scale = self.decimation as f32;
+        bins.iter_mut().for_each(|c| *c *= scale);
+        Ok(bins)
+    }
+
+    pub fn reset(&mut self) {
+        self.history = [0.0; TAPS];
+        self.position = 0;
+        self.count = 0;
+        self.decimated.clear();
+    }
+}
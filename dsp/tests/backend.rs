@@ -0,0 +1,164 @@
+use dsp::backend::{self, Fft, Microfft, Reference};
+use dsp::fft::Normalization;
+use dsp::Error;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn signal<const N: usize>(seed: u32) -> [Complex32; N] {
+    let mut x = [Complex32::new(0.0, 0.0); N];
+    let mut noise = noise(seed);
+    for c in x.iter_mut() {
+        *c = Complex32::new(noise.next().unwrap(), noise.next().unwrap());
+    }
+    x
+}
+
+fn fft_agrees<const N: usize>()
+where
+    Microfft: Fft<N>,
+    Reference: Fft<N>,
+{
+    let x = signal::<N>(N as u32);
+    let (mut fast, mut slow) = (x, x);
+    Microfft::fft(&mut fast);
+    Reference::fft(&mut slow);
+
+    for (k, (a, b)) in fast.iter().zip(slow.iter()).enumerate() {
+        assert!(
+            (a - b).norm_sqr().sqrt() < 1e-4 * N as f32,
+            "N={} bin {} {} vs {}",
+            N,
+            k,
+            a,
+            b
+        );
+    }
+}
+
+fn rfft_agrees<const N: usize>()
+where
+    Microfft: Fft<N>,
+    Reference: Fft<N>,
+{
+    let mut fast = [0f32; N];
+    fast.iter_mut()
+        .zip(noise(N as u32))
+        .for_each(|(x, n)| *x = n);
+    let mut slow = fast;
+
+    let fast = Microfft::rfft(&mut fast);
+    let slow = Reference::rfft(&mut slow);
+    assert_eq!(fast.len(), N / 2 + 1);
+    assert_eq!(slow.len(), N / 2 + 1);
+
+    for (k, (a, b)) in fast.bins().zip(slow.bins()).enumerate() {
+        assert!(
+            (a - b).norm_sqr().sqrt() < 1e-4 * N as f32,
+            "N={} bin {} {} vs {}",
+            N,
+            k,
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn reference_agrees_with_microfft() {
+    fft_agrees::<2>();
+    fft_agrees::<8>();
+    fft_agrees::<64>();
+    fft_agrees::<256>();
+    fft_agrees::<1024>();
+}
+
+#[test]
+fn reference_rfft_agrees_with_microfft() {
+    rfft_agrees::<2>();
+    rfft_agrees::<16>();
+    rfft_agrees::<128>();
+    rfft_agrees::<512>();
+}
+
+#[test]
+fn reference_works_at_any_length() {
+    // an impulse at 1 is a single turn of the unit circle
+    let mut x = [Complex32::new(0.0, 0.0); 5];
+    x[1] = Complex32::new(1.0, 0.0);
+    Reference::fft(&mut x);
+    for (k, bin) in x.iter().enumerate() {
+        let theta = -2.0 * core::f32::consts::PI * k as f32 / 5.0;
+        assert!(
+            (bin - Complex32::new(theta.cos(), theta.sin()))
+                .norm_sqr()
+                .sqrt()
+                < 1e-6
+        );
+    }
+}
+
+#[test]
+fn ifft_round_trips() {
+    let x = signal::<64>(1);
+
+    let mut y = x;
+    Reference::fft(&mut y);
+    Reference::ifft(&mut y, Normalization::OneOverN);
+    for (a, b) in x.iter().zip(y.iter()) {
+        assert!((a - b).norm_sqr().sqrt() < 1e-5);
+    }
+
+    let mut y = x;
+    Microfft::fft(&mut y);
+    Microfft::ifft(&mut y, Normalization::OneOverN);
+    for (a, b) in x.iter().zip(y.iter()) {
+        assert!((a - b).norm_sqr().sqrt() < 1e-5);
+    }
+}
+
+#[test]
+fn selected_takes_slices_and_vecs() {
+    let x = signal::<32>(2);
+    let mut expected = x;
+    backend::Selected::fft(&mut expected);
+
+    let mut slice = x.to_vec();
+    backend::fft::<_, 32>(&mut slice[..]).unwrap();
+    assert_eq!(&slice[..], &expected[..]);
+
+    let mut vec: heapless::Vec<Complex32, 32> = x.iter().copied().collect();
+    backend::ifft(&mut vec, Normalization::OneOverN).unwrap();
+    backend::fft(&mut vec).unwrap();
+    for (a, b) in x.iter().zip(vec.iter()) {
+        assert!((a - b).norm_sqr().sqrt() < 1e-5);
+    }
+}
+
+#[test]
+fn wrong_length_is_an_error() {
+    let mut complex = [Complex32::new(0.0, 0.0); 10];
+    assert_eq!(
+        backend::fft::<_, 16>(&mut complex[..]).unwrap_err(),
+        Error::Length {
+            expected: 16,
+            found: 10
+        }
+    );
+
+    let mut real = [0f32; 10];
+    assert_eq!(
+        backend::rfft::<_, 8>(&mut real[..]).unwrap_err(),
+        Error::Length {
+            expected: 8,
+            found: 10
+        }
+    );
+}
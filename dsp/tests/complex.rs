@@ -0,0 +1,81 @@
+use core::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use dsp::complex::{from_interleaved, from_interleaved_mut, ComplexExt, Interleaved};
+use microfft::Complex32;
+
+fn close(a: Complex32, b: Complex32) -> bool {
+    (a - b).magnitude() < 1e-6
+}
+
+#[test]
+fn magnitude_and_arg() {
+    let z = Complex32::new(3.0, -4.0);
+
+    assert_eq!(z.magnitude_squared(), 25.0);
+    assert_eq!(z.magnitude(), 5.0);
+    assert!((z.arg() - (-4.0f32).atan2(3.0)).abs() < 1e-7);
+
+    assert_eq!(Complex32::new(-1.0, 0.0).arg(), PI);
+    assert_eq!(Complex32::new(0.0, 1.0).arg(), FRAC_PI_2);
+}
+
+#[test]
+fn polar_round_trip() {
+    for &(r, theta) in &[(1.0, 0.0), (2.0, FRAC_PI_4), (0.5, -3.0), (7.0, PI)] {
+        let z = Complex32::from_polar(r, theta);
+        assert!((z.magnitude() - r).abs() < 1e-6);
+        assert!(close(Complex32::from_polar(z.magnitude(), z.arg()), z));
+    }
+}
+
+#[test]
+fn exp() {
+    // e^(i pi) = -1
+    assert!(close(
+        Complex32::new(0.0, PI).exp(),
+        Complex32::new(-1.0, 0.0)
+    ));
+    // e^(ln 2 + i pi/2) = 2i
+    assert!(close(
+        Complex32::new(2f32.ln(), FRAC_PI_2).exp(),
+        Complex32::new(0.0, 2.0)
+    ));
+    // e^a e^b = e^(a+b)
+    let (a, b) = (Complex32::new(0.3, 1.1), Complex32::new(-0.2, 0.4));
+    assert!(close(a.exp() * b.exp(), (a + b).exp()));
+}
+
+#[test]
+fn operators() {
+    let a = Complex32::new(1.0, 2.0);
+    let b = Complex32::new(3.0, -1.0);
+
+    assert_eq!(a * b, Complex32::new(5.0, 5.0));
+    assert_eq!(a + b, Complex32::new(4.0, 1.0));
+    assert_eq!(a - b, Complex32::new(-2.0, 3.0));
+    assert_eq!(a * a.conj(), Complex32::new(a.magnitude_squared(), 0.0));
+    assert!(close(a / b * b, a));
+}
+
+#[test]
+fn interleaved_views() {
+    let mut x = [
+        Complex32::new(1.0, 2.0),
+        Complex32::new(3.0, 4.0),
+        Complex32::new(5.0, 6.0),
+    ];
+
+    assert_eq!(x.as_interleaved(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    // writes through the float view land in the complex values, like CMSIS
+    // working in place
+    x.as_interleaved_mut()[3] = -4.0;
+    assert_eq!(x[1], Complex32::new(3.0, -4.0));
+
+    let mut floats = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(
+        from_interleaved(&floats),
+        &[Complex32::new(1.0, 2.0), Complex32::new(3.0, 4.0)]
+    );
+    from_interleaved_mut(&mut floats)[0] = Complex32::new(9.0, 8.0);
+    assert_eq!(floats, [9.0, 8.0, 3.0, 4.0, 5.0]);
+}
@@ -0,0 +1,223 @@
+use core::f64::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::czt::Czt;
+use dsp::fft::cfft;
+use dsp::window::Window;
+use dsp::zoom::Zoom;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+/// sum of x[n] z^-n straight from the definition, in f64
+fn z_transform(x: &[Complex32], radius: f64, turns: f64) -> Complex32 {
+    let (mut re, mut im) = (0.0, 0.0);
+    for (n, x) in x.iter().enumerate() {
+        let r = radius.powi(-(n as i32));
+        let theta = -2.0 * PI * turns * n as f64;
+        let (sin, cos) = theta.sin_cos();
+        re += r * (x.re as f64 * cos - x.im as f64 * sin);
+        im += r * (x.re as f64 * sin + x.im as f64 * cos);
+    }
+    Complex32::new(re as f32, im as f32)
+}
+
+fn assert_close(a: Complex32, b: Complex32, tolerance: f32, what: &str) {
+    let error = (a - b).magnitude();
+    assert!(error < tolerance, "{}: {} != {} ({})", what, a, b, error);
+}
+
+#[test]
+fn arc_matches_a_direct_dft() {
+    // not a power of two, and the arc doesnt line up with any bin
+    let x: Vec<f32> = noise(1).take(100).collect();
+    let complex: Vec<Complex32> = x.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+
+    let czt = Czt::<100, 37, 256>::frequencies(3.3, 0.07, 100.0);
+    let spectrum = czt.transform(&x[..]).unwrap();
+
+    for (k, &bin) in spectrum.iter().enumerate() {
+        let turns = (3.3 + 0.07 * k as f64) / 100.0;
+        assert!((czt.frequency(k, 100.0) as f64 - turns * 100.0).abs() < 1e-4);
+        assert_close(bin, z_transform(&complex, 1.0, turns), 1e-3, "bin");
+    }
+}
+
+#[test]
+fn complex_input_matches_a_direct_dft() {
+    let x: Vec<Complex32> = noise(2)
+        .zip(noise(3))
+        .take(64)
+        .map(|(re, im)| Complex32::new(re, im))
+        .collect();
+
+    // across zero and round to negative frequencies
+    let czt = Czt::<64, 50, 128>::new(1.0, -0.1, 1.0, 0.004);
+    let spectrum = czt.transform(&x[..]).unwrap();
+
+    for (k, &bin) in spectrum.iter().enumerate() {
+        let expected = z_transform(&x, 1.0, -0.1 + 0.004 * k as f64);
+        assert_close(bin, expected, 1e-3, "bin");
+    }
+}
+
+#[test]
+fn a_step_of_one_over_n_is_the_fft() {
+    let x: Vec<f32> = noise(4).take(128).collect();
+    let czt = Czt::<128, 128, 256>::new(1.0, 0.0, 1.0, 1.0 / 128.0);
+    let spectrum = czt.transform(&x[..]).unwrap();
+
+    let mut fft: Vec<Complex32> = x.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+    cfft::<_, 128>(&mut fft[..]).unwrap();
+    for (a, b) in spectrum.iter().zip(fft.iter()) {
+        assert_close(*a, *b, 1e-3, "bin");
+    }
+}
+
+#[test]
+fn spiral_matches_the_z_transform() {
+    let x: Vec<f32> = noise(5).take(32).collect();
+    let complex: Vec<Complex32> = x.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+
+    // starting just inside the circle and winding out
+    let czt = Czt::<32, 16, 64>::new(0.98, 0.05, 1.002, 0.01);
+    let spectrum = czt.transform(&x[..]).unwrap();
+
+    for (k, &bin) in spectrum.iter().enumerate() {
+        let radius = 0.98 * 1.002f64.powi(k as i32);
+        let expected = z_transform(&complex, radius, 0.05 + 0.01 * k as f64);
+        let tolerance: f32 = 1e-4 * expected.magnitude().max(1.0);
+        assert_close(bin, expected, tolerance.max(1e-3), "point");
+    }
+}
+
+#[test]
+fn wrong_length_is_an_error() {
+    let czt = Czt::<16, 8, 32>::frequencies(0.0, 1.0, 16.0);
+    let x = [0.0f32; 15];
+    assert_eq!(
+        czt.transform(&x[..]).unwrap_err(),
+        dsp::Error::Length {
+            expected: 16,
+            found: 15
+        }
+    );
+}
+
+const FS: f32 = 100.0;
+
+/// tones at frequency, amplitude and phase
+fn tones(len: usize, tones: &[(f64, f64, f64)]) -> Vec<f32> {
+    (0..len)
+        .map(|n| {
+            tones
+                .iter()
+                .map(|&(f, a, phase)| a * (2.0 * PI * f * n as f64 / FS as f64 + phase).cos())
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+#[test]
+fn zoom_matches_a_direct_dft_in_the_band() {
+    const K: usize = 64;
+    const TAPS: usize = 257;
+    const D: usize = 16;
+    let mut zoom = Zoom::<K, TAPS>::new(12.5, FS, D, Window::Blackman);
+    assert_eq!(zoom.resolution(), FS / (K * D) as f32);
+
+    // tones in the flat middle, one between bins, plus one well outside the
+    // band the filter has to keep out
+    let band = [(11.718_75, 1.0, 0.3), (12.91, 0.5, -1.0)];
+    let x: Vec<f32> = tones(zoom.samples_needed(), &band)
+        .iter()
+        .zip(tones(zoom.samples_needed(), &[(30.0, 2.0, 0.0)]))
+        .map(|(a, b)| a + b)
+        .collect();
+    assert_eq!(zoom.process(&x), x.len());
+    assert!(zoom.is_full());
+    let spectrum = zoom.spectrum().unwrap();
+
+    // the samples the filter delay lines up with. The filter keeps just the
+    // positive frequency half of each tone in the band, and the zoom only
+    // ever sees every Dth sample, so its the direct DFT of those at each bins
+    // frequency, scaled up by D.
+    let start = (TAPS - 1) / 2;
+    let record: Vec<Complex32> = (start..start + K * D)
+        .step_by(D)
+        .map(|n| {
+            band.iter()
+                .map(|&(f, a, phase)| {
+                    let theta = 2.0 * PI * f * n as f64 / FS as f64 + phase;
+                    Complex32::new(
+                        (a / 2.0 * theta.cos()) as f32,
+                        (a / 2.0 * theta.sin()) as f32,
+                    )
+                })
+                .sum()
+        })
+        .collect();
+
+    let peak = (K * D) as f32 / 2.0;
+    for (k, &bin) in spectrum.iter().enumerate().take(3 * K / 4).skip(K / 4) {
+        let turns = zoom.frequency(k) as f64 / FS as f64 * D as f64;
+        let expected = z_transform(&record, 1.0, turns) * D as f32;
+        assert_close(bin, expected, 1e-3 * peak, "bin");
+    }
+
+    // the tone right on a bin has the same peak as a K*D point DFT, 8 bins of 100/1024Hz below the centre
+    assert!((zoom.frequency(K / 2 - 8) - 11.718_75).abs() < 1e-4);
+    assert!((spectrum[K / 2 - 8].magnitude() - peak).abs() < 5e-3 * peak);
+}
+
+#[test]
+fn zoom_resolves_what_a_short_fft_cant() {
+    // 0.1Hz apart, half a bin of a 512 point FFT at 100Hz
+    const K: usize = 128;
+    let mut zoom = Zoom::<K, 129>::new(3.0, FS, 32, Window::Blackman);
+    let x = tones(zoom.samples_needed(), &[(2.95, 1.0, 0.0), (3.05, 1.0, 0.0)]);
+    zoom.process(&x);
+    let magnitude: Vec<f32> = zoom
+        .spectrum()
+        .unwrap()
+        .iter()
+        .map(|c| c.magnitude())
+        .collect();
+
+    let bin = |f: f32| ((f - zoom.frequency(0)) / zoom.resolution()).round() as usize;
+    let (low, high) = (bin(2.95), bin(3.05));
+    let dip = magnitude[(low + high) / 2];
+    assert!(dip < 0.5 * magnitude[low], "{} {}", dip, magnitude[low]);
+    assert!(dip < 0.5 * magnitude[high], "{} {}", dip, magnitude[high]);
+}
+
+#[test]
+fn zoom_isnt_ready_until_full() {
+    let mut zoom = Zoom::<16, 33>::new(5.0, FS, 4, Window::Hann);
+    assert_eq!(zoom.samples_needed(), 33 + 15 * 4);
+    let x: Vec<f32> = noise(6).take(200).collect();
+
+    assert_eq!(zoom.process(&x[..40]), 40);
+    assert_eq!(
+        zoom.spectrum().unwrap_err(),
+        dsp::Error::Length {
+            expected: 16,
+            found: 2
+        }
+    );
+
+    // only takes what it needs
+    assert_eq!(zoom.process(&x[40..]), zoom.samples_needed() - 40);
+    let first = zoom.spectrum().unwrap();
+
+    zoom.reset();
+    assert!(!zoom.is_full());
+    zoom.process(&x);
+    assert_eq!(zoom.spectrum().unwrap(), first);
+}
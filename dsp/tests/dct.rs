@@ -0,0 +1,137 @@
+use core::f64::consts::PI;
+use dsp::dct::{dct2, dct3, dst2, dst3};
+use dsp::fft::Normalization;
+use dsp::Error;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn samples<const N: usize>(seed: u32) -> [f32; N] {
+    let mut x = [0.0; N];
+    x.iter_mut().zip(noise(seed)).for_each(|(x, n)| *x = n);
+    x
+}
+
+/// Straight from the definitions in f64
+fn direct(x: &[f32], sine: bool) -> Vec<f32> {
+    let n = x.len() as f64;
+    (0..x.len())
+        .map(|k| {
+            x.iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    let phase = PI * (2.0 * i as f64 + 1.0) / (2.0 * n);
+                    let basis = if sine {
+                        (phase * (k + 1) as f64).sin()
+                    } else {
+                        (phase * k as f64).cos()
+                    };
+                    x as f64 * basis
+                })
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+fn assert_close(a: &[f32], b: &[f32], tolerance: f32) {
+    for (i, (a, b)) in a.iter().zip(b).enumerate() {
+        assert!((a - b).abs() < tolerance, "{}: {} != {}", i, a, b);
+    }
+}
+
+fn check_against_direct<const N: usize>() {
+    let x = samples::<N>(N as u32);
+
+    let mut cosine = x;
+    dct2(&mut cosine, Normalization::None).unwrap();
+    assert_close(&cosine, &direct(&x, false), 1e-4 * N as f32);
+
+    let mut sine = x;
+    dst2(&mut sine, Normalization::None).unwrap();
+    assert_close(&sine, &direct(&x, true), 1e-4 * N as f32);
+}
+
+#[test]
+fn matches_the_definitions() {
+    check_against_direct::<2>();
+    check_against_direct::<8>();
+    check_against_direct::<64>();
+    check_against_direct::<512>();
+}
+
+#[test]
+fn orthonormal_scaling() {
+    let x = samples::<32>(1);
+    let expected = direct(&x, false);
+
+    let mut y = x;
+    dct2(&mut y, Normalization::Unitary).unwrap();
+    assert!((y[0] - expected[0] * (1.0 / 32.0f32).sqrt()).abs() < 1e-5);
+    for k in 1..32 {
+        assert!((y[k] - expected[k] * (2.0 / 32.0f32).sqrt()).abs() < 1e-5);
+    }
+
+    // orthonormal so energy is the same both sides
+    let energy = |x: &[f32]| x.iter().map(|x| x * x).sum::<f32>();
+    assert!((energy(&x) - energy(&y)).abs() < 1e-4 * energy(&x));
+
+    let mut y = x;
+    dst2(&mut y, Normalization::Unitary).unwrap();
+    assert!((energy(&x) - energy(&y)).abs() < 1e-4 * energy(&x));
+}
+
+#[test]
+fn third_kind_inverts_the_second() {
+    let x = samples::<128>(2);
+
+    for &(normalization, gain) in &[
+        (Normalization::OneOverN, 1.0),
+        (Normalization::Unitary, 1.0),
+        (Normalization::None, 128.0),
+    ] {
+        let mut y = x;
+        dct2(&mut y, normalization).unwrap();
+        dct3(&mut y, normalization).unwrap();
+        let expected: Vec<f32> = x.iter().map(|x| x * gain).collect();
+        assert_close(&y, &expected, 1e-5 * gain);
+
+        let mut y = x;
+        dst2(&mut y, normalization).unwrap();
+        dst3(&mut y, normalization).unwrap();
+        assert_close(&y, &expected, 1e-5 * gain);
+    }
+}
+
+#[test]
+fn smooth_signals_compact_into_a_few_coefficients() {
+    // slow drift plus a low tone, like an accelerometer log
+    let mut x = [0.0; 256];
+    for (n, x) in x.iter_mut().enumerate() {
+        let t = n as f64 / 256.0;
+        *x = (1.0 + 0.3 * t + 0.2 * (2.0 * PI * 1.5 * t).sin()) as f32;
+    }
+    let total: f32 = x.iter().map(|x| x * x).sum();
+
+    dct2(&mut x, Normalization::Unitary).unwrap();
+    let first: f32 = x[..8].iter().map(|x| x * x).sum();
+    assert!(first > 0.999 * total, "{} of {}", first, total);
+}
+
+#[test]
+fn wrong_length_is_an_error() {
+    let mut x: heapless::Vec<f32, 16> = heapless::Vec::new();
+    x.extend_from_slice(&[1.0; 10]).unwrap();
+    assert_eq!(
+        dct2(&mut x, Normalization::Unitary).unwrap_err(),
+        Error::Length {
+            expected: 16,
+            found: 10
+        }
+    );
+}
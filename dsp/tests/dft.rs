@@ -0,0 +1,147 @@
+use dsp::dft::{dft, dft_bin, idft, Twiddles};
+use dsp::fft::{cfft, Normalization};
+use dsp::plan::Plan;
+use dsp::rfft::rfft;
+use dsp::Error;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn close(a: Complex32, b: Complex32, tolerance: f32) -> bool {
+    (a - b).norm_sqr().sqrt() < tolerance
+}
+
+// the table is a constant so it has to be computable by the compiler
+const QUARTER: Complex32 = Twiddles::<8>::TABLE[2];
+
+#[test]
+fn table_is_built_at_compile_time() {
+    assert_eq!(QUARTER, Complex32::new(0.0, -1.0));
+
+    let table = Twiddles::<12>::TABLE;
+    assert_eq!(table[0], Complex32::new(1.0, 0.0));
+    assert_eq!(table[3], Complex32::new(0.0, -1.0));
+    assert_eq!(table[6], Complex32::new(-1.0, 0.0));
+    assert!(close(table[1], Complex32::new(0.75f32.sqrt(), -0.5), 1e-7));
+}
+
+#[test]
+fn complex_input_matches_cfft() {
+    let mut noise = noise(1);
+    let mut x = [Complex32::new(0.0, 0.0); 256];
+    x.iter_mut()
+        .for_each(|x| *x = Complex32::new(noise.next().unwrap(), noise.next().unwrap()));
+
+    let spectrum: heapless::Vec<Complex32, 256> = dft(&x).unwrap().collect();
+
+    let mut expected = x;
+    cfft(&mut expected).unwrap();
+
+    for (k, (a, b)) in spectrum.iter().zip(expected.iter()).enumerate() {
+        assert!(close(*a, *b, 1e-3), "bin {} {:?} vs {:?}", k, a, b);
+    }
+}
+
+#[test]
+fn real_input_matches_rfft() {
+    let x: heapless::Vec<f32, 128> = noise(2).take(128).collect();
+
+    let spectrum: heapless::Vec<Complex32, 128> = dft(&x).unwrap().collect();
+
+    let mut samples = x.clone();
+    let expected = rfft(&mut samples).unwrap();
+
+    for (k, b) in expected.bins().enumerate() {
+        assert!(close(spectrum[k], b, 1e-3), "bin {}", k);
+    }
+    // and the negative frequencies rfft leaves out are the conjugates
+    for k in 1..64 {
+        assert!(close(spectrum[128 - k], spectrum[k].conj(), 1e-3));
+    }
+}
+
+#[test]
+fn odd_length_matches_plan() {
+    const N: usize = 75;
+    let mut noise = noise(3);
+    let mut x = [Complex32::new(0.0, 0.0); N];
+    x.iter_mut()
+        .for_each(|x| *x = Complex32::new(noise.next().unwrap(), noise.next().unwrap()));
+
+    let mut storage = [Complex32::new(0.0, 0.0); N];
+    let mut scratch = [Complex32::new(0.0, 0.0); N];
+    let plan = Plan::new(N, &mut storage, &mut scratch).unwrap();
+    let mut expected = x;
+    plan.fft(&mut expected, &mut scratch).unwrap();
+
+    for (k, (a, b)) in dft(&x).unwrap().zip(expected.iter()).enumerate() {
+        assert!(close(a, *b, 1e-4), "bin {}", k);
+    }
+}
+
+#[test]
+fn single_bin() {
+    // cos at bin 3 of 16 with amplitude 2 puts N at bins 3 and 13
+    let x: heapless::Vec<f32, 16> = (0..16)
+        .map(|n| 2.0 * (2.0 * core::f32::consts::PI * 3.0 * n as f32 / 16.0).cos())
+        .collect();
+
+    assert!(close(
+        dft_bin(&x, 3).unwrap(),
+        Complex32::new(16.0, 0.0),
+        1e-4
+    ));
+    assert!(close(
+        dft_bin(&x, 13).unwrap(),
+        Complex32::new(16.0, 0.0),
+        1e-4
+    ));
+    assert!(close(
+        dft_bin(&x, 4).unwrap(),
+        Complex32::new(0.0, 0.0),
+        1e-4
+    ));
+    // k wraps around
+    assert_eq!(dft_bin(&x, 19).unwrap(), dft_bin(&x, 3).unwrap());
+}
+
+#[test]
+fn round_trip() {
+    let x: heapless::Vec<f32, 30> = noise(4).take(30).collect();
+
+    let spectrum: heapless::Vec<Complex32, 30> = dft(&x).unwrap().collect();
+    let back: heapless::Vec<Complex32, 30> =
+        idft(&spectrum, Normalization::OneOverN).unwrap().collect();
+
+    for (a, b) in back.iter().zip(x.iter()) {
+        assert!(close(*a, Complex32::new(*b, 0.0), 1e-5));
+    }
+}
+
+#[test]
+fn lengths_are_checked() {
+    let mut x: heapless::Vec<f32, 16> = heapless::Vec::new();
+    x.push(1.0).unwrap();
+
+    assert!(matches!(
+        dft(&x),
+        Err(Error::Length {
+            expected: 16,
+            found: 1
+        })
+    ));
+    assert_eq!(
+        dft_bin::<_, _, 8>(&[0f32; 4][..], 0),
+        Err(Error::Length {
+            expected: 8,
+            found: 4
+        })
+    );
+}
@@ -0,0 +1,251 @@
+use dsp::backend::{CmsisModel, Fft, Microfft, Reference, Selected};
+use dsp::differential::{Differential, Mismatch, Report};
+use dsp::fft::Normalization;
+use dsp::rfft::RealSpectrum;
+use dsp::Error;
+use microfft::Complex32;
+
+/// f32 rounding leaves about 115dB at a few thousand points, well over this
+const MIN_SNR: f32 = 80.0;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn signal<const N: usize>(seed: u32) -> [Complex32; N] {
+    let mut x = [Complex32::new(0.0, 0.0); N];
+    let mut noise = noise(seed);
+    for c in x.iter_mut() {
+        *c = Complex32::new(noise.next().unwrap(), noise.next().unwrap());
+    }
+    x
+}
+
+fn real<const N: usize>(seed: u32) -> [f32; N] {
+    let mut x = [0f32; N];
+    x.iter_mut().zip(noise(seed)).for_each(|(x, n)| *x = n);
+    x
+}
+
+/// Every transform of T against Reference at N
+fn against_reference<T: Fft<N>, const N: usize>(name: &str)
+where
+    Reference: Fft<N>,
+{
+    let x = signal::<N>(N as u32);
+    let h = signal::<N>(N as u32 + 1);
+
+    let runs = [
+        ("fft", Differential::fft::<Reference, T>(&x)),
+        (
+            "ifft",
+            Differential::ifft::<Reference, T>(&x, Normalization::OneOverN),
+        ),
+        (
+            "unitary ifft",
+            Differential::ifft::<Reference, T>(&x, Normalization::Unitary),
+        ),
+        (
+            "rfft",
+            Differential::rfft::<Reference, T>(&real::<N>(N as u32)),
+        ),
+        ("filter", Differential::filter::<Reference, T>(&x, &h)),
+    ];
+    for (what, outputs) in runs.iter() {
+        let report = outputs.report(MIN_SNR);
+        assert!(report.agrees(), "{} {} N={}\n{}", name, what, N, report);
+        assert_eq!(report.mismatch, None);
+    }
+}
+
+#[test]
+fn backends_agree_with_reference() {
+    against_reference::<Microfft, 16>("microfft");
+    against_reference::<Microfft, 256>("microfft");
+    against_reference::<Microfft, 1024>("microfft");
+
+    against_reference::<CmsisModel, 32>("cmsis");
+    against_reference::<CmsisModel, 512>("cmsis");
+    against_reference::<CmsisModel, 2048>("cmsis");
+
+    against_reference::<Selected, 64>("selected");
+}
+
+#[test]
+fn microfft_agrees_with_cmsis() {
+    let x = signal::<1024>(1);
+    let outputs = Differential::fft::<CmsisModel, Microfft>(&x);
+    let report = outputs.report(MIN_SNR);
+    assert!(report.agrees(), "{}", report);
+    assert_eq!(report.errors().count(), 1024);
+}
+
+#[test]
+fn cmsis_rfft_packs_nyquist_into_dc() {
+    // a constant and an alternating sign, all DC and all Nyquist
+    let mut input = [1.5f32; 32];
+    input.iter_mut().skip(1).step_by(2).for_each(|x| *x = 0.5);
+    let mut output = [0f32; 32];
+    CmsisModel::rfft_fast(&input, &mut output);
+    assert!((output[0] - 32.0).abs() < 1e-4);
+    assert!((output[1] - 16.0).abs() < 1e-4);
+    assert!(output[2..].iter().all(|x| x.abs() < 1e-4));
+
+    let spectrum = RealSpectrum::from_packed(dsp::complex::from_interleaved_mut(&mut output));
+    assert!((spectrum.bin(16).unwrap().re - 16.0).abs() < 1e-4);
+}
+
+#[test]
+fn bit_reversal_is_spotted() {
+    // the bitReverseFlag left off
+    struct Unreversed;
+    impl<const N: usize> Fft<N> for Unreversed {
+        fn fft(buffer: &mut [Complex32; N]) {
+            CmsisModel::cfft(buffer, false, false);
+        }
+
+        fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_> {
+            Reference::rfft(buffer)
+        }
+    }
+
+    let x = signal::<64>(2);
+    let outputs = Differential::fft::<Reference, Unreversed>(&x);
+    let report = outputs.report(MIN_SNR);
+    assert!(!report.agrees());
+    assert_eq!(report.mismatch, Some(Mismatch::BitReversed));
+    assert!(
+        report.to_string().contains("bit reversed order"),
+        "{}",
+        report
+    );
+
+    // bins 0 and N-1 are their own reversal so dont move
+    let errors: Vec<f32> = report.errors().collect();
+    assert!(errors[0] < 1e-4 && errors[63] < 1e-4);
+}
+
+#[test]
+fn scaling_is_spotted() {
+    // an inverse that doesnt scale at all, CMSIS's always does
+    struct Unscaled;
+    impl<const N: usize> Fft<N> for Unscaled {
+        fn fft(buffer: &mut [Complex32; N]) {
+            Microfft::fft(buffer);
+        }
+
+        fn ifft(buffer: &mut [Complex32; N], _: Normalization) {
+            Microfft::ifft(buffer, Normalization::None);
+        }
+
+        fn rfft(buffer: &mut [f32; N]) -> RealSpectrum<'_> {
+            Microfft::rfft(buffer)
+        }
+    }
+
+    let x = signal::<128>(3);
+    let outputs = Differential::ifft::<Reference, Unscaled>(&x, Normalization::OneOverN);
+    let report = outputs.report(MIN_SNR);
+    match report.mismatch {
+        Some(Mismatch::Scaled { factor }) => assert!((factor - 128.0).abs() < 1e-2, "{}", factor),
+        mismatch => panic!("{:?}\n{}", mismatch, report),
+    }
+    assert!(report.to_string().contains("N for N=128"), "{}", report);
+
+    // the forward transforms agree, only the inverse is out
+    assert!(Differential::fft::<Reference, Unscaled>(&x)
+        .report(MIN_SNR)
+        .agrees());
+
+    let outputs = Differential::ifft::<Reference, Unscaled>(&x, Normalization::Unitary);
+    let report = outputs.report(MIN_SNR);
+    assert!(
+        report.to_string().contains("sqrt(N) for N=128"),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn both_at_once() {
+    let x = signal::<32>(4);
+    let mut reference = x;
+    let mut test = x;
+    Reference::fft(&mut reference);
+    CmsisModel::cfft(&mut test, false, false);
+    test.iter_mut().for_each(|c| *c *= 0.5);
+
+    let report = Report::new(&reference, &test, MIN_SNR).unwrap();
+    match report.mismatch {
+        Some(Mismatch::BitReversedAndScaled { factor }) => assert!((factor - 0.5).abs() < 1e-5),
+        mismatch => panic!("{:?}\n{}", mismatch, report),
+    }
+}
+
+#[test]
+fn unexplained_differences_list_the_bins() {
+    let reference = signal::<64>(5);
+    let mut test = reference;
+    for k in [3, 10, 40].iter() {
+        test[*k] += Complex32::new(0.5, -0.5);
+    }
+
+    let report = Report::new(&reference, &test, MIN_SNR).unwrap();
+    assert!(!report.agrees());
+    assert_eq!(report.mismatch, None);
+    assert!((report.max_error - 0.5f32.hypot(0.5)).abs() < 1e-6);
+    assert_eq!(report.worst, 3);
+
+    let text = report.to_string();
+    let rows: Vec<&str> = text.lines().collect();
+    // summary, header and the three bins
+    assert_eq!(rows.len(), 5, "{}", text);
+    assert!(rows[2].trim_start().starts_with("3 "), "{}", text);
+    assert!(rows[4].trim_start().starts_with("40 "), "{}", text);
+}
+
+#[test]
+fn the_same_output_is_infinitely_close() {
+    let x = signal::<16>(6);
+    let report = Report::new(&x, &x, MIN_SNR).unwrap();
+    assert!(report.agrees());
+    assert_eq!(report.snr, f32::INFINITY);
+    assert_eq!(report.max_error, 0.0);
+    // nothing to list
+    assert_eq!(report.to_string().lines().count(), 1);
+}
+
+#[test]
+fn nan_never_agrees() {
+    let x = signal::<16>(7);
+    let mut test = x;
+    test[5].re = f32::NAN;
+    let report = Report::new(&x, &test, MIN_SNR).unwrap();
+    assert!(!report.agrees());
+    assert_eq!(report.worst, 5);
+    assert!(report.max_error.is_nan());
+}
+
+#[test]
+fn lengths_have_to_match() {
+    let x = signal::<16>(8);
+    assert_eq!(
+        Report::new(&x, &x[..8], MIN_SNR).unwrap_err(),
+        Error::Length {
+            expected: 16,
+            found: 8
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "CMSIS has no FFT of length 8")]
+fn cmsis_model_only_has_cmsis_lengths() {
+    let mut x = signal::<8>(9);
+    CmsisModel::fft(&mut x);
+}
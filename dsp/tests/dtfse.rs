@@ -0,0 +1,148 @@
+use core::f32::consts::PI;
+use dsp::dft::dft;
+use dsp::dtfse::Series;
+use dsp::fft::cfft;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn coefficients<const N: usize>(x: &[f32]) -> Vec<Complex32> {
+    let mut buffer = [Complex32::new(0.0, 0.0); N];
+    buffer
+        .iter_mut()
+        .zip(x)
+        .for_each(|(c, &x)| *c = Complex32::new(x, 0.0));
+    cfft(&mut buffer).unwrap().to_vec()
+}
+
+fn square(n: usize) -> Vec<f32> {
+    (0..n).map(|i| if i < n / 2 { 1.0 } else { 0.0 }).collect()
+}
+
+#[test]
+fn every_harmonic_gives_back_the_samples() {
+    let x: Vec<f32> = noise(1).take(16).collect();
+    let coefficients = coefficients::<16>(&x);
+    let series = Series::new(&coefficients);
+    assert_eq!(series.max_harmonics(), 8);
+
+    for (y, x) in series.synthesize(8, 16).zip(&x) {
+        assert!((y - x).abs() < 1e-5, "{} != {}", y, x);
+    }
+    assert!(series.mse(8, &x) < 1e-10);
+    // asking for more than there are is the same as all of them
+    assert_eq!(series.at(3.0, 8), series.at(3.0, 100));
+}
+
+#[test]
+fn odd_lengths_have_no_nyquist_bin() {
+    let x: [f32; 15] = [
+        0.3, -0.1, 0.8, 0.2, -0.5, 0.0, 0.9, -0.7, 0.4, 0.1, -0.2, 0.6, -0.9, 0.5, 0.05,
+    ];
+    let coefficients: Vec<Complex32> = dft(&x).unwrap().collect();
+    let series = Series::new(&coefficients);
+    assert_eq!(series.max_harmonics(), 7);
+
+    for (y, x) in series.synthesize(7, 15).zip(x.iter()) {
+        assert!((y - x).abs() < 1e-5, "{} != {}", y, x);
+    }
+}
+
+#[test]
+fn one_harmonic_is_the_whole_cosine() {
+    // X[1] alone would only be half of it, X[N-1] is the other half
+    let x: Vec<f32> = (0..16)
+        .map(|n| 0.25 + 0.75 * (2.0 * PI * n as f32 / 16.0 + 0.6).cos())
+        .collect();
+    let coefficients = coefficients::<16>(&x);
+    let series = Series::new(&coefficients);
+
+    for (y, x) in series.synthesize(1, 16).zip(&x) {
+        assert!((y - x).abs() < 1e-5, "{} != {}", y, x);
+    }
+    // DC alone is the mean
+    assert!(series.synthesize(0, 16).all(|y| (y - 0.25).abs() < 1e-6));
+}
+
+#[test]
+fn between_samples_is_the_smooth_cosine() {
+    let x: Vec<f32> = (0..16)
+        .map(|n| (2.0 * PI * 3.0 * n as f32 / 16.0).sin())
+        .collect();
+    let coefficients = coefficients::<16>(&x);
+    let series = Series::new(&coefficients);
+
+    for (i, y) in series.synthesize(8, 160).enumerate() {
+        let expected = (2.0 * PI * 3.0 * i as f32 / 160.0).sin();
+        assert!((y - expected).abs() < 1e-5, "{}: {} != {}", i, y, expected);
+    }
+}
+
+#[test]
+fn square_mse_falls_as_harmonics_are_added() {
+    let x = square(16);
+    let coefficients = coefficients::<16>(&x);
+    let series = Series::new(&coefficients);
+
+    let reports: Vec<_> = series.convergence(&x, 16).collect();
+    assert_eq!(reports.len(), 9);
+    for pair in reports.windows(2) {
+        assert!(pair[1].mse <= pair[0].mse + 1e-7, "{:?}", pair);
+    }
+    // DC alone is off by half everywhere
+    assert!((reports[0].mse - 0.25).abs() < 1e-6);
+    assert!(reports[8].mse < 1e-10);
+}
+
+#[test]
+fn square_overshoot_settles_at_gibbs() {
+    // long enough period that the partial sums look like the continuous
+    // square wave's, evaluated finely enough to catch the peak
+    let x = square(1024);
+    let coefficients = coefficients::<1024>(&x);
+    let series = Series::new(&coefficients);
+
+    let mut previous: Option<f32> = None;
+    for &harmonics in &[25, 51, 101] {
+        let overshoot = series.overshoot(harmonics, &x, 16 * 1024);
+        assert!(
+            (overshoot - 0.0895).abs() < 0.005,
+            "{} harmonics: {}",
+            harmonics,
+            overshoot
+        );
+        // doesnt get any smaller with more harmonics
+        if let Some(previous) = previous {
+            assert!((overshoot - previous).abs() < 0.003);
+        }
+        previous = Some(overshoot);
+    }
+}
+
+#[test]
+fn triangle_has_no_gibbs_overshoot() {
+    // no jump for it to ring at
+    let x: Vec<f32> = (0..64)
+        .map(|n| {
+            let n = n as f32;
+            if n < 32.0 {
+                n / 16.0 - 1.0
+            } else {
+                3.0 - n / 16.0
+            }
+        })
+        .collect();
+    let coefficients = coefficients::<64>(&x);
+    let series = Series::new(&coefficients);
+
+    for report in series.convergence(&x, 512) {
+        assert!(report.overshoot < 0.01, "{:?}", report);
+    }
+}
@@ -0,0 +1,149 @@
+use dsp::dtmf::{detect, Decoder, Key, Thresholds};
+
+const SAMPLE_RATE: f32 = 8000.0;
+const BLOCK: usize = 205;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+/// Each key for tone_ms then silence for gap_ms, with noise over all of it
+fn dial(keys: &str, tone_ms: usize, gap_ms: usize, noise_level: f32, seed: u32) -> Vec<f32> {
+    let per_ms = SAMPLE_RATE as usize / 1000;
+    let mut signal = vec![];
+    for c in keys.chars() {
+        let key = Key::from_char(c).unwrap();
+        signal.extend(key.tone(SAMPLE_RATE, 0.5).take(tone_ms * per_ms));
+        signal.extend((0..gap_ms * per_ms).map(|_| 0.0));
+    }
+    signal
+        .iter()
+        .zip(noise(seed))
+        .map(|(x, n)| x + noise_level * n)
+        .collect()
+}
+
+fn decode(signal: &[f32]) -> String {
+    let mut decoder = Decoder::new(SAMPLE_RATE, BLOCK, Thresholds::default());
+    decoder.process(signal).map(Key::to_char).collect()
+}
+
+/// One block of a key with the column tone twist dB below the row tone
+fn twisted(key: Key, twist_db: f32) -> Vec<f32> {
+    let (row, column) = key.frequencies();
+    let gain = 10f32.powf(-twist_db / 20.0);
+    (0..BLOCK)
+        .map(|n| {
+            let t = n as f32 / SAMPLE_RATE;
+            let row = (2.0 * core::f32::consts::PI * row * t).sin();
+            let column = (2.0 * core::f32::consts::PI * column * t).sin();
+            0.5 * (row + gain * column)
+        })
+        .collect()
+}
+
+#[test]
+fn keys_round_trip_through_chars_and_positions() {
+    for c in "123A456B789C*0#D".chars() {
+        let key = Key::from_char(c).unwrap();
+        assert_eq!(key.to_char(), c);
+        let (row, column) = key.position();
+        assert_eq!(Key::from_position(row, column), Some(key));
+    }
+    assert_eq!(Key::from_char('a'), Some(Key::A));
+    assert_eq!(Key::from_char('x'), None);
+    assert_eq!(Key::Five.frequencies(), (770.0, 1336.0));
+}
+
+#[test]
+fn decodes_a_noisy_sequence() {
+    let keys = "1234567890*#ABCD";
+    let signal = dial(keys, 60, 60, 0.1, 1);
+    assert_eq!(decode(&signal), keys);
+}
+
+#[test]
+fn decodes_repeated_keys_separated_by_gaps() {
+    let signal = dial("55500", 60, 60, 0.1, 2);
+    assert_eq!(decode(&signal), "55500");
+}
+
+#[test]
+fn held_key_is_reported_once() {
+    let signal = dial("9", 500, 60, 0.05, 3);
+    assert_eq!(decode(&signal), "9");
+}
+
+#[test]
+fn silence_and_noise_arent_keys() {
+    let silence = vec![0.0; BLOCK];
+    assert_eq!(detect(&silence, SAMPLE_RATE, &Thresholds::default()), None);
+
+    let hiss: Vec<f32> = noise(4).take(BLOCK).collect();
+    assert_eq!(detect(&hiss, SAMPLE_RATE, &Thresholds::default()), None);
+
+    let signal: Vec<f32> = noise(5).take(20 * BLOCK).map(|x| 0.3 * x).collect();
+    assert_eq!(decode(&signal), "");
+}
+
+#[test]
+fn single_tone_isnt_a_key() {
+    let tone: Vec<f32> = (0..BLOCK)
+        .map(|n| (2.0 * core::f32::consts::PI * 852.0 * n as f32 / SAMPLE_RATE).sin())
+        .collect();
+    assert_eq!(detect(&tone, SAMPLE_RATE, &Thresholds::default()), None);
+}
+
+#[test]
+fn tones_buried_in_noise_arent_keys() {
+    // both tones are there but most of the energy isnt in them
+    let signal: Vec<f32> = Key::Seven
+        .tone(SAMPLE_RATE, 0.1)
+        .zip(noise(6))
+        .map(|(x, n)| x + n)
+        .take(BLOCK)
+        .collect();
+    assert_eq!(detect(&signal, SAMPLE_RATE, &Thresholds::default()), None);
+}
+
+#[test]
+fn twist_limits() {
+    let thresholds = Thresholds::default();
+
+    // column below row, normal twist, allowed to 8 dB
+    assert_eq!(
+        detect(&twisted(Key::Three, 6.0), SAMPLE_RATE, &thresholds),
+        Some(Key::Three)
+    );
+    assert_eq!(
+        detect(&twisted(Key::Three, 10.0), SAMPLE_RATE, &thresholds),
+        None
+    );
+
+    // column above row, reverse twist, allowed to 4 dB
+    assert_eq!(
+        detect(&twisted(Key::Hash, -3.0), SAMPLE_RATE, &thresholds),
+        Some(Key::Hash)
+    );
+    assert_eq!(
+        detect(&twisted(Key::Hash, -6.0), SAMPLE_RATE, &thresholds),
+        None
+    );
+}
+
+#[test]
+fn tones_between_two_keys_arent_either() {
+    // two row tones at once, neither stands out from the other
+    let signal: Vec<f32> = Key::One
+        .tone(SAMPLE_RATE, 0.5)
+        .zip(Key::Four.tone(SAMPLE_RATE, 0.5))
+        .map(|(a, b)| a + b)
+        .take(BLOCK)
+        .collect();
+    assert_eq!(detect(&signal, SAMPLE_RATE, &Thresholds::default()), None);
+}
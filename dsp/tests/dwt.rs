@@ -0,0 +1,293 @@
+use dsp::dwt::{decomposition_len, dwt, idwt, Decomposition, Extension, Thresholding, Wavelet};
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+const WAVELETS: [Wavelet; 15] = [
+    Wavelet::Haar,
+    Wavelet::Db2,
+    Wavelet::Db3,
+    Wavelet::Db4,
+    Wavelet::Db5,
+    Wavelet::Db6,
+    Wavelet::Db7,
+    Wavelet::Db8,
+    Wavelet::Sym2,
+    Wavelet::Sym3,
+    Wavelet::Sym4,
+    Wavelet::Sym5,
+    Wavelet::Sym6,
+    Wavelet::Sym7,
+    Wavelet::Sym8,
+];
+
+const EXTENSIONS: [Extension; 6] = [
+    Extension::Zero,
+    Extension::Constant,
+    Extension::Symmetric,
+    Extension::Reflect,
+    Extension::Periodic,
+    Extension::Periodization,
+];
+
+fn snr(original: &[f32], estimate: &[f32]) -> f32 {
+    let signal: f32 = original.iter().map(|x| x * x).sum();
+    let error: f32 = original
+        .iter()
+        .zip(estimate)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum();
+    10.0 * (signal / error).log10()
+}
+
+#[test]
+fn filters_are_orthonormal() {
+    for &wavelet in WAVELETS.iter() {
+        let l = wavelet.filter_len();
+        let sum: f32 = wavelet.filter().iter().sum();
+        assert!(
+            (sum - core::f32::consts::SQRT_2).abs() < 1e-5,
+            "{:?}",
+            wavelet
+        );
+
+        // orthogonal to itself shifted by any even amount, and to the high
+        // pass at every even shift
+        for shift in (0..l).step_by(2) {
+            let low: f32 = (shift..l)
+                .map(|j| wavelet.low(j) * wavelet.low(j - shift))
+                .sum();
+            let expected = if shift == 0 { 1.0 } else { 0.0 };
+            assert!((low - expected).abs() < 1e-5, "{:?} {}", wavelet, shift);
+
+            let cross: f32 = (shift..l)
+                .map(|j| wavelet.low(j) * wavelet.high(j - shift))
+                .sum();
+            assert!(cross.abs() < 1e-5, "{:?} {}", wavelet, shift);
+        }
+    }
+}
+
+#[test]
+fn db2_matches_pywt() {
+    let low = [-0.12940952, 0.22414387, 0.8365163, 0.4829629];
+    let high = [-0.4829629, 0.8365163, -0.22414387, -0.12940952];
+    for j in 0..4 {
+        assert!((Wavelet::Db2.low(j) - low[j]).abs() < 1e-7);
+        assert!((Wavelet::Db2.high(j) - high[j]).abs() < 1e-7);
+    }
+}
+
+#[test]
+fn haar_is_sums_and_differences() {
+    let x = [1.0, 3.0, 2.0, 2.0, 5.0, -1.0];
+    let pairs: Vec<(f32, f32)> = dwt(&x, Wavelet::Haar, Extension::Periodization).collect();
+    // pywt has the detail as the first minus the second
+    let expected = [(4.0, -2.0), (4.0, 0.0), (4.0, 6.0)];
+    assert_eq!(pairs.len(), 3);
+    for ((a, d), (ea, ed)) in pairs.iter().zip(expected.iter()) {
+        let scale = core::f32::consts::FRAC_1_SQRT_2;
+        assert!((a - ea * scale).abs() < 1e-6, "{} {}", a, ea);
+        assert!((d - ed * scale).abs() < 1e-6, "{} {}", d, ed);
+    }
+}
+
+#[test]
+fn db2_sees_no_detail_in_a_line() {
+    // two vanishing moments, so only the ends where the mirroring bends the
+    // line show up in the details
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+    let approximation = [1.767_767, 2.310_789, 5.139_216, 7.967_643, 10.960_155];
+    let detail = [-0.612_372_4, 0.0, 0.0, 0.0, 0.612_372_4];
+    let pairs: Vec<(f32, f32)> = dwt(&x, Wavelet::Db2, Extension::Symmetric).collect();
+    assert_eq!(pairs.len(), 5);
+    for (o, &(a, d)) in pairs.iter().enumerate() {
+        assert!((a - approximation[o]).abs() < 1e-5, "{}: {}", o, a);
+        assert!((d - detail[o]).abs() < 1e-5, "{}: {}", o, d);
+    }
+}
+
+#[test]
+fn one_level_reconstructs_exactly() {
+    // odd and even, and shorter than the longest filter
+    for &len in [5, 8, 33, 64].iter() {
+        let x: Vec<f32> = noise(len as u32).take(len).collect();
+        for &wavelet in WAVELETS.iter() {
+            for &extension in EXTENSIONS.iter() {
+                let (approximation, detail): (Vec<f32>, Vec<f32>) =
+                    dwt(&x, wavelet, extension).unzip();
+                assert_eq!(approximation.len(), extension.coefficients(len, wavelet));
+
+                let y: Vec<f32> = idwt(&approximation, &detail, wavelet, extension, len)
+                    .unwrap()
+                    .collect();
+                assert_eq!(y.len(), len);
+                for (n, (y, x)) in y.iter().zip(&x).enumerate() {
+                    assert!(
+                        (y - x).abs() < 1e-5,
+                        "{:?} {:?} len {} sample {}: {} != {}",
+                        wavelet,
+                        extension,
+                        len,
+                        n,
+                        y,
+                        x
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn periodization_keeps_the_energy() {
+    let x: Vec<f32> = noise(7).take(64).collect();
+    for &wavelet in WAVELETS.iter() {
+        let energy: f32 = dwt(&x, wavelet, Extension::Periodization)
+            .map(|(a, d)| a * a + d * d)
+            .sum();
+        let expected: f32 = x.iter().map(|x| x * x).sum();
+        assert!((energy - expected).abs() < 1e-3 * expected, "{:?}", wavelet);
+    }
+}
+
+#[test]
+fn idwt_checks_band_lengths() {
+    let a = [0.0; 4];
+    let d = [0.0; 3];
+    assert!(idwt(&a, &d, Wavelet::Haar, Extension::Periodization, 8).is_err());
+    assert!(idwt(&a, &a, Wavelet::Db2, Extension::Symmetric, 8).is_err());
+}
+
+#[test]
+fn multi_level_reconstructs_exactly() {
+    let x: Vec<f32> = noise(3).take(100).collect();
+    for &wavelet in WAVELETS.iter() {
+        for &extension in EXTENSIONS.iter() {
+            let levels = wavelet.max_level(x.len()).max(1);
+            let decomposition: Decomposition<400> =
+                Decomposition::new(&x, wavelet, extension, levels).unwrap();
+            assert_eq!(
+                decomposition.coefficients().len(),
+                decomposition_len(x.len(), wavelet, extension, levels)
+            );
+
+            let mut y = [0.0; 100];
+            decomposition.reconstruct(&mut y).unwrap();
+            for (n, (y, x)) in y.iter().zip(&x).enumerate() {
+                assert!(
+                    (y - x).abs() < 1e-4,
+                    "{:?} {:?} sample {}: {} != {}",
+                    wavelet,
+                    extension,
+                    n,
+                    y,
+                    x
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn levels_match_single_steps() {
+    let x: Vec<f32> = noise(11).take(50).collect();
+    let decomposition: Decomposition<200> =
+        Decomposition::new(&x, Wavelet::Db3, Extension::Symmetric, 3).unwrap();
+
+    let mut approximation = x.clone();
+    for level in 1..=3 {
+        let (a, d): (Vec<f32>, Vec<f32>) =
+            dwt(&approximation, Wavelet::Db3, Extension::Symmetric).unzip();
+        assert_eq!(decomposition.detail(level), &d[..]);
+        approximation = a;
+    }
+    assert_eq!(decomposition.approximation(), &approximation[..]);
+}
+
+#[test]
+fn max_level_matches_pywt() {
+    assert_eq!(Wavelet::Haar.max_level(1024), 10);
+    assert_eq!(Wavelet::Db4.max_level(1024), 7);
+    assert_eq!(Wavelet::Sym8.max_level(100), 2);
+    assert_eq!(Wavelet::Db8.max_level(10), 0);
+}
+
+#[test]
+fn too_small_a_buffer_is_an_error() {
+    let x = [0.0; 64];
+    let needed = decomposition_len(64, Wavelet::Db4, Extension::Symmetric, 2);
+    assert_eq!(
+        Decomposition::<64>::new(&x, Wavelet::Db4, Extension::Symmetric, 2).unwrap_err(),
+        dsp::Error::Length {
+            expected: needed,
+            found: 64
+        }
+    );
+
+    let decomposition: Decomposition<128> =
+        Decomposition::new(&x, Wavelet::Db4, Extension::Symmetric, 2).unwrap();
+    let mut short = [0.0; 63];
+    assert!(decomposition.reconstruct(&mut short).is_err());
+}
+
+#[test]
+fn thresholding() {
+    assert_eq!(Thresholding::Hard.apply(0.5, 1.0), 0.0);
+    assert_eq!(Thresholding::Hard.apply(-3.0, 1.0), -3.0);
+    assert_eq!(Thresholding::Soft.apply(3.0, 1.0), 2.0);
+    assert_eq!(Thresholding::Soft.apply(-3.0, 1.0), -2.0);
+    assert_eq!(Thresholding::Soft.apply(-1.0, 1.0), 0.0);
+}
+
+#[test]
+fn denoising_improves_snr() {
+    // a slow wave with a step in it, where a low pass would smear the edge
+    let clean: Vec<f32> = (0..512)
+        .map(|n| {
+            let t = n as f32 / 512.0;
+            let step = if n >= 300 { 1.0 } else { 0.0 };
+            (2.0 * core::f32::consts::PI * 2.0 * t).sin() + step
+        })
+        .collect();
+    let noisy: Vec<f32> = clean
+        .iter()
+        .zip(noise(5))
+        .map(|(x, e)| x + 0.1 * e)
+        .collect();
+    let before = snr(&clean, &noisy);
+
+    for &thresholding in [Thresholding::Hard, Thresholding::Soft].iter() {
+        let mut decomposition: Decomposition<1024> =
+            Decomposition::new(&noisy, Wavelet::Sym4, Extension::Symmetric, 5).unwrap();
+
+        // uniform noise of amplitude 0.1 has a sigma of 0.1/sqrt(3)
+        let threshold = decomposition.universal_threshold();
+        let expected = 0.1 / 3f32.sqrt() * (2.0 * (512f32).ln()).sqrt();
+        assert!(
+            (threshold - expected).abs() < 0.3 * expected,
+            "{} {}",
+            threshold,
+            expected
+        );
+
+        decomposition.denoise(thresholding);
+        let mut denoised = [0.0; 512];
+        decomposition.reconstruct(&mut denoised).unwrap();
+        let after = snr(&clean, &denoised);
+        // soft shrinks what it keeps too so gains a bit less than hard
+        assert!(
+            after > before + 3.0,
+            "{:?}: {} dB to {} dB",
+            thresholding,
+            before,
+            after
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use dsp::fft::{cfft, cfft_normalized, icfft, icfft_real, Normalization};
+use dsp::Error;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn signal<const N: usize>(seed: u32) -> [Complex32; N] {
+    let mut signal = [Complex32::new(0.0, 0.0); N];
+    let mut noise = noise(seed);
+    for c in signal.iter_mut() {
+        *c = Complex32::new(noise.next().unwrap(), noise.next().unwrap());
+    }
+    signal
+}
+
+fn max_error(a: &[Complex32], b: &[Complex32], scale: f32) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b * scale).norm_sqr().sqrt())
+        .fold(0.0, f32::max)
+}
+
+fn round_trip<const N: usize>() {
+    let x = signal::<N>(N as u32);
+
+    for (normalization, scale) in [
+        (Normalization::OneOverN, 1.0),
+        (Normalization::Unitary, 1.0),
+        (Normalization::None, N as f32),
+    ] {
+        let mut y = x;
+        cfft_normalized(&mut y, normalization).unwrap();
+        icfft(&mut y, normalization).unwrap();
+
+        let error = max_error(&y, &x, scale) / scale;
+        assert!(
+            error < 1e-5,
+            "N={} {:?} round trip error {}",
+            N,
+            normalization,
+            error
+        );
+    }
+}
+
+macro_rules! round_trips {
+    ($($name:ident: $n:literal,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                round_trip::<$n>();
+            }
+        )*
+    };
+}
+
+round_trips! {
+    round_trip_2: 2,
+    round_trip_4: 4,
+    round_trip_8: 8,
+    round_trip_16: 16,
+    round_trip_32: 32,
+    round_trip_64: 64,
+    round_trip_128: 128,
+    round_trip_256: 256,
+    round_trip_512: 512,
+    round_trip_1024: 1024,
+    round_trip_2048: 2048,
+    round_trip_4096: 4096,
+}
+
+#[test]
+fn unitary_preserves_energy() {
+    let x = signal::<256>(7);
+    let mut y = x;
+    cfft_normalized(&mut y, Normalization::Unitary).unwrap();
+
+    let energy = |s: &[Complex32]| s.iter().map(|c| c.norm_sqr()).sum::<f32>();
+    assert!((energy(&x) - energy(&y)).abs() < 1e-3 * energy(&x));
+}
+
+#[test]
+fn real_output() {
+    let x: heapless::Vec<f32, 64> = noise(3).take(64).collect();
+    let mut y: heapless::Vec<Complex32, 64> = x.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+
+    cfft(&mut y).unwrap();
+    let back: heapless::Vec<f32, 64> = icfft_real(&mut y, Normalization::OneOverN)
+        .unwrap()
+        .collect();
+
+    for (a, b) in x.iter().zip(back.iter()) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn unfilled_vec_is_an_error() {
+    let mut v: heapless::Vec<Complex32, 16> = heapless::Vec::new();
+    v.push(Complex32::new(1.0, 0.0)).unwrap();
+
+    assert_eq!(
+        cfft(&mut v).err(),
+        Some(Error::Length {
+            expected: 16,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn slices_are_length_checked() {
+    let mut x = signal::<32>(1);
+
+    assert!(cfft::<_, 32>(&mut x[..]).is_ok());
+    assert_eq!(
+        cfft::<_, 16>(&mut x[..]).err(),
+        Some(Error::Length {
+            expected: 16,
+            found: 32
+        })
+    );
+}
@@ -0,0 +1,145 @@
+use core::f64::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::goertzel::{Bank, Goertzel};
+use dsp::rfft::rfft;
+use microfft::Complex32;
+
+const N: usize = 256;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn close(a: Complex32, b: Complex32, tolerance: f32) -> bool {
+    (a - b).norm_sqr().sqrt() < tolerance
+}
+
+/// sum of x[n] e^(-i 2 pi f n) in f64
+fn dtft(x: &[f32], turns: f64) -> Complex32 {
+    let (re, im) = x.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, &x)| {
+        let phase = -2.0 * PI * turns * n as f64;
+        (re + x as f64 * phase.cos(), im + x as f64 * phase.sin())
+    });
+    Complex32::new(re as f32, im as f32)
+}
+
+#[test]
+fn matches_the_fft_on_integer_bins() {
+    let x: Vec<f32> = noise(1).take(N).collect();
+    let mut buffer = [0.0; N];
+    buffer.copy_from_slice(&x);
+    let spectrum = rfft(&mut buffer).unwrap();
+
+    for k in [0, 1, 7, 32, 100, N / 2] {
+        let mut goertzel = Goertzel::new(k, N);
+        goertzel.process(&x);
+        let expected = spectrum.bin(k).unwrap();
+
+        assert!(
+            close(goertzel.bin(), expected, 1e-3),
+            "bin {}: {} != {}",
+            k,
+            goertzel.bin(),
+            expected
+        );
+        // s1 and s2 nearly cancel near DC and Nyquist so allow for rounding
+        // relative to the signal rather than the bin
+        let power = expected.norm_sqr();
+        assert!(
+            (goertzel.power() - power).abs() < 1e-3 * power.max(N as f32),
+            "bin {}: {} != {}",
+            k,
+            goertzel.power(),
+            power
+        );
+    }
+}
+
+#[test]
+fn generalized_matches_the_dtft_between_bins() {
+    let x: Vec<f32> = noise(2).take(205).collect();
+
+    for &frequency in &[697.0, 1209.0, 1633.0, 3999.0] {
+        let mut goertzel = Goertzel::with_frequency(frequency, 8000.0);
+        goertzel.process(&x);
+        let expected = dtft(&x, frequency as f64 / 8000.0);
+
+        assert!(
+            close(goertzel.bin(), expected, 1e-3),
+            "{}Hz: {} != {}",
+            frequency,
+            goertzel.bin(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn pushing_a_sample_at_a_time_matches_a_block() {
+    let x: Vec<f32> = noise(3).take(100).collect();
+
+    let mut block = Goertzel::with_frequency(12.3, 100.0);
+    block.process(&x);
+    let mut streamed = Goertzel::with_frequency(12.3, 100.0);
+    x.iter().for_each(|&x| streamed.push(x));
+
+    assert_eq!(block.bin(), streamed.bin());
+    assert_eq!(streamed.count(), 100);
+}
+
+#[test]
+fn reset_starts_a_new_block() {
+    let x: Vec<f32> = noise(4).take(2 * N).collect();
+
+    let mut goertzel = Goertzel::new(5, N);
+    goertzel.process(&x[..N]);
+    goertzel.reset();
+    goertzel.process(&x[N..]);
+
+    let mut fresh = Goertzel::new(5, N);
+    fresh.process(&x[N..]);
+    assert_eq!(goertzel.bin(), fresh.bin());
+}
+
+#[test]
+fn tone_power_is_half_amplitude_times_length_squared() {
+    let amplitude = 0.7;
+    let x: Vec<f32> = (0..N)
+        .map(|n| amplitude * (2.0 * PI * 10.0 * n as f64 / N as f64 + 0.4).cos() as f32)
+        .collect();
+
+    let mut goertzel = Goertzel::new(10, N);
+    goertzel.process(&x);
+
+    let expected = (amplitude * N as f32 / 2.0).powi(2);
+    assert!((goertzel.power() - expected).abs() < 1e-4 * expected);
+    // phase of a cosine comes straight through
+    assert!((goertzel.bin().arg() - 0.4).abs() < 1e-4);
+}
+
+#[test]
+fn bank_picks_out_each_tone() {
+    let frequencies = [5.0, 12.5, 20.0, 31.0];
+    let x: Vec<f32> = (0..400)
+        .map(|n| {
+            let t = n as f64 / 100.0;
+            ((2.0 * PI * 12.5 * t).sin() + 0.5 * (2.0 * PI * 31.0 * t).sin()) as f32
+        })
+        .collect();
+
+    let mut bank = Bank::new(frequencies, 100.0);
+    bank.process(&x);
+    let powers = bank.powers();
+
+    assert!((powers[1] - 200.0f32.powi(2)).abs() < 1e-3 * 200.0f32.powi(2));
+    assert!((powers[3] - 100.0f32.powi(2)).abs() < 1e-3 * 100.0f32.powi(2));
+    assert!(powers[0] < 1.0 && powers[2] < 1.0);
+
+    bank.reset();
+    assert_eq!(bank.powers(), [0.0; 4]);
+}
@@ -0,0 +1,258 @@
+use core::f32::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::hilbert::{
+    analytic, envelope, instantaneous_frequency, instantaneous_phase, Hilbert, Unwrap,
+};
+use dsp::window::Window;
+use microfft::Complex32;
+
+const N: usize = 1024;
+const SAMPLE_RATE: f32 = 100.0;
+
+/// The chirp from 4_10, DC up to Nyquist over N samples
+fn chirp() -> Vec<f32> {
+    (0..N)
+        .map(|n| {
+            let n = n as f32;
+            (PI * n * n / (2.0 * (N as f32 - 1.0))).cos()
+        })
+        .collect()
+}
+
+/// Its frequency in Hz at sample n, the derivative of its phase
+fn chirp_frequency(n: f32) -> f32 {
+    n / (N as f32 - 1.0) * SAMPLE_RATE / 2.0
+}
+
+/// A 20Hz carrier at 100Hz sampling, its amplitude swinging by half at
+/// 0.5Hz
+fn am() -> (Vec<f32>, Vec<f32>) {
+    let envelope: Vec<f32> = (0..N)
+        .map(|n| 1.0 + 0.5 * (2.0 * PI * 0.5 * n as f32 / SAMPLE_RATE).cos())
+        .collect();
+    let signal = envelope
+        .iter()
+        .enumerate()
+        .map(|(n, a)| a * (2.0 * PI * 20.0 * n as f32 / SAMPLE_RATE).cos())
+        .collect();
+    (signal, envelope)
+}
+
+fn complex(x: &[f32]) -> Vec<Complex32> {
+    x.iter().map(|&re| Complex32::new(re, 0.0)).collect()
+}
+
+#[test]
+fn analytic_cosine_is_a_phasor() {
+    let x: Vec<f32> = (0..64)
+        .map(|n| 0.7 * (2.0 * PI * 5.0 * n as f32 / 64.0 + 0.4).cos())
+        .collect();
+    let mut buffer = complex(&x);
+    analytic::<_, 64>(&mut buffer[..]).unwrap();
+
+    for (n, z) in buffer.iter().enumerate() {
+        let expected = Complex32::from_polar(0.7, 2.0 * PI * 5.0 * n as f32 / 64.0 + 0.4);
+        assert!(
+            (z - expected).magnitude() < 1e-5,
+            "{}: {} != {}",
+            n,
+            z,
+            expected
+        );
+        // the real part is left as it was
+        assert!((z.re - x[n]).abs() < 1e-5);
+    }
+}
+
+/// Least squares slope and intercept of y against x
+fn fit(points: &[(f32, f32)]) -> (f32, f32) {
+    let len = points.len() as f32;
+    let mean_x = points.iter().map(|p| p.0).sum::<f32>() / len;
+    let mean_y = points.iter().map(|p| p.1).sum::<f32>() / len;
+    let covariance: f32 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f32 = points.iter().map(|p| (p.0 - mean_x) * (p.0 - mean_x)).sum();
+    let slope = covariance / variance;
+    (slope, mean_y - slope * mean_x)
+}
+
+#[test]
+fn chirp_frequency_sweeps_linearly() {
+    let mut buffer = complex(&chirp());
+    analytic::<_, N>(&mut buffer[..]).unwrap();
+
+    let frequencies: Vec<f32> =
+        instantaneous_frequency(buffer.iter().copied(), SAMPLE_RATE).collect();
+    assert_eq!(frequencies.len(), N - 1);
+
+    // the chirp isnt periodic, the FFT sees it jump from Nyquist back to DC
+    // at the block edge and rings either side of it, so its a line give or
+    // take a ripple that dies away towards the middle
+    let points: Vec<(f32, f32)> = frequencies
+        .iter()
+        .enumerate()
+        .take(N - 100)
+        .skip(100)
+        .map(|(n, &f)| (n as f32 + 0.5, f))
+        .collect();
+    let (slope, intercept) = fit(&points);
+    let expected = chirp_frequency(1.0);
+    assert!((slope - expected).abs() < 0.01 * expected, "{}", slope);
+    assert!(intercept.abs() < 0.2, "{}", intercept);
+
+    for &(n, f) in points.iter() {
+        let expected = chirp_frequency(n);
+        let distance = n.min(N as f32 - n);
+        // about 2Hz at 100 samples in down to 0.4Hz in the middle
+        assert!(
+            (f - expected).abs() < 200.0 / distance,
+            "{}: {} != {}",
+            n,
+            f,
+            expected
+        );
+    }
+}
+
+#[test]
+fn fir_follows_the_chirp() {
+    // no block edges, but below a few Hz and near Nyquist the 63 taps cant
+    // make a quarter turn
+    let mut hilbert = Hilbert::<63>::new(Window::Blackman);
+    let output: Vec<Complex32> = chirp().iter().map(|&x| hilbert.push(x)).collect();
+    let delay = hilbert.delay() as f32;
+
+    for (n, f) in instantaneous_frequency(output, SAMPLE_RATE)
+        .enumerate()
+        .skip(150)
+        .take(N - 250)
+    {
+        let expected = chirp_frequency(n as f32 + 0.5 - delay);
+        assert!((f - expected).abs() < 0.01, "{}: {} != {}", n, f, expected);
+    }
+}
+
+#[test]
+fn chirp_phase_is_the_quadratic() {
+    let mut hilbert = Hilbert::<63>::new(Window::Blackman);
+    let output: Vec<Complex32> = chirp().iter().map(|&x| hilbert.push(x)).collect();
+    let delay = hilbert.delay();
+
+    // unwrapped its hundreds of radians by the end, counted from where the
+    // filter has settled
+    let phase: Vec<f32> = instantaneous_phase(output).collect();
+    let quadratic = |n: usize| PI * (n * n) as f32 / (2.0 * (N as f32 - 1.0));
+    let start = 150;
+    for (n, phi) in phase.iter().enumerate().take(N - 100).skip(start) {
+        let expected = quadratic(n - delay) - quadratic(start - delay);
+        let found = phi - phase[start];
+        assert!(
+            (found - expected).abs() < 0.01,
+            "{}: {} != {}",
+            n,
+            found,
+            expected
+        );
+    }
+    assert!(phase[N - 100] - phase[start] > 100.0);
+}
+
+#[test]
+fn am_envelope_from_the_fft() {
+    let (signal, expected) = am();
+    let mut buffer = complex(&signal);
+    analytic::<_, N>(&mut buffer[..]).unwrap();
+
+    for (n, (a, e)) in envelope(buffer.iter().copied())
+        .zip(expected.iter())
+        .enumerate()
+        .take(N - 50)
+        .skip(50)
+    {
+        assert!((a - e).abs() < 0.02, "{}: {} != {}", n, a, e);
+    }
+}
+
+#[test]
+fn am_envelope_from_the_fir() {
+    let (signal, expected) = am();
+    let mut hilbert = Hilbert::<63>::new(Window::Blackman);
+    assert_eq!(hilbert.delay(), 31);
+
+    let mut output = vec![Complex32::new(0.0, 0.0); N];
+    hilbert.process(&signal, &mut output).unwrap();
+
+    // once its full, each output is delay samples behind
+    for (n, a) in envelope(output.iter().copied()).enumerate().skip(63) {
+        let e = expected[n - hilbert.delay()];
+        assert!((a - e).abs() < 0.01, "{}: {} != {}", n, a, e);
+    }
+}
+
+#[test]
+fn fir_matches_the_fft_away_from_the_edges() {
+    // a tone right on a bin so the FFT version is exact
+    let x: Vec<f32> = (0..256)
+        .map(|n| (2.0 * PI * 40.0 * n as f32 / 256.0).sin())
+        .collect();
+    let mut fft = complex(&x);
+    analytic::<_, 256>(&mut fft[..]).unwrap();
+
+    let mut hilbert = Hilbert::<31>::new(Window::Blackman);
+    let delay = hilbert.delay();
+    for (n, &x) in x.iter().enumerate() {
+        let z = hilbert.push(x);
+        if n >= 31 {
+            let expected = fft[n - delay];
+            assert!(
+                (z - expected).magnitude() < 1e-3,
+                "{}: {} != {}",
+                n,
+                z,
+                expected
+            );
+        }
+    }
+
+    // reset starts it filling up again
+    hilbert.reset();
+    assert!(hilbert.push(1.0).magnitude() < 1e-6);
+}
+
+#[test]
+fn fir_frequency_of_a_tone() {
+    let mut hilbert = Hilbert::<63>::new(Window::Hann);
+    let output: Vec<Complex32> = (0..512)
+        .map(|n| hilbert.push((2.0 * PI * 13.0 * n as f32 / SAMPLE_RATE).cos()))
+        .skip(63)
+        .collect();
+    for f in instantaneous_frequency(output, SAMPLE_RATE) {
+        assert!((f - 13.0).abs() < 0.05, "{}", f);
+    }
+}
+
+#[test]
+fn unwrap_keeps_counting() {
+    let mut unwrap = Unwrap::new();
+    // a phasor turning a third of a turn a sample, wrapped every 3 samples
+    let mut expected = 0.0;
+    for n in 0..30 {
+        let turn = 2.0 * PI / 3.0;
+        let wrapped = Complex32::from_polar(1.0, n as f32 * turn).arg();
+        let phi = unwrap.push(wrapped);
+        assert!(
+            (phi - expected).abs() < 1e-3,
+            "{}: {} != {}",
+            n,
+            phi,
+            expected
+        );
+        expected += turn;
+    }
+}
+
+#[test]
+fn process_checks_lengths() {
+    let mut hilbert = Hilbert::<7>::new(Window::Rectangular);
+    let mut output = [Complex32::new(0.0, 0.0); 3];
+    assert!(hilbert.process(&[0.0; 4], &mut output).is_err());
+}
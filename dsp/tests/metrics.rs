@@ -0,0 +1,183 @@
+use dsp::metrics::{self, Diff};
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+#[test]
+fn the_same_sequence_has_no_error() {
+    let x: Vec<f32> = noise(1).take(100).collect();
+    let comparison = metrics::compare(&x, &x).unwrap();
+    assert_eq!(comparison.len, 100);
+    assert_eq!(comparison.mse, 0.0);
+    assert_eq!(comparison.rmse, 0.0);
+    assert_eq!(comparison.snr, f32::INFINITY);
+    assert_eq!(comparison.max_absolute, 0.0);
+    assert_eq!(comparison.max_relative, 0.0);
+    assert_eq!(comparison.max_ulps, 0);
+    assert!((comparison.correlation - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn one_value_off() {
+    let reference = [1.0, 2.0, 3.0, 4.0];
+    let test = [1.0, 2.0, 3.0, 5.0];
+
+    assert_eq!(metrics::mse(&reference, &test).unwrap(), 0.25);
+    assert_eq!(metrics::rmse(&reference, &test).unwrap(), 0.5);
+    assert_eq!(metrics::max_absolute_error(&reference, &test).unwrap(), 1.0);
+    assert_eq!(metrics::max_relative_error(&reference, &test).unwrap(), 0.25);
+
+    // 30 of signal against 1 of error
+    let snr = metrics::snr(&reference, &test).unwrap();
+    assert!((snr - 10.0 * 30.0f32.log10()).abs() < 1e-4, "{}", snr);
+
+    let comparison = metrics::compare(&reference, &test).unwrap();
+    assert_eq!(comparison.worst, 3);
+}
+
+#[test]
+fn sqnr_is_about_6db_a_bit() {
+    // a full scale sine quantized to b bits is 6.02b + 1.76dB
+    let sine: Vec<f32> = (0..4096)
+        .map(|n| (2.0 * core::f32::consts::PI * 0.0123 * n as f32).sin())
+        .collect();
+
+    for &bits in &[8, 12] {
+        let step = 2.0 / (1u32 << bits) as f32;
+        let quantized: Vec<f32> = sine.iter().map(|x| (x / step).round() * step).collect();
+        let sqnr = metrics::snr(&sine, &quantized).unwrap();
+        let expected = 6.02 * bits as f32 + 1.76;
+        assert!((sqnr - expected).abs() < 1.0, "{} bits {}dB", bits, sqnr);
+    }
+}
+
+#[test]
+fn ulps_count_floats() {
+    let next = f32::from_bits(1.0f32.to_bits() + 1);
+    assert_eq!(metrics::ulps(1.0, next), 1);
+    assert_eq!(metrics::ulps(next, 1.0), 1);
+    assert_eq!(metrics::ulps(1.0, 1.0), 0);
+    assert_eq!(metrics::ulps(0.0, -0.0), 0);
+
+    // either side of zero counts through it
+    let tiny = f32::from_bits(1);
+    assert_eq!(metrics::ulps(tiny, -tiny), 2);
+    assert_eq!(metrics::ulps(f32::MAX, f32::INFINITY), 1);
+    assert_eq!(metrics::ulps(f32::NAN, 1.0), u32::MAX);
+
+    // the same relative error is more ulps at the bottom of a power of two
+    let reference = [1.0, 1024.0];
+    let test = [1.0, 1024.0 + 1024.0 * f32::EPSILON * 3.0];
+    assert_eq!(metrics::max_ulps(&reference, &test).unwrap(), 3);
+}
+
+#[test]
+fn relative_error_of_zero() {
+    assert_eq!(
+        metrics::max_relative_error(&[0.0, 2.0], &[0.0, 1.0]).unwrap(),
+        0.5
+    );
+    assert_eq!(
+        metrics::max_relative_error(&[0.0, 2.0], &[1e-30, 2.0]).unwrap(),
+        f32::INFINITY
+    );
+}
+
+#[test]
+fn correlation_ignores_scale_and_offset() {
+    let x: Vec<f32> = noise(2).take(256).collect();
+    let scaled: Vec<f32> = x.iter().map(|x| 3.0 * x + 7.0).collect();
+    let flipped: Vec<f32> = x.iter().map(|x| -0.5 * x).collect();
+    let other: Vec<f32> = noise(3).take(256).collect();
+
+    assert!((metrics::correlation(&x, &scaled).unwrap() - 1.0).abs() < 1e-6);
+    assert!((metrics::correlation(&x, &flipped).unwrap() + 1.0).abs() < 1e-6);
+    // two lots of independent noise hardly correlate
+    assert!(metrics::correlation(&x, &other).unwrap().abs() < 0.2);
+    assert!(metrics::correlation(&x, &[1.0; 256]).unwrap().is_nan());
+}
+
+#[test]
+fn nan_is_never_close() {
+    let reference = [1.0, 2.0, 3.0];
+    let test = [1.0, f32::NAN, 3.0];
+    let comparison = metrics::compare(&reference, &test).unwrap();
+    assert!(comparison.max_absolute.is_nan());
+    assert_eq!(comparison.worst, 1);
+    assert!(comparison.rmse.is_nan());
+    assert!(comparison.snr.is_nan());
+    assert_eq!(comparison.max_ulps, u32::MAX);
+}
+
+#[test]
+fn lengths_have_to_match() {
+    let error = dsp::Error::Length {
+        expected: 3,
+        found: 2,
+    };
+    assert_eq!(metrics::mse(&[0.0; 3], &[0.0; 2]).unwrap_err(), error);
+    assert_eq!(metrics::snr(&[0.0; 3], &[0.0; 2]).unwrap_err(), error);
+    assert_eq!(metrics::max_ulps(&[0.0; 3], &[0.0; 2]).unwrap_err(), error);
+    assert_eq!(metrics::correlation(&[0.0; 3], &[0.0; 2]).unwrap_err(), error);
+}
+
+#[test]
+fn diff_lists_the_first_values_out() {
+    let reference: Vec<f32> = (0..20).map(|x| x as f32).collect();
+    let mut test = reference.clone();
+    for x in test.iter_mut().skip(5) {
+        *x += 0.5;
+    }
+    test[2] += 0.01;
+
+    let diff = Diff::new(&reference, &test, 0.1).to_string();
+    let rows: Vec<&str> = diff.lines().collect();
+    // header, 8 rows then the rest counted
+    assert_eq!(rows.len(), 10, "{}", diff);
+    assert!(rows[1].trim_start().starts_with("5 "), "{}", diff);
+    assert_eq!(rows[9], "... and 7 more");
+
+    // with no tolerance the small one is out too
+    let diff = Diff::new(&reference, &test, 0.0).to_string();
+    assert!(diff.lines().nth(1).unwrap().trim_start().starts_with("2 "));
+}
+
+#[test]
+fn asserts_pass_when_close() {
+    let x: Vec<f32> = noise(4).take(64).collect();
+    let y: Vec<f32> = x.iter().map(|x| x + 1e-6).collect();
+    metrics::assert_close(&x, &y, 1e-5);
+    metrics::assert_snr(&x, &y, 80.0);
+    metrics::assert_ulps(&x, &x, 0);
+}
+
+#[test]
+#[should_panic(expected = "not within 1e-3")]
+fn assert_close_panics() {
+    metrics::assert_close(&[1.0, 2.0], &[1.0, 2.1], 1e-3);
+}
+
+#[test]
+#[should_panic(expected = "snr under 60dB")]
+fn assert_snr_panics() {
+    metrics::assert_snr(&[1.0, 2.0], &[1.0, 2.1], 60.0);
+}
+
+#[test]
+#[should_panic(expected = "not within 1 ulps")]
+fn assert_ulps_panics() {
+    let next = f32::from_bits(2.0f32.to_bits() + 2);
+    metrics::assert_ulps(&[1.0, 2.0], &[1.0, next], 1);
+}
+
+#[test]
+#[should_panic(expected = "reference has 2 values but test has 1")]
+fn assert_panics_on_length() {
+    metrics::assert_close(&[1.0, 2.0], &[1.0], 1.0);
+}
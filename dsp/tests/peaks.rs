@@ -0,0 +1,188 @@
+use core::f32::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::peaks::{find, Interpolation, Options, Peak};
+use dsp::rfft::rfft;
+use dsp::window::{Symmetry, Window};
+use microfft::Complex32;
+
+const N: usize = 256;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+/// Tones at fractional bins, windowed, as the N/2+1 complex bins
+fn spectrum(tones: &[(f32, f32)], window: Window, noise_level: f32) -> Vec<Complex32> {
+    let mut noise = noise(7);
+    let mut x = [0.0; N];
+    for (n, (x, w)) in x
+        .iter_mut()
+        .zip(window.coefficients(N, Symmetry::Periodic))
+        .enumerate()
+    {
+        let tone: f32 = tones
+            .iter()
+            .map(|&(amplitude, bin)| amplitude * (2.0 * PI * bin * n as f32 / N as f32 + 0.3).cos())
+            .sum();
+        *x = w * (tone + noise_level * noise.next().unwrap());
+    }
+    rfft(&mut x).unwrap().bins().collect()
+}
+
+fn magnitude(spectrum: &[Complex32]) -> Vec<f32> {
+    spectrum.iter().map(|c| c.magnitude()).collect()
+}
+
+fn options(interpolation: Interpolation) -> Options {
+    Options {
+        interpolation,
+        ..Options::default()
+    }
+}
+
+fn worst_error(window: Window, interpolation: Interpolation, complex: bool) -> f32 {
+    let mut worst: f32 = 0.0;
+    for i in 0..20 {
+        let bin = 40.0 + i as f32 * 0.05;
+        let spectrum = spectrum(&[(1.0, bin)], window, 0.0);
+        let peaks: heapless::Vec<Peak, 1> = if complex {
+            find(&spectrum, &options(interpolation))
+        } else {
+            find(&magnitude(&spectrum), &options(interpolation))
+        };
+        worst = worst.max((peaks[0].position() - bin).abs());
+    }
+    worst
+}
+
+// sweeping a tone across a bin from 40 to 41, the worst error of each
+// estimator is its bias at some offset. These are a little over what each
+// one measures.
+#[test]
+fn off_bin_tones() {
+    for (window, interpolation, complex, tolerance) in [
+        // half a bin off at worst without interpolating
+        (Window::Hann, Interpolation::None, false, 0.5),
+        (Window::Rectangular, Interpolation::Parabolic, false, 0.25),
+        (Window::Hann, Interpolation::Parabolic, false, 0.06),
+        (Window::Hann, Interpolation::Gaussian, false, 0.02),
+        // exact for a true Gaussian, this one is cut off at the ends
+        (
+            Window::Gaussian { alpha: 3.0 },
+            Interpolation::Gaussian,
+            false,
+            0.003,
+        ),
+        // the leftover error is the negative frequency of a real tone
+        // leaking in
+        (Window::Rectangular, Interpolation::Quinn, true, 0.005),
+    ] {
+        let error = worst_error(window, interpolation, complex);
+        assert!(
+            error <= tolerance,
+            "{:?} {:?} {}",
+            window,
+            interpolation,
+            error
+        );
+    }
+
+    // without phase Quinn is parabolic
+    assert_eq!(
+        worst_error(Window::Rectangular, Interpolation::Quinn, false),
+        worst_error(Window::Rectangular, Interpolation::Parabolic, false)
+    );
+}
+
+#[test]
+fn top_k_in_hz() {
+    // 100Hz sample rate like the accelerometer, three tones in noise
+    let resolution = 100.0 / N as f32;
+    let tones = [(0.25, 90.2), (1.0, 30.3), (0.5, 60.7)];
+    let mag = magnitude(&spectrum(&tones, Window::Hann, 0.05));
+
+    let peaks: heapless::Vec<Peak, 3> = find(&mag, &options(Interpolation::Gaussian));
+    assert_eq!(peaks.len(), 3);
+    // highest first
+    for (peak, &(_, bin)) in peaks.iter().zip([tones[1], tones[2], tones[0]].iter()) {
+        assert!((peak.frequency(resolution) - bin * resolution).abs() < 0.03 * resolution);
+    }
+    // interpolating lifts the height back most of the way to the true one,
+    // N/4 times the amplitude for Hann, from the 6% scalloping lost at 0.3
+    // of a bin
+    assert!((peaks[0].magnitude - N as f32 / 4.0).abs() < 0.02 * N as f32 / 4.0);
+    assert!(peaks[0].magnitude >= mag[30]);
+
+    // more peaks asked for than there are tones picks up noise, fewer gets
+    // the highest
+    let peaks: heapless::Vec<Peak, 1> = find(&mag, &Options::default());
+    assert_eq!(peaks[0].bin, 30);
+}
+
+#[test]
+fn separation() {
+    // two tones 3 bins apart
+    let mag = magnitude(&spectrum(&[(1.0, 50.0), (0.8, 53.0)], Window::Hann, 0.0));
+
+    let peaks: heapless::Vec<Peak, 2> = find(&mag, &Options::default());
+    assert_eq!(peaks.iter().map(|p| p.bin).collect::<Vec<_>>(), [50, 53]);
+
+    // the threshold keeps out the rounding error between the tones
+    let far_apart = Options {
+        min_separation: 4,
+        threshold: Some(1.0),
+        ..Options::default()
+    };
+    let peaks: heapless::Vec<Peak, 2> = find(&mag, &far_apart);
+    assert_eq!(peaks.len(), 1);
+    assert_eq!(peaks[0].bin, 50);
+}
+
+#[test]
+fn threshold_and_prominence() {
+    //          0    1    2    3    4    5    6    7    8    9
+    let mag = [9.0, 1.0, 5.0, 4.0, 4.5, 2.0, 6.0, 3.0, 3.0, 8.0];
+    let none = Options {
+        interpolation: Interpolation::None,
+        ..Options::default()
+    };
+    let bins = |options: &Options| -> Vec<usize> {
+        let peaks: heapless::Vec<Peak, 8> = find(&mag[..], options);
+        peaks.iter().map(|p| p.bin).collect()
+    };
+
+    // the ends dont count even though theyre highest
+    assert_eq!(bins(&none), [6, 2, 4]);
+
+    let peaks: heapless::Vec<Peak, 8> = find(&mag[..], &none);
+    // 6 drops to 2 on the left before the 9 and to 3 on the right before the
+    // 8, so it stands 3 above the higher of those
+    assert_eq!(peaks[0].prominence, 3.0);
+    // 2 drops to 1 on the left and 2 on the right before the 6
+    assert_eq!(peaks[1].prominence, 3.0);
+    // 4.5 only drops to 4 before the 5
+    assert_eq!(peaks[2].prominence, 0.5);
+
+    let threshold = Options {
+        threshold: Some(5.0),
+        ..none
+    };
+    assert_eq!(bins(&threshold), [6, 2]);
+
+    let prominence = Options {
+        prominence: Some(1.0),
+        ..none
+    };
+    assert_eq!(bins(&prominence), [6, 2]);
+
+    // nothing to find in something flat or too short
+    let peaks: heapless::Vec<Peak, 2> = find(&[1.0; 8][..], &none);
+    assert!(peaks.is_empty());
+    let peaks: heapless::Vec<Peak, 2> = find(&[1.0, 2.0][..], &none);
+    assert!(peaks.is_empty());
+}
@@ -0,0 +1,165 @@
+use core::f32::consts::PI;
+use dsp::fft::{cfft, Normalization};
+use dsp::plan::Plan;
+use dsp::Error;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn signal(len: usize, seed: u32) -> Vec<Complex32> {
+    let mut noise = noise(seed);
+    (0..len)
+        .map(|_| Complex32::new(noise.next().unwrap(), noise.next().unwrap()))
+        .collect()
+}
+
+/// The naive DFT from lab4-native 4_1_dft_calculations. It accumulates the
+/// imaginary part with the opposite sign so what comes out is conj(X[k]).
+fn dft<I: Iterator<Item = Complex32> + Clone>(
+    input: I,
+    size: usize,
+) -> impl Iterator<Item = Complex32> {
+    (0..size).map(move |k| {
+        let size = size as f32;
+        let (re, im) = input.clone().enumerate().fold(
+            (0f32, 0f32),
+            |(mut sum_re, mut sum_im), (n, complex)| {
+                let n = n as f32;
+                sum_re += complex.re * (2.0 * PI * k as f32 * n / size).cos()
+                    + complex.im * (2.0 * PI * k as f32 * n / size).sin();
+                sum_im += -complex.im * (2.0 * PI * k as f32 * n / size).cos()
+                    + complex.re * (2.0 * PI * k as f32 * n / size).sin();
+
+                (sum_re, sum_im)
+            },
+        );
+        Complex32::new(re, im)
+    })
+}
+
+fn plan_fft(x: &[Complex32]) -> Vec<Complex32> {
+    let len = x.len();
+    let mut storage = vec![Complex32::new(0.0, 0.0); Plan::storage_len(len)];
+    let mut scratch = vec![Complex32::new(0.0, 0.0); Plan::scratch_len(len)];
+    let plan = Plan::new(len, &mut storage, &mut scratch).unwrap();
+
+    let mut y = x.to_vec();
+    plan.fft(&mut y, &mut scratch).unwrap();
+    y
+}
+
+fn matches_dft(len: usize) {
+    let x = signal(len, len as u32);
+    let y = plan_fft(&x);
+
+    // 4_1 takes cos and sin of 2 pi k n/N in f32 without wrapping, so its own
+    // error grows with N
+    let tolerance = 1e-4 * len as f32;
+    for (k, (y, expected)) in y.iter().zip(dft(x.iter().cloned(), len)).enumerate() {
+        let error = (y - expected.conj()).norm_sqr().sqrt();
+        assert!(
+            error < tolerance,
+            "N={} bin {} {:?} vs {:?}",
+            len,
+            k,
+            y,
+            expected.conj()
+        );
+    }
+}
+
+#[test]
+fn every_length_to_64_matches_dft() {
+    for len in 1..=64 {
+        matches_dft(len);
+    }
+}
+
+#[test]
+fn lab_lengths_match_dft() {
+    // lab5 5_1, lab1 accelerometer_usage_ii, and a couple of primes
+    for &len in &[100, 1000, 97, 1009, 2 * 3 * 5 * 7] {
+        matches_dft(len);
+    }
+}
+
+#[test]
+fn powers_of_two_match_cfft() {
+    let x = signal(512, 3);
+    let y = plan_fft(&x);
+
+    let mut expected = [Complex32::new(0.0, 0.0); 512];
+    expected.copy_from_slice(&x);
+    cfft(&mut expected).unwrap();
+
+    for (y, expected) in y.iter().zip(expected.iter()) {
+        assert!((y - expected).norm_sqr().sqrt() < 1e-3);
+    }
+}
+
+#[test]
+fn round_trip() {
+    for &len in &[1, 7, 12, 100, 243, 1000, 1013] {
+        let mut storage = vec![Complex32::new(0.0, 0.0); Plan::storage_len(len)];
+        let mut scratch = vec![Complex32::new(0.0, 0.0); Plan::scratch_len(len)];
+        let plan = Plan::new(len, &mut storage, &mut scratch).unwrap();
+
+        let x = signal(len, 11);
+        for &normalization in &[Normalization::OneOverN, Normalization::Unitary] {
+            let mut y = x.clone();
+            plan.fft_normalized(&mut y, &mut scratch, normalization)
+                .unwrap();
+            plan.ifft(&mut y, &mut scratch, normalization).unwrap();
+
+            for (y, x) in y.iter().zip(x.iter()) {
+                assert!(
+                    (y - x).norm_sqr().sqrt() < 1e-5,
+                    "N={} {:?}",
+                    len,
+                    normalization
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn sizes_are_checked() {
+    let len = 97;
+    let mut storage = vec![Complex32::new(0.0, 0.0); Plan::storage_len(len)];
+    let mut scratch = vec![Complex32::new(0.0, 0.0); Plan::scratch_len(len)];
+
+    let expected = storage.len();
+    assert_eq!(
+        Plan::new(len, &mut storage[1..], &mut scratch).err(),
+        Some(Error::Length {
+            expected,
+            found: expected - 1
+        })
+    );
+
+    let plan = Plan::new(len, &mut storage, &mut scratch).unwrap();
+    assert_eq!(plan.len(), len);
+
+    let mut x = signal(len, 5);
+    let before = x.clone();
+    assert!(plan
+        .ifft(&mut x, &mut scratch[1..], Normalization::OneOverN)
+        .is_err());
+    assert_eq!(x, before);
+
+    assert_eq!(
+        plan.fft(&mut x[1..], &mut scratch),
+        Err(Error::Length {
+            expected: 97,
+            found: 96
+        })
+    );
+}
@@ -0,0 +1,269 @@
+//! Transform identities over random signals, for every length and backend
+//! that runs on the host:
+//!
+//! - dft, the direct sum, at a spread of lengths
+//! - cfft, microfft at every power of two it supports
+//! - rfft, the real FFT, expanded out to all N bins
+//! - Plan, mixed radix and Bluestein at any length
+//!
+//! Tolerances are relative to the size of the whole spectrum rather than
+//! each bin, so a bin thats near zero isnt held to a tighter tolerance than
+//! its rounding allows.
+
+use core::f64::consts::PI;
+use dsp::dft::dft;
+use dsp::fft::cfft;
+use dsp::plan::Plan;
+use dsp::rfft::rfft;
+use microfft::Complex32;
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Backend {
+    Dft,
+    Cfft,
+    Rfft,
+    Plan,
+}
+
+const POWERS_OF_TWO: &[usize] = &[2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096];
+
+/// the direct sum is N^2 so stays short
+const DFT_LENGTHS: &[usize] = &[1, 2, 3, 5, 7, 8, 12, 16, 31, 64, 100, 128];
+
+/// smooth lengths go mixed radix, the rest Bluestein
+const PLAN_LENGTHS: &[usize] = &[1, 2, 3, 5, 6, 7, 12, 17, 30, 64, 97, 100, 127, 360, 1000];
+
+/// Call body with a const N for each length, there has to be a
+/// monomorphised copy for every one
+macro_rules! for_len {
+    ($len:expr, $n:ident => $body:expr; $($size:literal)*) => {
+        match $len {
+            $($size => {
+                const $n: usize = $size;
+                $body
+            })*
+            len => panic!("no transform of length {}", len),
+        }
+    };
+}
+
+fn transform(backend: Backend, x: &[Complex32]) -> Vec<Complex32> {
+    let mut y = x.to_vec();
+    match backend {
+        Backend::Dft => {
+            y = for_len!(x.len(), N => dft::<_, _, N>(x).unwrap().collect();
+                1 2 3 5 7 8 12 16 31 64 100 128);
+        }
+        Backend::Cfft => {
+            for_len!(x.len(), N => { cfft::<_, N>(&mut y[..]).unwrap(); };
+                2 4 8 16 32 64 128 256 512 1024 2048 4096);
+        }
+        Backend::Rfft => {
+            // only the real parts go in, the other half comes back from
+            // X[N-k] = conj(X[k])
+            let n = x.len();
+            let mut real: Vec<f32> = x.iter().map(|c| c.re).collect();
+            let bins: Vec<Complex32> = for_len!(n, N => rfft::<_, N>(&mut real[..]).unwrap().bins().collect();
+                2 4 8 16 32 64 128 256 512 1024 2048 4096);
+            for (k, y) in y.iter_mut().enumerate() {
+                *y = if k < bins.len() {
+                    bins[k]
+                } else {
+                    bins[n - k].conj()
+                };
+            }
+        }
+        Backend::Plan => {
+            let n = x.len();
+            let mut storage = vec![Complex32::new(0.0, 0.0); Plan::storage_len(n)];
+            let mut scratch = vec![Complex32::new(0.0, 0.0); Plan::scratch_len(n)];
+            let plan = Plan::new(n, &mut storage, &mut scratch).unwrap();
+            plan.fft(&mut y, &mut scratch).unwrap();
+        }
+    }
+    y
+}
+
+fn lengths(backend: Backend) -> &'static [usize] {
+    match backend {
+        Backend::Dft => DFT_LENGTHS,
+        Backend::Cfft | Backend::Rfft => POWERS_OF_TWO,
+        Backend::Plan => PLAN_LENGTHS,
+    }
+}
+
+/// A backend and one of its lengths, up to max
+fn backend_and_len(backends: &'static [Backend], max: usize) -> BoxedStrategy<(Backend, usize)> {
+    prop::sample::select(backends)
+        .prop_flat_map(move |backend| {
+            let lengths: Vec<usize> = lengths(backend)
+                .iter()
+                .copied()
+                .filter(|&len| len <= max)
+                .collect();
+            (Just(backend), prop::sample::select(lengths))
+        })
+        .boxed()
+}
+
+fn signal(len: usize, real: bool) -> impl Strategy<Value = Vec<Complex32>> {
+    let im = if real { 0.0..=0.0 } else { -1.0f32..=1.0 };
+    prop::collection::vec((-1.0f32..1.0, im), len)
+        .prop_map(|values| values.into_iter().map(|(re, im)| Complex32::new(re, im)).collect())
+}
+
+const ALL: &[Backend] = &[Backend::Dft, Backend::Cfft, Backend::Rfft, Backend::Plan];
+const COMPLEX: &[Backend] = &[Backend::Dft, Backend::Cfft, Backend::Plan];
+
+fn energy(x: &[Complex32]) -> f64 {
+    x.iter()
+        .map(|c| c.re as f64 * c.re as f64 + c.im as f64 * c.im as f64)
+        .sum()
+}
+
+/// sqrt of the error energy within tolerance of sqrt of the expected energy,
+/// with a floor so all zeros expected doesnt need an exact all zeros
+fn close(expected: &[Complex32], found: &[Complex32], tolerance: f64) -> Result<(), TestCaseError> {
+    prop_assert_eq!(expected.len(), found.len());
+    let error: Vec<Complex32> = expected
+        .iter()
+        .zip(found.iter())
+        .map(|(a, b)| a - b)
+        .collect();
+    let (error, size) = (energy(&error).sqrt(), energy(expected).sqrt());
+    let allowed = tolerance * size + 1e-6 * (expected.len() as f64).sqrt();
+    prop_assert!(
+        error <= allowed,
+        "error {} against {} allowed",
+        error,
+        allowed
+    );
+    Ok(())
+}
+
+/// rounding in f32 grows with log N for the FFTs and with N for the direct
+/// sums, this is well over either
+const TOLERANCE: f64 = 1e-4;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn parseval(
+        (backend, x) in backend_and_len(ALL, 4096).prop_flat_map(|(backend, len)| {
+            (Just(backend), signal(len, backend == Backend::Rfft))
+        })
+    ) {
+        // sum |x|^2 = 1/N sum |X|^2, unscaled
+        let n = x.len() as f64;
+        let time = energy(&x);
+        let frequency = energy(&transform(backend, &x)) / n;
+        prop_assert!(
+            (time - frequency).abs() <= TOLERANCE * time + 1e-9,
+            "{:?} {}: {} != {}", backend, x.len(), time, frequency
+        );
+    }
+
+    #[test]
+    fn linearity(
+        (backend, x, y) in backend_and_len(ALL, 4096).prop_flat_map(|(backend, len)| {
+            let real = backend == Backend::Rfft;
+            (Just(backend), signal(len, real), signal(len, real))
+        }),
+        a in -4.0f32..4.0,
+        b in -4.0f32..4.0,
+    ) {
+        let sum: Vec<Complex32> = x.iter().zip(y.iter()).map(|(x, y)| x * a + y * b).collect();
+        let expected: Vec<Complex32> = transform(backend, &x)
+            .iter()
+            .zip(transform(backend, &y).iter())
+            .map(|(x, y)| x * a + y * b)
+            .collect();
+        close(&expected, &transform(backend, &sum), TOLERANCE)?;
+    }
+
+    #[test]
+    fn complex_linearity(
+        (backend, x, y) in backend_and_len(COMPLEX, 4096).prop_flat_map(|(backend, len)| {
+            (Just(backend), signal(len, false), signal(len, false))
+        }),
+        (a, b) in (-2.0f32..2.0, -2.0f32..2.0),
+    ) {
+        // a complex scale turns the real and imaginary parts into each other
+        let a = Complex32::new(a, b);
+        let scaled: Vec<Complex32> = x.iter().zip(y.iter()).map(|(x, y)| x * a + y).collect();
+        let expected: Vec<Complex32> = transform(backend, &x)
+            .iter()
+            .zip(transform(backend, &y).iter())
+            .map(|(x, y)| x * a + y)
+            .collect();
+        close(&expected, &transform(backend, &scaled), TOLERANCE)?;
+    }
+
+    #[test]
+    fn circular_shift(
+        (backend, x, shift) in backend_and_len(ALL, 4096).prop_flat_map(|(backend, len)| {
+            (Just(backend), signal(len, backend == Backend::Rfft), 0..len)
+        })
+    ) {
+        // x[n - m] round the end has spectrum X[k] e^(-2 pi i k m/N)
+        let n = x.len();
+        let shifted: Vec<Complex32> = (0..n).map(|i| x[(i + n - shift) % n]).collect();
+        let expected: Vec<Complex32> = transform(backend, &x)
+            .iter()
+            .enumerate()
+            .map(|(k, &bin)| {
+                let turns = ((k * shift) % n) as f64 / n as f64;
+                let theta = -2.0 * PI * turns;
+                bin * Complex32::new(theta.cos() as f32, theta.sin() as f32)
+            })
+            .collect();
+        close(&expected, &transform(backend, &shifted), TOLERANCE)?;
+    }
+
+    #[test]
+    fn real_input_is_conjugate_symmetric(
+        (backend, x) in backend_and_len(COMPLEX, 4096).prop_flat_map(|(backend, len)| {
+            (Just(backend), signal(len, true))
+        })
+    ) {
+        // X[N-k] = conj(X[k]), so DC and Nyquist are real. rfft builds the
+        // top half this way so theres nothing to check
+        let spectrum = transform(backend, &x);
+        let n = x.len();
+        let mirrored: Vec<Complex32> = (0..n).map(|k| spectrum[(n - k) % n].conj()).collect();
+        close(&spectrum, &mirrored, TOLERANCE)?;
+    }
+
+    #[test]
+    fn convolution_theorem(
+        (backend, x, h) in backend_and_len(ALL, 512).prop_flat_map(|(backend, len)| {
+            let real = backend == Backend::Rfft;
+            (Just(backend), signal(len, real), signal(len, real))
+        })
+    ) {
+        // the spectrum of the circular convolution is the product of the
+        // spectra, the convolution worked out directly in f64
+        let n = x.len();
+        let convolution: Vec<Complex32> = (0..n)
+            .map(|i| {
+                let (mut re, mut im) = (0.0f64, 0.0f64);
+                for (j, x) in x.iter().enumerate() {
+                    let h = h[(i + n - j) % n];
+                    re += x.re as f64 * h.re as f64 - x.im as f64 * h.im as f64;
+                    im += x.re as f64 * h.im as f64 + x.im as f64 * h.re as f64;
+                }
+                Complex32::new(re as f32, im as f32)
+            })
+            .collect();
+
+        let expected: Vec<Complex32> = transform(backend, &x)
+            .iter()
+            .zip(transform(backend, &h).iter())
+            .map(|(x, h)| x * h)
+            .collect();
+        close(&expected, &transform(backend, &convolution), TOLERANCE)?;
+    }
+}
@@ -0,0 +1,157 @@
+use core::f32::consts::PI;
+use dsp::psd::{Psd, Sides};
+use dsp::window::Window;
+use dsp::Error;
+
+const FS: f32 = 100.0;
+
+/// Deterministic noise in -1..1 so failures are reproducible, variance 1/3
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn mean_square(x: &[f32]) -> f32 {
+    x.iter().map(|x| x * x).sum::<f32>() / x.len() as f32
+}
+
+/// area under the PSD, which should be the mean square
+fn area<const N: usize>(psd: &Psd<N>) -> f32 {
+    psd.psd().sum::<f32>() * psd.resolution()
+}
+
+#[test]
+fn parseval() {
+    // a rectangular periodogram is exactly Parseval, either side
+    let x: Vec<f32> = noise(1).take(256).collect();
+
+    for sides in [Sides::One, Sides::Two] {
+        let mut psd = Psd::<256>::periodogram(FS, sides);
+        psd.add_segment(&x).unwrap();
+        assert!((area(&psd) - mean_square(&x)).abs() < 1e-4 * mean_square(&x));
+    }
+}
+
+#[test]
+fn sine_power() {
+    // amplitude 3 on bin 20 of 256, 7.8125Hz, has mean square 4.5
+    let x: Vec<f32> = (0..256 * 40)
+        .map(|n| 3.0 * (2.0 * PI * 20.0 * n as f32 / 256.0).sin())
+        .collect();
+
+    let mut psd = Psd::<256>::periodogram(FS, Sides::One);
+    psd.add_segment(&x[..256]).unwrap();
+    let bins: Vec<f32> = psd.psd().collect();
+    assert!((bins[20] * psd.resolution() - 4.5).abs() < 1e-3);
+    assert!(bins.iter().enumerate().all(|(k, &p)| k == 20 || p < 1e-6));
+
+    // the window spreads it over neighbouring bins but the area is the same
+    for window in [Window::Hann, Window::Hamming, Window::BlackmanHarris] {
+        let mut psd = Psd::<256>::welch(window, 128, FS, Sides::One);
+        assert_eq!(psd.add_signal(&x), 79);
+        assert!(
+            (area(&psd) - 4.5).abs() < 0.01,
+            "{:?} {}",
+            window,
+            area(&psd)
+        );
+    }
+}
+
+#[test]
+fn white_noise() {
+    // uniform noise has variance 1/3 spread evenly from -fs/2 to fs/2
+    let x: Vec<f32> = noise(2).take(256 * 200).collect();
+    let two_sided = 1.0 / 3.0 / FS;
+
+    let spread = |bins: &[f32], expected: f32| {
+        let mean = bins.iter().sum::<f32>() / bins.len() as f32;
+        let variance = bins.iter().map(|p| (p - mean).powi(2)).sum::<f32>() / bins.len() as f32;
+        assert!(
+            (mean - expected).abs() < 0.1 * expected,
+            "{} {}",
+            mean,
+            expected
+        );
+        variance.sqrt() / mean
+    };
+
+    // one periodogram is as noisy as the signal, each bin is roughly
+    // exponentially distributed so its standard deviation is its mean
+    let mut periodogram = Psd::<256>::periodogram(FS, Sides::Two);
+    periodogram.add_segment(&x[..256]).unwrap();
+    let bins: Vec<f32> = periodogram.psd().collect();
+    assert!(spread(&bins, two_sided) > 0.7);
+
+    // averaging 399 half overlapped Hann segments takes most of that out.
+    // Overlapped segments arent independent so its not quite 1/sqrt(399).
+    let mut welch = Psd::<256>::welch(Window::Hann, 128, FS, Sides::One);
+    welch.add_signal(&x);
+    assert_eq!(welch.segments(), 399);
+    let bins: Vec<f32> = welch.psd().collect();
+    assert!(spread(&bins[1..128], 2.0 * two_sided) < 0.1);
+    assert!((area(&welch) - mean_square(&x)).abs() < 0.01);
+}
+
+#[test]
+fn streaming() {
+    let x: Vec<f32> = noise(3).take(1000).collect();
+
+    let mut batch = Psd::<64>::welch(Window::Hann, 24, FS, Sides::One);
+    let added = batch.add_signal(&x);
+
+    // blocks that dont line up with the hop or the segment length
+    let mut streamed = Psd::<64>::welch(Window::Hann, 24, FS, Sides::One);
+    let pushed: usize = x.chunks(37).map(|block| streamed.push(block)).sum();
+
+    assert_eq!(pushed, added);
+    assert_eq!(streamed.segments(), batch.segments());
+    assert!(streamed.psd().eq(batch.psd()));
+
+    streamed.reset();
+    assert_eq!(streamed.segments(), 0);
+    assert!(streamed.psd().all(|p| p == 0.0));
+    // the samples left over from before reset dont make it into a segment
+    assert_eq!(streamed.push(&x[..63]), 0);
+    assert_eq!(streamed.push(&x[63..64]), 1);
+}
+
+#[test]
+fn axes() {
+    let one = Psd::<8>::periodogram(FS, Sides::One);
+    assert_eq!(one.len(), 5);
+    assert_eq!(one.resolution(), 12.5);
+    assert!(one.frequencies().eq([0.0, 12.5, 25.0, 37.5, 50.0]));
+
+    // fft order like the spectrum itself
+    let mut two = Psd::<8>::periodogram(FS, Sides::Two);
+    assert_eq!(two.len(), 8);
+    assert!(two
+        .frequencies()
+        .eq([0.0, 12.5, 25.0, 37.5, -50.0, -37.5, -25.0, -12.5]));
+
+    // real input so the negative frequencies mirror the positive
+    two.add_segment(&[1.0, 3.0, -2.0, 0.5, 0.0, 1.0, 2.0, -1.0])
+        .unwrap();
+    let bins: Vec<f32> = two.psd().collect();
+    for k in 1..4 {
+        assert_eq!(bins[k], bins[8 - k]);
+    }
+}
+
+#[test]
+fn lengths() {
+    let mut psd = Psd::<16>::periodogram(FS, Sides::One);
+    assert_eq!(
+        psd.add_segment(&[0.0; 15]),
+        Err(Error::Length {
+            expected: 16,
+            found: 15
+        })
+    );
+    assert_eq!(psd.add_signal(&[0.0; 15]), 0);
+    assert_eq!(psd.segments(), 0);
+}
@@ -0,0 +1,137 @@
+use dsp::fft::cfft;
+use dsp::rfft::{rfft, RealSpectrum};
+use dsp::Error;
+use microfft::Complex32;
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+fn matches_cfft<const N: usize>() {
+    let mut x = [0f32; N];
+    x.iter_mut().zip(noise(N as u32)).for_each(|(x, n)| *x = n);
+
+    let mut expected = [Complex32::new(0.0, 0.0); N];
+    expected
+        .iter_mut()
+        .zip(x.iter())
+        .for_each(|(c, &re)| *c = Complex32::new(re, 0.0));
+    cfft(&mut expected).unwrap();
+
+    let spectrum = rfft(&mut x).unwrap();
+    assert_eq!(spectrum.len(), N / 2 + 1);
+
+    for (k, bin) in spectrum.bins().enumerate() {
+        let error = (bin - expected[k]).norm_sqr().sqrt();
+        assert!(
+            error < 1e-4 * N as f32,
+            "N={} bin {} {:?} vs {:?}",
+            N,
+            k,
+            bin,
+            expected[k]
+        );
+    }
+}
+
+macro_rules! matches_cfft {
+    ($($name:ident: $n:literal,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                matches_cfft::<$n>();
+            }
+        )*
+    };
+}
+
+matches_cfft! {
+    matches_cfft_2: 2,
+    matches_cfft_4: 4,
+    matches_cfft_8: 8,
+    matches_cfft_16: 16,
+    matches_cfft_32: 32,
+    matches_cfft_64: 64,
+    matches_cfft_128: 128,
+    matches_cfft_256: 256,
+    matches_cfft_512: 512,
+    matches_cfft_1024: 1024,
+    matches_cfft_2048: 2048,
+    matches_cfft_4096: 4096,
+}
+
+#[test]
+fn dc_and_nyquist_are_unpacked() {
+    // 3 + 2(-1)^n has 3N at DC and 2N at Nyquist
+    let mut x: heapless::Vec<f32, 16> = (0..16)
+        .map(|n| if n & 1 == 1 { 1.0 } else { 5.0 })
+        .collect();
+
+    let spectrum = rfft(&mut x).unwrap();
+
+    assert_eq!(spectrum.packed()[0], Complex32::new(48.0, 32.0));
+    assert_eq!(spectrum.bin(0), Some(Complex32::new(48.0, 0.0)));
+    assert_eq!(spectrum.bin(8), Some(Complex32::new(32.0, 0.0)));
+    assert_eq!(spectrum.bin(9), None);
+    for k in 1..8 {
+        assert!(spectrum.bin(k).unwrap().norm_sqr() < 1e-8);
+    }
+}
+
+#[test]
+fn magnitude_and_power_of_a_tone() {
+    const N: usize = 64;
+    let w = 2.0 * core::f32::consts::PI * 5.0 / N as f32;
+    let mut x = [0f32; N];
+    x.iter_mut()
+        .enumerate()
+        .for_each(|(n, x)| *x = 2.0 * (w * n as f32).cos());
+
+    let spectrum = rfft(&mut x).unwrap();
+
+    // all the energy lands in bin 5 with magnitude A*N/2
+    for (k, (mag, power)) in spectrum.magnitude().zip(spectrum.power()).enumerate() {
+        let expected = if k == 5 { N as f32 } else { 0.0 };
+        assert!((mag - expected).abs() < 1e-3, "bin {} mag {}", k, mag);
+        assert!((power - expected * expected).abs() < 1e-1, "bin {}", k);
+    }
+}
+
+#[test]
+fn from_packed_matches_rfft() {
+    let mut x = [0f32; 32];
+    x.iter_mut().zip(noise(1)).for_each(|(x, n)| *x = n);
+
+    let mut packed = [Complex32::new(0.0, 0.0); 16];
+    packed.copy_from_slice(rfft(&mut x.clone()).unwrap().packed());
+
+    let a = rfft(&mut x).unwrap();
+    let b = RealSpectrum::from_packed(&mut packed);
+    assert!(a.bins().eq(b.bins()));
+}
+
+#[test]
+fn unfilled_vec_is_an_error() {
+    let mut v: heapless::Vec<f32, 16> = heapless::Vec::new();
+    v.push(1.0).unwrap();
+
+    assert!(matches!(
+        rfft(&mut v),
+        Err(Error::Length {
+            expected: 16,
+            found: 1
+        })
+    ));
+    assert!(matches!(
+        rfft::<_, 16>(&mut [0f32; 8][..]),
+        Err(Error::Length {
+            expected: 16,
+            found: 8
+        })
+    ));
+}
@@ -0,0 +1,145 @@
+use dsp::complex::ComplexExt;
+use dsp::rfft::rfft;
+use dsp::sdft::{Method, Sdft};
+use dsp::window::{Symmetry, Window};
+use microfft::Complex32;
+
+const N: usize = 64;
+const BINS: [usize; 5] = [0, 1, 7, 32, 60];
+
+/// Deterministic noise in -1..1 so failures are reproducible
+fn noise(seed: u32) -> impl Iterator<Item = f32> {
+    let mut state = seed;
+    core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    })
+}
+
+/// FFT of the N samples ending at end, windowed, at the requested bins
+fn windowed_fft(signal: &[f32], end: usize, window: Window) -> Vec<Complex32> {
+    let mut x = [0.0; N];
+    x.copy_from_slice(&signal[end + 1 - N..=end]);
+    window.apply(&mut x, Symmetry::Periodic);
+    let spectrum = rfft(&mut x).unwrap();
+
+    // past N/2 its the conjugate of the mirror image for a real signal
+    BINS.iter()
+        .map(|&k| match k {
+            k if k > N / 2 => spectrum.bin(N - k).unwrap().conj(),
+            k => spectrum.bin(k).unwrap(),
+        })
+        .collect()
+}
+
+fn assert_matches(method: Method, window: Window, tolerance: f32) {
+    let signal: Vec<f32> = noise(1).take(5 * N).collect();
+    let mut sdft = Sdft::<N, 5>::new(BINS, window, method);
+
+    for (n, &x) in signal.iter().enumerate() {
+        sdft.push(x);
+        assert_eq!(sdft.is_full(), n + 1 >= N);
+        if n + 1 < N {
+            continue;
+        }
+
+        let expected = windowed_fft(&signal, n, window);
+        for (i, (got, expected)) in sdft.spectrum().iter().zip(expected).enumerate() {
+            assert!(
+                (got - expected).magnitude() < tolerance,
+                "{:?} {:?} sample {} bin {}: {} != {}",
+                method,
+                window,
+                n,
+                BINS[i],
+                got,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn modulated_matches_a_windowed_fft_at_every_position() {
+    for &window in &[
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+        Window::BlackmanHarris,
+        Window::FlatTop,
+    ] {
+        assert_matches(Method::Modulated, window, 1e-4 * N as f32);
+    }
+}
+
+#[test]
+fn sliding_matches_a_windowed_fft_at_every_position() {
+    for &window in &[Window::Rectangular, Window::Hann, Window::Blackman] {
+        assert_matches(Method::Sliding { damping: 1.0 }, window, 1e-3 * N as f32);
+    }
+}
+
+#[test]
+fn modulated_stays_accurate_over_a_long_run() {
+    let signal: Vec<f32> = noise(2).take(200_000).collect();
+    let mut sdft = Sdft::<N, 5>::new(BINS, Window::Hann, Method::Modulated);
+    sdft.process(&signal);
+
+    let expected = windowed_fft(&signal, signal.len() - 1, Window::Hann);
+    for (got, expected) in sdft.spectrum().iter().zip(expected) {
+        assert!((got - expected).magnitude() < 1e-3 * N as f32);
+    }
+}
+
+#[test]
+fn damping_keeps_the_textbook_recurrence_close() {
+    // a tone right on bin 7, damping shrinks older samples a little
+    let signal: Vec<f32> = (0..50_000)
+        .map(|n| (2.0 * core::f32::consts::PI * 7.0 * n as f32 / N as f32).cos())
+        .collect();
+    let mut sdft = Sdft::<N, 1>::new(
+        [7],
+        Window::Rectangular,
+        Method::Sliding { damping: 0.9999 },
+    );
+    sdft.process(&signal);
+
+    let magnitude = sdft.bin(0).magnitude();
+    assert!(
+        (magnitude - N as f32 / 2.0).abs() < 0.01 * N as f32 / 2.0,
+        "{}",
+        magnitude
+    );
+}
+
+#[test]
+fn starts_from_zeros_and_resets() {
+    let signal: Vec<f32> = noise(3).take(N / 2).collect();
+    let mut sdft = Sdft::<N, 5>::new(BINS, Window::Rectangular, Method::Modulated);
+    sdft.process(&signal);
+
+    // half a window in, the rest is still the zeros it started with
+    let mut padded = vec![0.0; N / 2];
+    padded.extend(&signal);
+    let expected = windowed_fft(&padded, N - 1, Window::Rectangular);
+    for (got, expected) in sdft.spectrum().iter().zip(expected) {
+        assert!((got - expected).magnitude() < 1e-4 * N as f32);
+    }
+
+    sdft.reset();
+    assert!(!sdft.is_full());
+    assert_eq!(sdft.spectrum(), [Complex32::new(0.0, 0.0); 5]);
+}
+
+#[test]
+fn bins_wrap_around_n() {
+    let sdft = Sdft::<N, 2>::new([N + 3, 2 * N], Window::Hann, Method::Modulated);
+    assert_eq!(sdft.bins(), &[3, 0]);
+}
+
+#[test]
+#[should_panic]
+fn windows_that_arent_cosine_sums_are_refused() {
+    Sdft::<N, 1>::new([1], Window::Kaiser { beta: 8.0 }, Method::Modulated);
+}
@@ -0,0 +1,173 @@
+use dsp::fft::cfft;
+use dsp::stft::Stft;
+use dsp::window::{Symmetry, Window};
+use dsp::Error;
+use microfft::Complex32;
+
+const N: usize = 1024;
+
+/// The linear chirp from 4_10, 0 to pi over N samples
+fn chirp() -> Vec<f32> {
+    let (w1, w2) = (0.0, core::f32::consts::PI);
+    (0..N)
+        .map(|n| {
+            let n = n as f32;
+            (w1 * n + (w2 - w1) * n * n / (2.0 * (N as f32 - 1.0))).cos()
+        })
+        .collect()
+}
+
+fn round_trip<const M: usize>(window: Window, window_len: usize, hop: usize) -> (Vec<f32>, usize) {
+    let signal = chirp();
+    let stft = Stft::<M>::new(window, window_len, hop).unwrap();
+    let frames: Vec<[Complex32; M]> = stft.frames(&signal).collect();
+    assert_eq!(frames.len(), stft.frame_count(N));
+
+    let mut output = vec![f32::NAN; N];
+    let written = stft.istft(frames, &mut output).unwrap();
+    assert_eq!(written, stft.output_len(stft.frame_count(N)));
+    assert!(output[written..].iter().all(|&y| y == 0.0));
+
+    (output, written)
+}
+
+fn max_error(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn chirp_round_trip() {
+    let signal = chirp();
+
+    // 4_10's Hamming at 50% overlap, 127 frames covering all 1024 samples.
+    // Hamming never reaches zero so every sample comes back.
+    let (output, written) = round_trip::<16>(Window::Hamming, 16, 8);
+    assert_eq!(written, N);
+    assert!(max_error(&output, &signal) < 1e-5);
+
+    // Hann is zero at the first sample so that one cant come back, the rest
+    // can
+    let (output, written) = round_trip::<64>(Window::Hann, 64, 16);
+    assert_eq!(written, N);
+    assert_eq!(output[0], 0.0);
+    assert!(max_error(&output[1..], &signal[1..]) < 1e-5);
+
+    // zero padded FFT and a hop that doesnt divide the length, the tail past
+    // the last whole frame is left out
+    let (output, written) = round_trip::<32>(Window::Kaiser { beta: 6.0 }, 20, 7);
+    assert_eq!(written, (N - 20) / 7 * 7 + 20);
+    assert!(max_error(&output[..written], &signal[..written]) < 1e-5);
+}
+
+#[test]
+fn frames_are_windowed_ffts() {
+    let signal = chirp();
+    let stft = Stft::<16>::new(Window::Hamming, 12, 5).unwrap();
+
+    let window: Vec<f32> = Window::Hamming
+        .coefficients(12, Symmetry::Periodic)
+        .collect();
+    assert_eq!(stft.window(), &window[..]);
+
+    for (m, frame) in stft.frames(&signal).enumerate() {
+        let mut expected = [Complex32::new(0.0, 0.0); 16];
+        for n in 0..12 {
+            expected[n].re = signal[m * 5 + n] * window[n];
+        }
+        cfft(&mut expected).unwrap();
+        assert_eq!(frame, expected);
+    }
+}
+
+#[test]
+fn modified_frames() {
+    // scaling every frame scales the signal, evenly since Hann at 75% overlap
+    // is COLA
+    let signal = chirp();
+    let stft = Stft::<64>::new(Window::Hann, 64, 16).unwrap();
+    assert!(stft.cola().holds());
+
+    let frames = stft.frames(&signal).map(|mut frame| {
+        frame.iter_mut().for_each(|c| *c *= 0.5);
+        frame
+    });
+    let mut output = vec![0.0; N];
+    stft.istft(frames, &mut output).unwrap();
+
+    for (y, x) in output[1..].iter().zip(signal[1..].iter()) {
+        assert!((y - 0.5 * x).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn cola() {
+    // Hann and Hamming w^2 need 75% overlap, 50% only makes w itself constant
+    for (window, len, hop, holds) in [
+        (Window::Hann, 64, 16, true),
+        (Window::Hann, 64, 32, false),
+        (Window::Hamming, 16, 4, true),
+        (Window::Hamming, 16, 8, false),
+        (Window::Blackman, 60, 10, true),
+        (Window::Rectangular, 16, 16, true),
+        (Window::Rectangular, 16, 5, false),
+    ] {
+        let cola = Stft::<64>::new(window, len, hop).unwrap().cola();
+        assert_eq!(
+            cola.holds(),
+            holds,
+            "{:?} {} {} {:?}",
+            window,
+            len,
+            hop,
+            cola
+        );
+        assert!(cola.invertible());
+    }
+
+    // Hann squared at 75% overlap adds up to 3/8 of the window length
+    let cola = Stft::<64>::new(Window::Hann, 64, 16).unwrap().cola();
+    assert!((cola.min - 1.5).abs() < 1e-5);
+
+    // gaps between frames
+    let cola = Stft::<16>::new(Window::Hamming, 16, 20).unwrap().cola();
+    assert!(!cola.invertible());
+    assert_eq!(cola.min, 0.0);
+}
+
+#[test]
+fn lengths() {
+    assert!(matches!(
+        Stft::<16>::new(Window::Hann, 17, 8),
+        Err(Error::Length {
+            expected: 16,
+            found: 17
+        })
+    ));
+
+    let stft = Stft::<16>::new(Window::Hann, 16, 8).unwrap();
+    assert_eq!(stft.frame_count(15), 0);
+    assert_eq!(stft.frame_count(16), 1);
+    assert_eq!(stft.frame_count(31), 2);
+    assert_eq!(stft.frames(&[0.0; 15]).count(), 0);
+    assert_eq!(
+        stft.frame(&[0.0; 20], 8),
+        Err(Error::Length {
+            expected: 24,
+            found: 20
+        })
+    );
+
+    // three frames need 32 samples
+    let frames = [[Complex32::new(0.0, 0.0); 16]; 3];
+    assert_eq!(
+        stft.istft(frames, &mut [0.0; 31]),
+        Err(Error::Length {
+            expected: 32,
+            found: 31
+        })
+    );
+    assert_eq!(stft.istft(frames, &mut [0.0; 40]), Ok(32));
+}
@@ -0,0 +1,166 @@
+use core::f32::consts::PI;
+use dsp::window::{Metrics, Symmetry, Window};
+
+const LEN: usize = 128;
+
+fn assert_metrics(window: Window, expected: Metrics, sidelobe_tolerance: f32) {
+    let m = window.metrics(LEN, Symmetry::Periodic);
+
+    assert!(
+        (m.coherent_gain - expected.coherent_gain).abs() < 0.005,
+        "{:?} coherent gain {}",
+        window,
+        m.coherent_gain
+    );
+    assert!(
+        (m.enbw - expected.enbw).abs() < 0.01,
+        "{:?} enbw {}",
+        window,
+        m.enbw
+    );
+    assert!(
+        (m.scalloping_loss - expected.scalloping_loss).abs() < 0.05,
+        "{:?} scalloping loss {}",
+        window,
+        m.scalloping_loss
+    );
+    assert!(
+        (m.sidelobe_level - expected.sidelobe_level).abs() < sidelobe_tolerance,
+        "{:?} sidelobe level {}",
+        window,
+        m.sidelobe_level
+    );
+}
+
+fn metrics(coherent_gain: f32, enbw: f32, scalloping_loss: f32, sidelobe_level: f32) -> Metrics {
+    Metrics {
+        coherent_gain,
+        enbw,
+        scalloping_loss,
+        sidelobe_level,
+    }
+}
+
+// Harris 1978 table 1, rounded the way the table is. Hamming's scalloping
+// loss is listed as 1.78 but works out to 1.75, which other references agree
+// with.
+#[test]
+fn harris_table() {
+    assert_metrics(Window::Rectangular, metrics(1.0, 1.0, 3.92, -13.3), 0.1);
+    assert_metrics(Window::Hann, metrics(0.5, 1.5, 1.42, -31.5), 0.1);
+    assert_metrics(Window::Hamming, metrics(0.54, 1.36, 1.75, -43.0), 0.5);
+    assert_metrics(Window::Blackman, metrics(0.42, 1.73, 1.10, -58.0), 0.5);
+    assert_metrics(Window::BlackmanHarris, metrics(0.36, 2.0, 0.83, -92.0), 0.5);
+    // Kaiser-Bessel alpha 2 and 3
+    assert_metrics(
+        Window::Kaiser { beta: 2.0 * PI },
+        metrics(0.49, 1.50, 1.46, -46.0),
+        0.5,
+    );
+    assert_metrics(
+        Window::Kaiser { beta: 3.0 * PI },
+        metrics(0.40, 1.80, 1.02, -69.0),
+        1.0,
+    );
+    // cosine tapered alpha 0.5
+    assert_metrics(
+        Window::Tukey { alpha: 0.5 },
+        metrics(0.75, 1.22, 2.24, -15.0),
+        0.5,
+    );
+}
+
+#[test]
+fn flat_top() {
+    let m = Window::FlatTop.metrics(LEN, Symmetry::Periodic);
+
+    assert!((m.coherent_gain - 0.2156).abs() < 0.001);
+    assert!((m.enbw - 3.77).abs() < 0.01);
+    // the whole point, a tone anywhere in the bin reads the same
+    assert!(m.scalloping_loss < 0.02);
+    assert!(m.sidelobe_level < -90.0);
+}
+
+// Harris' Gaussian row doesnt agree with its own definition, so check against
+// the continuous integrals instead. For e^(-(alpha x)^2/2) over -1..1
+// coherent gain = sqrt(pi/2)/alpha erf(alpha/sqrt(2))
+// enbw = sqrt(pi)/(2 alpha) erf(alpha) / coherent gain^2
+#[test]
+fn gaussian() {
+    let m = Window::Gaussian { alpha: 2.5 }.metrics(LEN, Symmetry::Periodic);
+
+    assert!((m.coherent_gain - 0.4951).abs() < 0.005);
+    assert!((m.enbw - 1.4456).abs() < 0.01);
+    assert!(m.sidelobe_level < -42.0);
+}
+
+#[test]
+fn symmetric_and_periodic() {
+    let symmetric: Vec<f32> = Window::Hann.coefficients(5, Symmetry::Symmetric).collect();
+    let periodic: Vec<f32> = Window::Hann.coefficients(4, Symmetry::Periodic).collect();
+
+    assert_eq!(symmetric, [0.0, 0.5, 1.0, 0.5, 0.0]);
+    assert_eq!(periodic, [0.0, 0.5, 1.0, 0.5]);
+
+    for window in [
+        Window::Hamming,
+        Window::Blackman,
+        Window::Kaiser { beta: 5.0 },
+        Window::Tukey { alpha: 0.3 },
+        Window::Gaussian { alpha: 2.0 },
+    ] {
+        let w: Vec<f32> = window.coefficients(33, Symmetry::Symmetric).collect();
+        for n in 0..33 {
+            assert!((w[n] - w[32 - n]).abs() < 1e-6, "{:?} at {}", window, n);
+        }
+        // periodic is the symmetric one a sample longer, minus the last
+        let periodic: Vec<f32> = window.coefficients(32, Symmetry::Periodic).collect();
+        assert_eq!(&w[..32], &periodic[..]);
+    }
+
+    assert_eq!(
+        Window::Blackman
+            .coefficients(1, Symmetry::Symmetric)
+            .collect::<Vec<_>>(),
+        [1.0]
+    );
+}
+
+#[test]
+fn special_cases() {
+    let rect: Vec<f32> = Window::Rectangular
+        .coefficients(16, Symmetry::Periodic)
+        .collect();
+
+    for (window, expected) in [
+        (Window::Tukey { alpha: 0.0 }, Window::Rectangular),
+        (Window::Tukey { alpha: 1.0 }, Window::Hann),
+        (Window::Kaiser { beta: 0.0 }, Window::Rectangular),
+    ] {
+        for (a, b) in window
+            .coefficients(16, Symmetry::Periodic)
+            .zip(expected.coefficients(16, Symmetry::Periodic))
+        {
+            assert!((a - b).abs() < 1e-6, "{:?}", window);
+        }
+    }
+    assert!(rect.iter().all(|&w| w == 1.0));
+}
+
+#[test]
+fn hamming_matches_4_10() {
+    const WINDOW: usize = 16;
+    let inline = (0..WINDOW).map(|m| 0.54 - 0.46 * (2.0 * PI * m as f32 / WINDOW as f32).cos());
+
+    for (a, b) in Window::Hamming
+        .coefficients(WINDOW, Symmetry::Periodic)
+        .zip(inline)
+    {
+        assert!((a - b).abs() < 1e-6);
+    }
+
+    let mut samples = [2.0; WINDOW];
+    Window::Hamming.apply(&mut samples, Symmetry::Periodic);
+    assert!((samples[0] - 0.16).abs() < 1e-6);
+    assert!((samples[8] - 2.0).abs() < 1e-6);
+}
@@ -0,0 +1,60 @@
+//! This project is used for describing the digital systems of lab2 by their
+//! transfer functions. Here we take the multiplier and accumulator with
+//! feedback, y[n] = 2.2x[n] - 1.1x[n-1] + 0.7y[n-1], find its poles and
+//! partial fraction expansion, get its impulse response back with the inverse
+//! z-transform and look at its magnitude response on the unit circle.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over to no_std without alloc.
+//!
+//! `cargo run --example transfer_function`
+
+use lab3::transfer_function::TransferFunction;
+use lab3::{display, Shape};
+
+const N: usize = 20;
+
+fn main() {
+    // H(z) = (2.2 - 1.1z^-1) / (1 - 0.7z^-1)
+    let h = TransferFunction::new(&[2.2, -1.1], &[1.0, -0.7]).unwrap();
+
+    println!("poles: {:?}", h.poles().unwrap());
+    println!("zeros: {:?}", h.zeros().unwrap());
+    println!("stable: {:?}", h.is_stable().unwrap());
+    println!("dc gain: {:?}", h.dc_gain());
+    println!("nyquist gain: {:?}", h.nyquist_gain());
+
+    let pf = h.partial_fractions().unwrap();
+    println!("residues: {:?}", pf.residues);
+    println!("direct: {:?}", pf.direct);
+
+    // difference equation on d[n]
+    display(
+        "impulse response",
+        Shape::Line,
+        h.impulse_response().take(N),
+    );
+
+    // inverse z-transform of the partial fractions, should look the same
+    display(
+        "inverse z-transform",
+        Shape::Line,
+        pf.impulse_response().take(N),
+    );
+
+    display("magnitude response", Shape::Line, h.magnitude_response(64));
+
+    // moving average in series, sharing the same input, and in the feedback path
+    let average = TransferFunction::new(&[0.5, 0.5], &[1.0]).unwrap();
+
+    let series = h.series(&average).unwrap();
+    display("series", Shape::Line, series.magnitude_response(64));
+
+    let parallel = h.parallel(&average).unwrap();
+    display("parallel", Shape::Line, parallel.magnitude_response(64));
+
+    let feedback = h.feedback(&average).unwrap();
+    println!("feedback poles: {:?}", feedback.poles().unwrap());
+    display("feedback", Shape::Line, feedback.magnitude_response(64));
+}
@@ -0,0 +1,414 @@
+//! Rational transfer functions in z^-1, the glue between the difference
+//! equations of lab2 and the frequency analysis of lab4.
+//!
+//! H(z) = (b0 + b1 z^-1 + ... + bM z^-M) / (a0 + a1 z^-1 + ... + aN z^-N)
+//!
+//! So the lab2 system y[n] = 2.2x[n] - 1.1x[n-1] + 0.7y[n-1] is
+//! `TransferFunction::new(&[2.2, -1.1], &[1.0, -0.7])`, note the sign flip on
+//! the feedback coefficients when moving y[n-1] over to the left hand side.
+//!
+//! Everything is kept in heapless storage so it can port over to no_std
+//! without alloc.
+
+use core::f32::consts::PI;
+use core::iter::{Chain, Once, Repeat};
+use heapless::Vec;
+use num_complex::{Complex, Complex32};
+
+/// Most coefficients a numerator or denominator can hold
+pub const MAX_COEFFS: usize = 16;
+
+pub type Coefficients = Vec<f32, MAX_COEFFS>;
+pub type Roots = Vec<Complex32, MAX_COEFFS>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+    /// result would need more than MAX_COEFFS coefficients
+    Capacity,
+    /// the numerator or denominator has no coefficients, or a0 is 0
+    ZeroDenominator,
+    /// partial fractions are only computed for distinct poles
+    RepeatedPole,
+    /// root finding didnt settle
+    NoConvergence,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferFunction {
+    b: Coefficients,
+    a: Coefficients,
+}
+
+impl TransferFunction {
+    /// b are the numerator and a the denominator coefficients, both in
+    /// increasing powers of z^-1. Trailing zeros are trimmed.
+    pub fn new(b: &[f32], a: &[f32]) -> Result<Self, Error> {
+        let b = trim(Coefficients::from_slice(b).map_err(|_| Error::Capacity)?);
+        let a = trim(Coefficients::from_slice(a).map_err(|_| Error::Capacity)?);
+
+        if b.is_empty() || a.is_empty() || a[0] == 0.0 {
+            return Err(Error::ZeroDenominator);
+        }
+
+        Ok(Self { b, a })
+    }
+
+    pub fn numerator(&self) -> &[f32] {
+        &self.b
+    }
+
+    pub fn denominator(&self) -> &[f32] {
+        &self.a
+    }
+
+    /// H1(z)H2(z), output of self feeding other
+    pub fn series(&self, other: &Self) -> Result<Self, Error> {
+        Self::new(&multiply(&self.b, &other.b)?, &multiply(&self.a, &other.a)?)
+    }
+
+    /// H1(z) + H2(z), both fed the same input with their outputs summed
+    pub fn parallel(&self, other: &Self) -> Result<Self, Error> {
+        let b = add(&multiply(&self.b, &other.a)?, &multiply(&other.b, &self.a)?)?;
+        Self::new(&b, &multiply(&self.a, &other.a)?)
+    }
+
+    /// H1(z) / (1 + H1(z)H2(z)), self in the forward path and other in the
+    /// negative feedback path
+    pub fn feedback(&self, other: &Self) -> Result<Self, Error> {
+        let b = multiply(&self.b, &other.a)?;
+        let a = add(&multiply(&self.a, &other.a)?, &multiply(&self.b, &other.b)?)?;
+        Self::new(&b, &a)
+    }
+
+    /// H(z) at any point of the z plane
+    pub fn evaluate(&self, z: Complex32) -> Complex32 {
+        let z_inv = Complex32::new(1.0, 0.0) / z;
+        horner(&self.b, z_inv) / horner(&self.a, z_inv)
+    }
+
+    /// H(e^jw) on the unit circle, w in radians per sample
+    pub fn frequency_response(&self, w: f32) -> Complex32 {
+        self.evaluate(Complex32::new(w.cos(), w.sin()))
+    }
+
+    /// Magnitude of the frequency response at n evenly spaced frequencies from
+    /// 0 to pi inclusive
+    pub fn magnitude_response(&self, n: usize) -> impl Iterator<Item = f32> + Clone + '_ {
+        let step = if n > 1 { PI / (n - 1) as f32 } else { 0.0 };
+        (0..n).map(move |k| {
+            let h = self.frequency_response(k as f32 * step);
+            (h.re * h.re + h.im * h.im).sqrt()
+        })
+    }
+
+    /// H(1), the gain of a constant input
+    pub fn dc_gain(&self) -> f32 {
+        self.b.iter().sum::<f32>() / self.a.iter().sum::<f32>()
+    }
+
+    /// H(-1), the gain of an input alternating every sample
+    pub fn nyquist_gain(&self) -> f32 {
+        alternating_sum(&self.b) / alternating_sum(&self.a)
+    }
+
+    /// Roots of the numerator
+    pub fn zeros(&self) -> Result<Roots, Error> {
+        roots(&self.b)
+    }
+
+    /// Roots of the denominator
+    pub fn poles(&self) -> Result<Roots, Error> {
+        roots(&self.a)
+    }
+
+    /// Stable if every pole is strictly inside the unit circle
+    pub fn is_stable(&self) -> Result<bool, Error> {
+        Ok(self.poles()?.iter().all(|p| p.norm_sqr() < 1.0))
+    }
+
+    /// H(z) = sum(r_i / (1 - p_i z^-1)) + sum(k_j z^-j)
+    ///
+    /// Only distinct poles are supported, repeated poles return
+    /// Error::RepeatedPole.
+    pub fn partial_fractions(&self) -> Result<PartialFractions, Error> {
+        let (direct, remainder) = divide(&self.b, &self.a)?;
+        let poles = self.poles()?;
+
+        for (i, p) in poles.iter().enumerate() {
+            if poles[i + 1..].iter().any(|q| (p - q).norm_sqr() < 1e-6) {
+                return Err(Error::RepeatedPole);
+            }
+        }
+
+        let mut residues = Roots::new();
+        for (i, p) in poles.iter().enumerate() {
+            let p_inv = Complex32::new(1.0, 0.0) / p;
+            let others = poles
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Complex32::new(self.a[0], 0.0), |acc, (_, q)| {
+                    acc * (Complex32::new(1.0, 0.0) - q * p_inv)
+                });
+
+            residues
+                .push(horner(&remainder, p_inv) / others)
+                .map_err(|_| Error::Capacity)?;
+        }
+
+        Ok(PartialFractions {
+            residues,
+            poles,
+            direct,
+        })
+    }
+
+    /// Run the difference equation over an input signal
+    pub fn filter<I>(&self, iter: I) -> Filter<'_, I>
+    where
+        I: Iterator<Item = f32>,
+    {
+        Filter {
+            tf: self,
+            x: [0.0; MAX_COEFFS],
+            y: [0.0; MAX_COEFFS],
+            iter,
+        }
+    }
+
+    /// Inverse z-transform by running the difference equation on d[n], never
+    /// ends so take what you need
+    pub fn impulse_response(&self) -> Filter<'_, Chain<Once<f32>, Repeat<f32>>> {
+        self.filter(core::iter::once(1.0).chain(core::iter::repeat(0.0)))
+    }
+}
+
+/// Result of TransferFunction::partial_fractions
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialFractions {
+    /// r_i paired with poles
+    pub residues: Roots,
+    /// p_i
+    pub poles: Roots,
+    /// k_j, the direct terms left over when the numerator isnt of lower order
+    /// than the denominator
+    pub direct: Coefficients,
+}
+
+impl PartialFractions {
+    /// Inverse z-transform of each term summed, h[n] = sum(r_i p_i^n) + k[n].
+    /// Never ends so take what you need
+    pub fn impulse_response(&self) -> PartialFractionsImpulse<'_> {
+        PartialFractionsImpulse {
+            pf: self,
+            powers: self
+                .poles
+                .iter()
+                .map(|_| Complex32::new(1.0, 0.0))
+                .collect(),
+            n: 0,
+        }
+    }
+}
+
+/// y[n] = (sum(b_k x[n-k]) - sum(a_k y[n-k])) / a0
+#[derive(Clone, Debug)]
+pub struct Filter<'a, I>
+where
+    I: Iterator<Item = f32>,
+{
+    tf: &'a TransferFunction,
+    x: [f32; MAX_COEFFS],
+    y: [f32; MAX_COEFFS],
+    iter: I,
+}
+
+impl<'a, I> Iterator for Filter<'a, I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let val = self.iter.next()?;
+
+        // x[0] is x[n], x[1] is x[n-1].. and y[1] is y[n-1]..
+        self.x.rotate_right(1);
+        self.x[0] = val;
+        self.y.rotate_right(1);
+
+        let forward: f32 = self
+            .tf
+            .b
+            .iter()
+            .zip(self.x.iter())
+            .map(|(b, x)| b * x)
+            .sum();
+        let feedback: f32 = self.tf.a[1..]
+            .iter()
+            .zip(self.y[1..].iter())
+            .map(|(a, y)| a * y)
+            .sum();
+
+        self.y[0] = (forward - feedback) / self.tf.a[0];
+
+        Some(self.y[0])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PartialFractionsImpulse<'a> {
+    pf: &'a PartialFractions,
+    powers: Roots,
+    n: usize,
+}
+
+impl<'a> Iterator for PartialFractionsImpulse<'a> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let mut sum = self.pf.direct.get(self.n).cloned().unwrap_or(0.0);
+
+        for ((power, r), p) in self
+            .powers
+            .iter_mut()
+            .zip(self.pf.residues.iter())
+            .zip(self.pf.poles.iter())
+        {
+            // complex poles come in conjugate pairs so the imaginary parts
+            // cancel in the sum
+            sum += (r * *power).re;
+            *power *= p;
+        }
+
+        self.n += 1;
+
+        Some(sum)
+    }
+}
+
+fn trim(mut coeffs: Coefficients) -> Coefficients {
+    while coeffs.last() == Some(&0.0) {
+        coeffs.pop();
+    }
+    coeffs
+}
+
+fn alternating_sum(coeffs: &[f32]) -> f32 {
+    coeffs
+        .iter()
+        .enumerate()
+        .map(|(k, c)| if k % 2 == 0 { *c } else { -*c })
+        .sum()
+}
+
+/// c0 + c1 x + c2 x^2 ...
+fn horner(coeffs: &[f32], x: Complex32) -> Complex32 {
+    coeffs
+        .iter()
+        .rev()
+        .fold(Complex32::new(0.0, 0.0), |acc, c| acc * x + c)
+}
+
+fn multiply(p: &[f32], q: &[f32]) -> Result<Coefficients, Error> {
+    let len = p.len() + q.len() - 1;
+    if len > MAX_COEFFS {
+        return Err(Error::Capacity);
+    }
+
+    let mut out = Coefficients::new();
+    out.resize(len, 0.0).ok();
+    for (i, p) in p.iter().enumerate() {
+        for (j, q) in q.iter().enumerate() {
+            out[i + j] += p * q;
+        }
+    }
+    Ok(out)
+}
+
+fn add(p: &[f32], q: &[f32]) -> Result<Coefficients, Error> {
+    let mut out = Coefficients::new();
+    out.resize(p.len().max(q.len()), 0.0)
+        .map_err(|_| Error::Capacity)?;
+    for (o, p) in out.iter_mut().zip(p.iter()) {
+        *o += p;
+    }
+    for (o, q) in out.iter_mut().zip(q.iter()) {
+        *o += q;
+    }
+    Ok(out)
+}
+
+/// Polynomial long division in z^-1, b = k a + r with r of lower order than a
+fn divide(b: &[f32], a: &[f32]) -> Result<(Coefficients, Coefficients), Error> {
+    let mut remainder = Coefficients::from_slice(b).map_err(|_| Error::Capacity)?;
+    let mut quotient = Coefficients::new();
+
+    if b.len() >= a.len() {
+        quotient.resize(b.len() - a.len() + 1, 0.0).ok();
+
+        // highest powers of z^-1 first
+        let lead = a[a.len() - 1];
+        for q in (0..quotient.len()).rev() {
+            let k = remainder[q + a.len() - 1] / lead;
+            quotient[q] = k;
+            for (j, a) in a.iter().enumerate() {
+                remainder[q + j] -= k * a;
+            }
+        }
+        remainder.truncate(a.len() - 1);
+    }
+
+    Ok((quotient, remainder))
+}
+
+/// Roots of c0 z^M + c1 z^(M-1) + ... + cM via Durand-Kerner, iterated in f64
+/// as f32 struggles to settle much past second order
+fn roots(coeffs: &[f32]) -> Result<Roots, Error> {
+    let degree = coeffs.len() - 1;
+    let lead = coeffs[0] as f64;
+    let monic = |z: Complex<f64>| {
+        coeffs
+            .iter()
+            .fold(Complex::new(0.0, 0.0), |acc, c| acc * z + *c as f64 / lead)
+    };
+
+    let mut roots: Vec<Complex<f64>, MAX_COEFFS> = Vec::new();
+    let seed = Complex::new(0.4, 0.9);
+    let mut guess = Complex::new(1.0, 0.0);
+    for _ in 0..degree {
+        roots.push(guess).ok();
+        guess *= seed;
+    }
+
+    let mut delta: f64 = 0.0;
+    for _ in 0..500 {
+        delta = 0.0;
+
+        for i in 0..degree {
+            let zi = roots[i];
+            let denominator = roots
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Complex::new(1.0, 0.0), |acc, (_, zj)| acc * (zi - zj));
+            let step = monic(zi) / denominator;
+            roots[i] = zi - step;
+            delta = delta.max(step.norm_sqr());
+        }
+
+        if delta < 1e-24 {
+            break;
+        }
+    }
+
+    // repeated roots only converge linearly, close enough is fine for f32
+    if delta > 1e-12 {
+        return Err(Error::NoConvergence);
+    }
+
+    Ok(roots
+        .iter()
+        .map(|z| Complex32::new(z.re as f32, z.im as f32))
+        .collect())
+}
@@ -0,0 +1,204 @@
+use lab3::transfer_function::{Error, TransferFunction};
+use num_complex::Complex32;
+
+const N: usize = 32;
+
+/// y[n] = 2.2x[n] - 1.1x[n-1] + 0.7y[n-1], copied from lab2
+/// 2_11_iterator_based_systems
+#[derive(Clone, Debug)]
+struct DigitalSystem5<I>
+where
+    I: Iterator<Item = f32>,
+{
+    last_in: Option<f32>,
+    last_out: Option<f32>,
+    iter: I,
+}
+
+impl<I> DigitalSystem5<I>
+where
+    I: Iterator<Item = f32>,
+{
+    fn new(iter: I) -> Self {
+        Self {
+            last_in: None,
+            last_out: None,
+            iter,
+        }
+    }
+}
+
+impl<I> Iterator for DigitalSystem5<I>
+where
+    I: Iterator<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(val) = self.iter.next() {
+            let out = if let (Some(last_in), Some(last_out)) = (self.last_in, self.last_out) {
+                2.2 * val + -1.1 * last_in + 0.7 * last_out
+            } else {
+                2.2 * val
+            };
+
+            self.last_in = Some(val);
+            self.last_out = Some(out);
+
+            Some(out)
+        } else {
+            None
+        }
+    }
+}
+
+fn digital_system5() -> TransferFunction {
+    TransferFunction::new(&[2.2, -1.1], &[1.0, -0.7]).unwrap()
+}
+
+fn unit_pulse() -> impl Iterator<Item = f32> + Clone {
+    (0..N).map(|n| if n == 0 { 1.0 } else { 0.0 })
+}
+
+fn assert_close(name: &str, left: impl Iterator<Item = f32>, right: impl Iterator<Item = f32>) {
+    for (n, (l, r)) in left.zip(right).enumerate() {
+        assert!(
+            (l - r).abs() <= 1e-4 * (1.0 + r.abs()),
+            "{} differs at n={}: {} vs {}",
+            name,
+            n,
+            l,
+            r
+        );
+    }
+}
+
+fn mag(c: Complex32) -> f32 {
+    (c.re * c.re + c.im * c.im).sqrt()
+}
+
+#[test]
+fn impulse_response_matches_digital_system5() {
+    let tf = digital_system5();
+    let expected = DigitalSystem5::new(unit_pulse());
+
+    assert_close("impulse", tf.impulse_response().take(N), expected);
+}
+
+#[test]
+fn partial_fractions_match_digital_system5() {
+    let tf = digital_system5();
+    let pf = tf.partial_fractions().unwrap();
+
+    // (2.2 - 1.1z^-1)/(1 - 0.7z^-1) = 0.6286/(1 - 0.7z^-1) + 1.5714
+    assert_eq!(pf.poles.len(), 1);
+    assert!((pf.poles[0].re - 0.7).abs() < 1e-5);
+    assert!((pf.direct[0] - 1.1 / 0.7).abs() < 1e-5);
+    assert!((pf.residues[0].re - (2.2 - 1.1 / 0.7)).abs() < 1e-5);
+
+    let expected = DigitalSystem5::new(unit_pulse());
+    assert_close("partial fractions", pf.impulse_response().take(N), expected);
+}
+
+#[test]
+fn filter_matches_digital_system5_on_sinusoid() {
+    let w0 = core::f32::consts::PI / 5.0;
+    let sinusoidal = (0..N).map(|n| (w0 * n as f32).sin());
+    let tf = digital_system5();
+
+    assert_close(
+        "sinusoid",
+        tf.filter(sinusoidal.clone()),
+        DigitalSystem5::new(sinusoidal),
+    );
+}
+
+#[test]
+fn dc_and_nyquist_gain() {
+    let tf = digital_system5();
+
+    assert!((tf.dc_gain() - 1.1 / 0.3).abs() < 1e-5);
+    assert!((tf.nyquist_gain() - 3.3 / 1.7).abs() < 1e-5);
+
+    // unit circle evaluation at w=0 and w=pi agrees
+    assert!((tf.frequency_response(0.0).re - tf.dc_gain()).abs() < 1e-4);
+    let nyquist = tf.frequency_response(core::f32::consts::PI);
+    assert!((nyquist.re - tf.nyquist_gain()).abs() < 1e-4);
+    assert!(nyquist.im.abs() < 1e-4);
+
+    // step response settles on the dc gain
+    let step = tf.filter((0..200).map(|_| 1.0)).last().unwrap();
+    assert!((step - tf.dc_gain()).abs() < 1e-3);
+}
+
+#[test]
+fn complex_poles_partial_fractions() {
+    // resonator with poles at 0.9e^(+-j pi/4)
+    let r: f32 = 0.9;
+    let w = core::f32::consts::FRAC_PI_4;
+    let tf = TransferFunction::new(&[1.0, 0.5, 0.25], &[1.0, -2.0 * r * w.cos(), r * r]).unwrap();
+
+    assert!(tf.is_stable().unwrap());
+    for p in tf.poles().unwrap() {
+        assert!((mag(p) - r).abs() < 1e-5);
+        assert!((p.im.abs().atan2(p.re) - w).abs() < 1e-5);
+    }
+
+    let pf = tf.partial_fractions().unwrap();
+    assert_eq!(pf.direct.len(), 1);
+    assert_close(
+        "resonator",
+        pf.impulse_response().take(N),
+        tf.impulse_response().take(N),
+    );
+}
+
+#[test]
+fn repeated_poles_are_rejected() {
+    // (1 - 0.5z^-1)^2
+    let tf = TransferFunction::new(&[1.0], &[1.0, -1.0, 0.25]).unwrap();
+
+    assert_eq!(tf.partial_fractions(), Err(Error::RepeatedPole));
+}
+
+#[test]
+fn series_parallel_feedback_responses() {
+    let h1 = digital_system5();
+    let h2 = TransferFunction::new(&[0.5, 0.5], &[1.0]).unwrap();
+
+    let series = h1.series(&h2).unwrap();
+    let parallel = h1.parallel(&h2).unwrap();
+    let feedback = h1.feedback(&h2).unwrap();
+
+    for k in 0..16 {
+        let w = k as f32 * core::f32::consts::PI / 16.0;
+        let (a, b) = (h1.frequency_response(w), h2.frequency_response(w));
+
+        assert!(mag(series.frequency_response(w) - a * b) < 1e-3);
+        assert!(mag(parallel.frequency_response(w) - (a + b)) < 1e-3);
+        let closed = a / (Complex32::new(1.0, 0.0) + a * b);
+        assert!(mag(feedback.frequency_response(w) - closed) < 1e-3);
+    }
+
+    // series in the time domain is the cascade of the two systems
+    assert_close(
+        "series",
+        series.impulse_response().take(N),
+        h2.filter(h1.impulse_response().take(N)),
+    );
+}
+
+#[test]
+fn capacity_and_zero_denominator() {
+    assert_eq!(
+        TransferFunction::new(&[1.0], &[0.0, 1.0]),
+        Err(Error::ZeroDenominator)
+    );
+    assert_eq!(
+        TransferFunction::new(&[1.0; 17], &[1.0]),
+        Err(Error::Capacity)
+    );
+
+    let big = TransferFunction::new(&[1.0; 10], &[1.0]).unwrap();
+    assert_eq!(big.series(&big), Err(Error::Capacity));
+}
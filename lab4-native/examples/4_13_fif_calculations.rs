@@ -8,13 +8,13 @@
 //! device. Except for when printing you must be vigilent to not become reliant
 //! on any std tools that can't otherwise port over to no_std without alloc.
 //!
-//! `cargo run --example 4_10_stft_calculations`
+//! `cargo run --example 4_13_fif_calculations`
 
-use core::f32::consts::PI;
+use dsp::fft::{cfft, icfft_real, Normalization};
+use dsp::metrics;
 use lab4::{display, Shape};
 use microfft::Complex32;
 
-use microfft::complex::cfft_512 as cfft;
 const N: usize = 512;
 
 const W1: f32 = core::f32::consts::PI / 128.0;
@@ -39,49 +39,37 @@ fn main() {
         .take(N)
         .collect();
 
-    // SAFETY microfft now only accepts arrays instead of slices to avoid runtime errors
-    // Thats not great for us. However we can cheat since our slice into an array because
-    // "The layout of a slice [T] of length N is the same as that of a [T; N] array."
-    // https://rust-lang.github.io/unsafe-code-guidelines/layout/arrays-and-slices.html
-    // this goes away when something like heapless vec is in standard library
-    // https://github.com/rust-lang/rfcs/pull/2990
-    unsafe {
-        let ptr = &mut *(df_complex.as_mut_ptr() as *mut [Complex32; N]);
+    // Finding the FFT of the filter
+    let _ = cfft(&mut df_complex).unwrap();
 
-        // Finding the FFT of the filter
-        let _ = cfft(ptr);
-    }
-
-    // SAFETY same as above
-    unsafe {
-        let ptr = &mut *(s_complex.as_mut_ptr() as *mut [Complex32; N]);
-
-        // Finding the FFT of the input signal
-        let _ = cfft(ptr);
-    }
+    // Finding the FFT of the input signal
+    let _ = cfft(&mut s_complex).unwrap();
 
     // Filtering in the frequency domain
-    let y_complex = s_complex
+    let mut y_complex: heapless::Vec<Complex32, N> = s_complex
         .iter()
         .zip(df_complex.iter())
         //multiply complex
-        .map(|(s, df)| Complex32 {
-            re: s.re * df.re - s.im * df.im,
-            im: s.re * df.im + s.im * df.re,
-        });
+        .map(|(s, df)| s * df)
+        .collect();
 
     // Finding the complex result in time domain
-    // supposed to be inverse transform but microfft doesnt have it
-    // Could patch it in. inverse DFT is the same as the DFT, but with the
-    // opposite sign in the exponent and a 1/N factor, any FFT algorithm can
-    // easily be adapted for it.
-    // just dtfse approx instead for now
-    let y_freq: heapless::Vec<f32, N> = dtfse(y_complex.clone(), 15).collect();
+    // inverse transform, icfft scales by the 1/N the forward one left out and
+    // the input was real so the imaginary parts are only rounding error
+    let y_freq: heapless::Vec<f32, N> = icfft_real(&mut y_complex, Normalization::OneOverN)
+        .unwrap()
+        .collect();
     display("freq", Shape::Line, y_freq.iter().cloned());
 
     //y_time via convolution_sum developed in 2.14 to compare
     let y_time: heapless::Vec<f32, N> = convolution_sum(s).collect();
     display("time", Shape::Line, y_time.iter().cloned());
+
+    // the fft filters circularly, so the first H.len() - 1 samples have the
+    // end of the signal wrapped round into them and only the rest compare
+    let settled = H.len() - 1;
+    let comparison = metrics::compare(&y_time[settled..], &y_freq[settled..]).unwrap();
+    println!("freq against time: {}", comparison);
 }
 
 static H: &[f32] = &[
@@ -95,25 +83,6 @@ static H: &[f32] = &[
     0.001448,
 ];
 
-fn dtfse<I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N as f32;
-    (0..N).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
-}
-
 pub fn convolution_sum<I>(x: I) -> impl Iterator<Item = f32> + Clone
 where
     I: Iterator<Item = f32>
@@ -10,10 +10,11 @@
 //! `cargo run --example 4_14_linear_phase_calculations`
 
 use core::f32::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::fft::cfft;
 use lab4::{display, Shape};
 use microfft::Complex32;
 
-use microfft::complex::cfft_64 as cfft;
 const N: usize = 64;
 
 fn main() {
@@ -24,25 +25,15 @@ fn main() {
         .map(|h| Complex32 { re: h, im: 0.0 })
         .collect();
 
-    // SAFETY microfft now only accepts arrays instead of slices to avoid runtime errors
-    // Thats not great for us. However we can cheat since our slice into an array because
-    // "The layout of a slice [T] of length N is the same as that of a [T; N] array."
-    // https://rust-lang.github.io/unsafe-code-guidelines/layout/arrays-and-slices.html
-    // this goes away when something like heapless vec is in standard library
-    // https://github.com/rust-lang/rfcs/pull/2990
-    unsafe {
-        let ptr = &mut *(dtfsecoef.as_mut_ptr() as *mut [Complex32; N]);
-
-        // Coefficient calculation with CFFT function
-        // well use microfft uses an in place Radix-2 FFT
-        // it re-returns our array in case we were going to chain calls, throw it away
-        let _ = cfft(ptr);
-    }
+    // Coefficient calculation with CFFT function
+    // well use microfft uses an in place Radix-2 FFT
+    // it re-returns our array in case we were going to chain calls, throw it away
+    let _ = cfft(&mut dtfsecoef).unwrap();
 
     // Magnitude calculation
     let mag: heapless::Vec<f32, N> = dtfsecoef
         .iter()
-        .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
+        .map(|complex| complex.magnitude())
         .collect();
     display("mag", Shape::Line, mag.iter().cloned());
 
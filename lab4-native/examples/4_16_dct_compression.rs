@@ -0,0 +1,83 @@
+//! This project is used for explaining compression by energy compaction.
+//! Here we have a simulated block of accelerometer samples, gravity with a
+//! slow tilt, a sway and some sensor noise. Its orthonormal DCT puts nearly
+//! all of the energy in a handful of coefficients, so keeping only the K
+//! largest and sending those over RTT instead of the samples still gets the
+//! signal back close. The reconstruction SNR is printed for each K along
+//! with how many times smaller that is.
+//!
+//! Runs entirely locally without hardware. Rounding might be different than on
+//! device. Except for when printing you must be vigilent to not become reliant
+//! on any std tools that can't otherwise port over to no_std without alloc.
+//!
+//! `cargo run --example 4_16_dct_compression`
+
+use core::f32::consts::PI;
+use dsp::dct::{dct2, dct3};
+use dsp::fft::Normalization;
+use dsp::metrics;
+use lab4::{display, Shape};
+
+const N: usize = 256;
+
+// sampling period of 10 milliseconds
+const SAMPLE_RATE: f32 = 100.0;
+
+const KEEP: [usize; 6] = [2, 4, 8, 16, 32, 64];
+
+fn main() {
+    // deterministic noise in -1..1
+    let mut state = 1u32;
+    let mut noise = core::iter::repeat_with(move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+    });
+
+    // in g
+    let signal: heapless::Vec<f32, N> = (0..N)
+        .map(|n| {
+            let t = n as f32 / SAMPLE_RATE;
+            let tilt = 0.02 * t;
+            let sway = 0.05 * (2.0 * PI * 0.7 * t).sin();
+            1.0 + tilt + sway + 0.002 * noise.next().unwrap()
+        })
+        .collect();
+    display("signal", Shape::Line, signal.iter().cloned());
+
+    let mut coefficients = signal.clone();
+    dct2(&mut coefficients, Normalization::Unitary).unwrap();
+    display("coefficients", Shape::Points, coefficients.iter().cloned());
+
+    // biggest coefficients first
+    let mut order: heapless::Vec<usize, N> = (0..N).collect();
+    order.sort_unstable_by(|&a, &b| {
+        coefficients[b]
+            .abs()
+            .partial_cmp(&coefficients[a].abs())
+            .unwrap()
+    });
+
+    for &k in KEEP.iter() {
+        // everything but the k largest zeroed, as if only those were sent
+        let mut kept: heapless::Vec<f32, N> = (0..N).map(|_| 0.0).collect();
+        for &i in order.iter().take(k) {
+            kept[i] = coefficients[i];
+        }
+
+        dct3(&mut kept, Normalization::Unitary).unwrap();
+
+        let snr = metrics::snr(&signal, &kept).unwrap();
+
+        // each kept coefficient needs its index sent along with it
+        println!(
+            "keep {:>2}: snr {:.1}dB, {:.1}x smaller",
+            k,
+            snr,
+            N as f32 / (2 * k) as f32
+        );
+
+        if k == 8 {
+            display("reconstructed 8", Shape::Line, kept.iter().cloned());
+        }
+    }
+}
@@ -11,8 +11,10 @@
 //!
 //! `cargo run --example 4_1_dft_calculations`
 
-use core::f32::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::dft::dft;
 use lab4::{display, Shape};
+use microfft::Complex32;
 
 const N: usize = 256;
 
@@ -26,10 +28,10 @@ fn main() {
     let s2 = (0..N).map(|val| (W2 * val as f32).sin());
     let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
 
-    // map it to real, leave im blank well fill in with dft
-    let dtfsecoef = s.map(|f| Complex32 { re: f, im: 0.0 });
+    // real samples go straight in, no need to fill in a blank im
+    let dtfsecoef: heapless::Vec<f32, N> = s.collect();
 
-    let dft: heapless::Vec<Complex32, N> = dft(dtfsecoef).collect();
+    let dft: heapless::Vec<Complex32, N> = dft(&dtfsecoef).unwrap().collect();
 
     let re: heapless::Vec<f32, N> = dft.iter().map(|complex| complex.re).collect();
     display("re", Shape::Line, re.iter().cloned());
@@ -38,42 +40,6 @@ fn main() {
     display("im", Shape::Line, im.iter().cloned());
 
     //Magnitude calculation
-    let mag: heapless::Vec<f32, N> = dft
-        .iter()
-        .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-        .collect();
+    let mag: heapless::Vec<f32, N> = dft.iter().map(|complex| complex.magnitude()).collect();
     display("mag", Shape::Line, mag.iter().cloned());
 }
-
-fn dft<I: Iterator<Item = Complex32> + Clone>(input: I) -> impl Iterator<Item = Complex32> {
-    let size = N as f32;
-    (0..N).map(move |k| {
-        input
-            .clone()
-            .enumerate()
-            .fold((0f32, 0f32), |(mut sum_re, mut sum_im), (n, complex)| {
-                let n = n as f32;
-                sum_re += complex.re * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.im * (2.0 * PI * k as f32 * n / size).sin();
-                sum_im += -complex.im * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.re * (2.0 * PI * k as f32 * n / size).sin();
-
-                (sum_re, sum_im)
-            })
-            .into()
-    })
-}
-
-struct Complex32 {
-    re: f32,
-    im: f32,
-}
-
-impl From<(f32, f32)> for Complex32 {
-    fn from(incoming: (f32, f32)) -> Self {
-        Complex32 {
-            re: incoming.0,
-            im: incoming.1,
-        }
-    }
-}
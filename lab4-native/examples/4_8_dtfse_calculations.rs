@@ -5,73 +5,73 @@
 //! real form because original signal has only real parts in this example. The
 //! result is kept in the y_real array.
 //!
+//! Each harmonic is a coefficient together with its conjugate partner, so
+//! N/2 of them give the samples back exactly. Between the samples the series
+//! still rings either side of the jumps, the Gibbs overshoot printed at the
+//! end alongside the error for each number of harmonics.
+//!
 //! Runs entirely locally without hardware. Rounding might be different than on
 //! device. Except for when printing you must be vigilent to not become reliant
 //! on any std tools that can't otherwise port over to no_std without alloc.
 //!
 //! `cargo run --example 4_8_dtfse_calculations`
 
-use core::f32::consts::PI;
+use dsp::dtfse::Series;
+use dsp::fft::cfft;
 use lab4::{display, Shape};
 use microfft::Complex32;
 
-use microfft::complex::cfft_16 as cfft;
 const N: usize = 16;
 
+// points to evaluate the series at over one period, enough to see it ring
+// between the samples
+const FINE: usize = 8 * N;
+
 fn main() {
     //square signal
-    let square = (0..N).map(|idx| if idx < N / 2 { 1.0 } else { 0.0 });
-    display("square", Shape::Line, square.clone());
+    let square: heapless::Vec<f32, N> = (0..N)
+        .map(|idx| if idx < N / 2 { 1.0 } else { 0.0 })
+        .collect();
+    display("square", Shape::Line, square.iter().cloned());
 
     //map it to real, leave im blank well fill in with cfft
-    let mut dtfsecoef: heapless::Vec<Complex32, N> =
-        square.map(|f| Complex32 { re: f, im: 0.0 }).collect();
+    let mut dtfsecoef: heapless::Vec<Complex32, N> = square
+        .iter()
+        .map(|&f| Complex32 { re: f, im: 0.0 })
+        .collect();
 
-    // SAFETY microfft now only accepts arrays instead of slices to avoid runtime errors
-    // Thats not great for us. However we can cheat since our slice into an array because
-    // "The layout of a slice [T] of length N is the same as that of a [T; N] array."
-    // https://rust-lang.github.io/unsafe-code-guidelines/layout/arrays-and-slices.html
-    // this goes away when something like heapless vec is in standard library
-    // https://github.com/rust-lang/rfcs/pull/2990
-    unsafe {
-        let ptr = &mut *(dtfsecoef.as_mut_ptr() as *mut [Complex32; N]);
-
-        // Coefficient calculation with CFFT function
-        // well use microfft uses an in place Radix-2 FFT
-        // it re-returns our array in case we were going to chain calls, throw it away
-        let _ = cfft(ptr);
-    }
+    // Coefficient calculation with CFFT function
+    // well use microfft uses an in place Radix-2 FFT
+    // it re-returns our array in case we were going to chain calls, throw it away
+    let _ = cfft(&mut dtfsecoef).unwrap();
 
     println!("dtfsecoef: {:?}", &dtfsecoef);
 
+    let series = Series::new(&dtfsecoef);
+
     //dtfse to reclaim our original signal, note this is a bad approximation for our square wave
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 1).collect();
+    let y_real: heapless::Vec<f32, N> = series.synthesize(1, N).collect();
     display("y_real 1", Shape::Line, y_real.iter().cloned());
 
     //a bit better
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 5).collect();
-    display("y_real 5", Shape::Line, y_real.iter().cloned());
+    let y_real: heapless::Vec<f32, N> = series.synthesize(3, N).collect();
+    display("y_real 3", Shape::Line, y_real.iter().cloned());
 
-    //good
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 15).collect();
-    display("y_real 15", Shape::Line, y_real.iter().cloned());
-}
+    //every harmonic, the samples come back exactly
+    let y_real: heapless::Vec<f32, N> = series.synthesize(series.max_harmonics(), N).collect();
+    display("y_real 8", Shape::Line, y_real.iter().cloned());
 
-fn dtfse<I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N as f32;
-    (0..N).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
+    //but in between them it rings either side of the jumps
+    let y_fine: heapless::Vec<f32, FINE> =
+        series.synthesize(series.max_harmonics(), FINE).collect();
+    display("y_fine 8", Shape::Line, y_fine.iter().cloned());
+
+    for report in series.convergence(&square, FINE) {
+        println!(
+            "harmonics: {} mse: {:.6} overshoot: {:.2}%",
+            report.harmonics,
+            report.mse,
+            100.0 * report.overshoot
+        );
+    }
 }
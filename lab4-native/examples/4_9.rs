@@ -11,16 +11,19 @@
 //!
 //! `cargo run --example 4_9`
 
-use core::f32::consts::PI;
+use dsp::dtfse::Series;
+use dsp::fft::cfft;
 use lab4::{display, Shape};
 use microfft::Complex32;
 
-use microfft::complex::cfft_16 as cfft;
 const N: usize = 16;
 
 const TRIANGLE_AMPLITUDE: f32 = 1.5;
 const TRIANGLE_PERIOD: usize = 16;
 
+// points to evaluate the series at over one period
+const FINE: usize = 8 * N;
+
 fn main() {
     // Collecting to turn the Cycle into a clean iterator for our naive display fn
     let triangle: heapless::Vec<f32, N> = (0..TRIANGLE_PERIOD)
@@ -46,54 +49,33 @@ fn main() {
         .map(|f| Complex32 { re: f, im: 0.0 })
         .collect();
 
-    // SAFETY microfft now only accepts arrays instead of slices to avoid runtime errors
-    // Thats not great for us. However we can cheat since our slice into an array because
-    // "The layout of a slice [T] of length N is the same as that of a [T; N] array."
-    // https://rust-lang.github.io/unsafe-code-guidelines/layout/arrays-and-slices.html
-    // this goes away when something like heapless vec is in standard library
-    // https://github.com/rust-lang/rfcs/pull/2990
-    unsafe {
-        let ptr = &mut *(dtfsecoef.as_mut_ptr() as *mut [Complex32; N]);
-
-        // Coefficient calculation with CFFT function
-        // well use microfft uses an in place Radix-2 FFT
-        // it re-returns our array in case we were going to chain calls, throw it away
-        let _ = cfft(ptr);
-    }
+    // Coefficient calculation with CFFT function
+    // well use microfft uses an in place Radix-2 FFT
+    // it re-returns our array in case we were going to chain calls, throw it away
+    let _ = cfft(&mut dtfsecoef).unwrap();
     println!("dtfsecoef: {:?}", &dtfsecoef);
 
-    //dtfse to reclaim our original signal, note this is a bad approximation for our square wave
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 2).collect();
-    display("y_real 2", Shape::Line, y_real.iter().cloned());
+    let series = Series::new(&dtfsecoef);
+
+    //dtfse to reclaim our original signal, no jumps so even a few harmonics get close
+    let y_real: heapless::Vec<f32, N> = series.synthesize(1, N).collect();
+    display("y_real 1", Shape::Line, y_real.iter().cloned());
 
     //a bit better
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 5).collect();
-    display("y_real 5", Shape::Line, y_real.iter().cloned());
+    let y_real: heapless::Vec<f32, N> = series.synthesize(3, N).collect();
+    display("y_real 3", Shape::Line, y_real.iter().cloned());
 
-    //good
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 8).collect();
+    //every harmonic, the samples come back exactly
+    let y_real: heapless::Vec<f32, N> = series.synthesize(series.max_harmonics(), N).collect();
     display("y_real 8", Shape::Line, y_real.iter().cloned());
 
-    //good
-    let y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 15).collect();
-    display("y_real 15", Shape::Line, y_real.iter().cloned());
-}
-
-fn dtfse<I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N as f32;
-    (0..N).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                a * ((2.0 * PI * k as f32 * n as f32 / size) + p).cos() / size
-            })
-            .sum::<f32>()
-    })
+    //unlike the square wave in 4_8 theres next to no overshoot to see between them
+    for report in series.convergence(&triangle, FINE) {
+        println!(
+            "harmonics: {} mse: {:.6} overshoot: {:.2}%",
+            report.harmonics,
+            report.mse,
+            100.0 * report.overshoot
+        );
+    }
 }
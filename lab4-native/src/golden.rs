@@ -0,0 +1,213 @@
+//! Expected outputs kept as text files to check the examples against.
+//!
+//! A file is rows of numbers separated by whitespace or commas, with lines
+//! starting with # as comments saying where the numbers came from. Rows can
+//! be whatever suits the data, a frame of an STFT or just 16 values to keep
+//! lines short, theyre only used to say where a mismatch is.
+//!
+//! Nothing computed in f32 matches a MATLAB answer key exactly, so every
+//! comparison takes a Tolerance, and a mismatch reports the first value out
+//! of it along with how many others are and the worst one.
+//!
+//! ```ignore
+//! let golden = Golden::parse(include_str!("golden/4_10_stft_magnitude.txt")).unwrap();
+//! golden.check("4_10 stft", &magnitudes, Tolerance::absolute(0.2));
+//! ```
+
+use std::fmt;
+use std::num::ParseFloatError;
+
+/// How far a value can be from its expected value, |found - expected| <=
+/// absolute + relative * |expected|
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f32,
+    pub relative: f32,
+}
+
+impl Tolerance {
+    pub fn absolute(absolute: f32) -> Self {
+        Self {
+            absolute,
+            relative: 0.0,
+        }
+    }
+
+    pub fn relative(relative: f32) -> Self {
+        Self {
+            absolute: 0.0,
+            relative,
+        }
+    }
+
+    pub fn allows(&self, expected: f32, found: f32) -> bool {
+        // written so NaN is never allowed
+        (found - expected).abs() <= self.absolute + self.relative * expected.abs()
+    }
+}
+
+/// A line that isnt a comment had something that isnt a number on it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// counting from 1 like an editor
+    pub line: usize,
+    pub error: ParseFloatError,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why found didnt match
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    /// different number of values, nothing else is compared
+    Length { expected: usize, found: usize },
+    Value {
+        /// first value out of tolerance, counting across rows
+        index: usize,
+        /// which row and where along it index is
+        row: usize,
+        column: usize,
+        expected: f32,
+        found: f32,
+        /// how many values are out of tolerance in all
+        count: usize,
+        /// index and size of the biggest difference
+        worst: usize,
+        difference: f32,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Length { expected, found } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            Mismatch::Value {
+                index,
+                row,
+                column,
+                expected,
+                found,
+                count,
+                worst,
+                difference,
+            } => write!(
+                f,
+                "first difference at index {} (row {} column {}): expected {} found {}, \
+                 {} out of tolerance, worst {} at index {}",
+                index, row, column, expected, found, count, difference, worst
+            ),
+        }
+    }
+}
+
+/// Expected values read from a golden file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Golden {
+    rows: Vec<Vec<f32>>,
+}
+
+impl Golden {
+    /// Rows of numbers, skipping blank lines and # comments
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut rows = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let row = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|error| ParseError { line: i + 1, error })?;
+            rows.push(row);
+        }
+        Ok(Self { rows })
+    }
+
+    pub fn rows(&self) -> &[Vec<f32>] {
+        &self.rows
+    }
+
+    /// Every value a row at a time
+    pub fn values(&self) -> impl Iterator<Item = f32> + Clone + '_ {
+        self.rows.iter().flatten().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// found against every value a row at a time
+    pub fn compare(&self, found: &[f32], tolerance: Tolerance) -> Result<(), Mismatch> {
+        if found.len() != self.len() {
+            return Err(Mismatch::Length {
+                expected: self.len(),
+                found: found.len(),
+            });
+        }
+
+        let mut first = None;
+        let mut count = 0;
+        let mut worst = (0, 0.0f32);
+        for (index, (expected, &found)) in self.values().zip(found.iter()).enumerate() {
+            if tolerance.allows(expected, found) {
+                continue;
+            }
+            count += 1;
+            first.get_or_insert((index, expected, found));
+            // NaN is as bad as it gets, the first one stays the worst
+            let difference = (found - expected).abs();
+            if !worst.1.is_nan() && (difference.is_nan() || difference > worst.1) {
+                worst = (index, difference);
+            }
+        }
+
+        match first {
+            None => Ok(()),
+            Some((index, expected, found)) => {
+                let (row, column) = self.position(index);
+                Err(Mismatch::Value {
+                    index,
+                    row,
+                    column,
+                    expected,
+                    found,
+                    count,
+                    worst: worst.0,
+                    difference: worst.1,
+                })
+            }
+        }
+    }
+
+    /// compare, panicking with the mismatch and name if theres one
+    pub fn check(&self, name: &str, found: &[f32], tolerance: Tolerance) {
+        if let Err(mismatch) = self.compare(found, tolerance) {
+            panic!("{}: {}", name, mismatch);
+        }
+    }
+
+    fn position(&self, mut index: usize) -> (usize, usize) {
+        for (row, values) in self.rows.iter().enumerate() {
+            if index < values.len() {
+                return (row, index);
+            }
+            index -= values.len();
+        }
+        (self.rows.len(), index)
+    }
+}
@@ -1,6 +1,10 @@
 use itertools::Itertools;
 use textplots::{Chart, Plot};
 
+pub mod golden;
+pub mod png;
+pub mod spectrogram;
+
 #[non_exhaustive]
 pub enum Shape {
     Line,
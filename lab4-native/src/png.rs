@@ -0,0 +1,245 @@
+//! Matrices and spectrograms rendered to PNG.
+//!
+//! plot.show() needs a browser, this just needs a file system so it works
+//! in CI. Each value becomes a cell of pixels coloured by a colormap, rows
+//! top to bottom as given. There's no font to label anything with, so ticks
+//! are plain marks in a margin along the left and bottom edges.
+//!
+//! ```ignore
+//! spectrogram.save_png("chirp.png", &Options::default()).unwrap();
+//! ```
+
+use crate::spectrogram::Spectrogram;
+use image::{ImageResult, Rgb, RgbImage};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colormap {
+    /// matplotlib's default, dark blue through green to yellow, reads the
+    /// same in grayscale and to colour blind eyes
+    Viridis,
+    /// black through purple and orange to pale yellow
+    Magma,
+    /// black to white
+    Gray,
+}
+
+/// Which values map to the two ends of the colormap, anything outside is
+/// clipped to the nearest end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Range {
+    /// smallest to largest value in the data
+    Full,
+    Fixed {
+        min: f32,
+        max: f32,
+    },
+    /// for values already in dB, the largest value down to dynamic_range dB
+    /// below it. Keeps the -200 dB floor of silence from flattening
+    /// everything else to one colour.
+    Decibels {
+        dynamic_range: f32,
+    },
+}
+
+/// Tick marks as cell indices along each edge
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ticks {
+    /// columns to mark along the bottom
+    pub x: Vec<usize>,
+    /// rows to mark down the left
+    pub y: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub colormap: Colormap,
+    pub range: Range,
+    /// pixels per cell side
+    pub cell: u32,
+    /// adds a TICK_MARGIN pixel margin on the left and bottom when set
+    pub ticks: Option<Ticks>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::Viridis,
+            range: Range::Full,
+            cell: 4,
+            ticks: None,
+        }
+    }
+}
+
+/// Width of the tick margin in pixels
+pub const TICK_MARGIN: u32 = 6;
+
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+const TICK: Rgb<u8> = Rgb([0, 0, 0]);
+
+/// Render rows of values, rows[0] along the top. Rows shorter than the
+/// longest are left as background.
+pub fn render(rows: &[Vec<f32>], options: &Options) -> RgbImage {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+    let height = rows.len() as u32;
+    let margin = if options.ticks.is_some() {
+        TICK_MARGIN
+    } else {
+        0
+    };
+    let cell = options.cell;
+
+    let mut image = RgbImage::from_pixel(width * cell + margin, height * cell + margin, BACKGROUND);
+
+    let (min, max) = limits(rows, options.range);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &value) in row.iter().enumerate() {
+            let t = if max > min && value.is_finite() {
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let colour = options.colormap.colour(t);
+
+            for dy in 0..cell {
+                for dx in 0..cell {
+                    image.put_pixel(margin + x as u32 * cell + dx, y as u32 * cell + dy, colour);
+                }
+            }
+        }
+    }
+
+    if let Some(ticks) = &options.ticks {
+        // marks run the length of the margin, centred on their cell
+        for &x in ticks.x.iter().filter(|&&x| (x as u32) < width) {
+            let px = margin + x as u32 * cell + cell / 2;
+            for py in height * cell..height * cell + margin {
+                image.put_pixel(px, py, TICK);
+            }
+        }
+        for &y in ticks.y.iter().filter(|&&y| (y as u32) < height) {
+            let py = y as u32 * cell + cell / 2;
+            for px in 0..margin {
+                image.put_pixel(px, py, TICK);
+            }
+        }
+    }
+
+    image
+}
+
+/// render straight to a PNG file
+pub fn save<P: AsRef<Path>>(path: P, rows: &[Vec<f32>], options: &Options) -> ImageResult<()> {
+    render(rows, options).save(path)
+}
+
+fn limits(rows: &[Vec<f32>], range: Range) -> (f32, f32) {
+    let finite = rows.iter().flatten().copied().filter(|x| x.is_finite());
+    let min = finite.clone().fold(f32::INFINITY, f32::min);
+    let max = finite.fold(f32::NEG_INFINITY, f32::max);
+
+    match range {
+        Range::Full => (min, max),
+        Range::Fixed { min, max } => (min, max),
+        Range::Decibels { dynamic_range } => (min.max(max - dynamic_range), max),
+    }
+}
+
+impl Colormap {
+    /// Colour for t from 0 to 1
+    pub fn colour(self, t: f32) -> Rgb<u8> {
+        let stops = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Gray => {
+                let v = (t * 255.0).round() as u8;
+                return Rgb([v, v, v]);
+            }
+        };
+
+        // linear between the stops either side
+        let position = t * (stops.len() - 1) as f32;
+        let i = (position as usize).min(stops.len() - 2);
+        let frac = position - i as f32;
+        let mut rgb = [0; 3];
+        for (c, channel) in rgb.iter_mut().enumerate() {
+            let (a, b) = (stops[i][c] as f32, stops[i + 1][c] as f32);
+            *channel = (a + (b - a) * frac).round() as u8;
+        }
+        Rgb(rgb)
+    }
+}
+
+// matplotlib's colormaps sampled every tenth of the way, interpolating
+// between them stays within a few levels of the full 256 entry tables
+const VIRIDIS: [[u8; 3]; 11] = [
+    [68, 1, 84],
+    [72, 36, 117],
+    [65, 68, 135],
+    [53, 95, 141],
+    [42, 120, 142],
+    [33, 145, 140],
+    [34, 168, 132],
+    [68, 191, 112],
+    [122, 209, 81],
+    [189, 223, 38],
+    [253, 231, 37],
+];
+
+const MAGMA: [[u8; 3]; 11] = [
+    [0, 0, 4],
+    [20, 14, 54],
+    [59, 15, 112],
+    [100, 26, 128],
+    [140, 41, 129],
+    [183, 55, 121],
+    [222, 73, 104],
+    [247, 112, 92],
+    [254, 159, 109],
+    [254, 207, 146],
+    [252, 253, 191],
+];
+
+impl Spectrogram {
+    /// Frequency up and time along, the highest frequency on the top row
+    pub fn to_image(&self, options: &Options) -> RgbImage {
+        let mut rows = self.transpose();
+        rows.reverse();
+        render(&rows, options)
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P, options: &Options) -> ImageResult<()> {
+        self.to_image(options).save(path)
+    }
+
+    /// Ticks every seconds along time and every hz up frequency, on the
+    /// frames and bins nearest those, in to_image's orientation
+    pub fn ticks(&self, seconds: f32, hz: f32) -> Ticks {
+        let rows = self.frequencies().len();
+        Ticks {
+            x: nearest(self.times(), seconds),
+            y: nearest(self.frequencies(), hz)
+                .into_iter()
+                .map(|k| rows - 1 - k)
+                .collect(),
+        }
+    }
+}
+
+/// Index of the closest axis value to each multiple of step in the axis,
+/// axis ascending with at least two values spacing it out
+fn nearest(axis: &[f32], step: f32) -> Vec<usize> {
+    let (first, last) = match (axis.first(), axis.last()) {
+        (Some(&first), Some(&last)) if axis.len() > 1 && step > 0.0 => (first, last),
+        _ => return vec![],
+    };
+    let spacing = (last - first) / (axis.len() - 1) as f32;
+
+    let start = (first / step).ceil() as i64;
+    let end = (last / step).floor() as i64;
+    (start..=end)
+        .map(|i| ((i as f32 * step - first) / spacing).round() as usize)
+        .map(|k| k.min(axis.len() - 1))
+        .collect()
+}
@@ -0,0 +1,175 @@
+//! STFT frames as a matrix with real axes, ready to plot.
+//!
+//! Values are stored a row per frame, the way Stft hands them out, with the
+//! frequency axis ascending along each row. Plotly's HeatMap wants the
+//! opposite, a row per frequency so time runs left to right, which is what
+//! transpose and heat_map give it.
+
+use dsp::complex::ComplexExt;
+use dsp::stft::Stft;
+use microfft::Complex32;
+use plotly::common::Title;
+use plotly::layout::{Axis, Layout};
+use plotly::HeatMap;
+
+/// What each cell of the spectrogram holds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scale {
+    /// |X|
+    Magnitude,
+    /// |X|^2
+    Power,
+    /// 10 log10 |X|^2, floored at FLOOR_DB so silence doesnt come out as
+    /// -inf
+    Decibels,
+}
+
+/// Lowest value Scale::Decibels goes down to
+pub const FLOOR_DB: f32 = -200.0;
+
+/// Which frequencies to keep
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sides {
+    /// DC to Nyquist, all there is to see for a real signal since the
+    /// negative frequencies are the mirror image
+    One,
+    /// -fs/2 to just under fs/2 with DC in the middle
+    Two,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrogram {
+    /// values[frame][bin]
+    values: Vec<Vec<f32>>,
+    /// seconds, middle of each frame
+    times: Vec<f32>,
+    /// Hz, ascending
+    frequencies: Vec<f32>,
+    scale: Scale,
+}
+
+impl Spectrogram {
+    /// Frames from stft of a signal sampled at sample_rate Hz
+    pub fn new<I, const N: usize>(
+        stft: &Stft<N>,
+        frames: I,
+        sample_rate: f32,
+        scale: Scale,
+        sides: Sides,
+    ) -> Self
+    where
+        I: IntoIterator<Item = [Complex32; N]>,
+    {
+        // fft bin order, wrapped round so two sided starts at Nyquist as -N/2
+        let bins: Vec<usize> = match sides {
+            Sides::One => (0..=N / 2).collect(),
+            Sides::Two => (0..N).map(|k| (k + N / 2) % N).collect(),
+        };
+        let frequencies = bins
+            .iter()
+            .map(|&k| {
+                let k = match sides {
+                    Sides::Two if k >= N / 2 => k as f32 - N as f32,
+                    _ => k as f32,
+                };
+                k * sample_rate / N as f32
+            })
+            .collect();
+
+        let values: Vec<Vec<f32>> = frames
+            .into_iter()
+            .map(|frame| bins.iter().map(|&k| scaled(frame[k], scale)).collect())
+            .collect();
+
+        let times = (0..values.len())
+            .map(|m| (m * stft.hop()) as f32 + stft.window_len() as f32 / 2.0)
+            .map(|n| n / sample_rate)
+            .collect();
+
+        Self {
+            values,
+            times,
+            frequencies,
+            scale,
+        }
+    }
+
+    /// values[frame][bin]
+    pub fn values(&self) -> &[Vec<f32>] {
+        &self.values
+    }
+
+    /// Middle of each frame in seconds
+    pub fn times(&self) -> &[f32] {
+        &self.times
+    }
+
+    /// Frequency of each bin in Hz
+    pub fn frequencies(&self) -> &[f32] {
+        &self.frequencies
+    }
+
+    pub fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    /// values[bin][frame], a row per frequency
+    pub fn transpose(&self) -> Vec<Vec<f32>> {
+        (0..self.frequencies.len())
+            .map(|k| self.values.iter().map(|frame| frame[k]).collect())
+            .collect()
+    }
+
+    /// Just the bins from low to high Hz inclusive
+    pub fn band(&self, low: f32, high: f32) -> Self {
+        let keep: Vec<usize> = self
+            .frequencies
+            .iter()
+            .enumerate()
+            .filter(|(_, &f)| f >= low && f <= high)
+            .map(|(k, _)| k)
+            .collect();
+
+        Self {
+            values: self
+                .values
+                .iter()
+                .map(|frame| keep.iter().map(|&k| frame[k]).collect())
+                .collect(),
+            times: self.times.clone(),
+            frequencies: keep.iter().map(|&k| self.frequencies[k]).collect(),
+            scale: self.scale,
+        }
+    }
+
+    /// Time along x and frequency up y
+    pub fn heat_map(&self) -> Box<HeatMap<Vec<f32>, f32, f32>> {
+        HeatMap::new(
+            self.times.clone(),
+            self.frequencies.clone(),
+            self.transpose(),
+        )
+    }
+
+    /// Axis titles to go with heat_map
+    pub fn layout(&self) -> Layout {
+        let title = match self.scale {
+            Scale::Magnitude => "magnitude",
+            Scale::Power => "power",
+            Scale::Decibels => "power (dB)",
+        };
+
+        Layout::new()
+            .title(Title::new(title))
+            .x_axis(Axis::new().title(Title::new("time (s)")))
+            .y_axis(Axis::new().title(Title::new("frequency (Hz)")))
+    }
+}
+
+fn scaled(c: Complex32, scale: Scale) -> f32 {
+    match scale {
+        Scale::Magnitude => c.magnitude(),
+        Scale::Power => c.magnitude_squared(),
+        Scale::Decibels => (10.0 * c.magnitude_squared().log10()).max(FLOOR_DB),
+    }
+}
@@ -0,0 +1,179 @@
+//! The examples computations against the expected outputs in tests/golden.
+//! Each test builds the same signal the example does, so if an example
+//! changes its signal the test has to follow.
+
+use dsp::complex::ComplexExt;
+use dsp::dft::dft;
+use dsp::dtfse::Series;
+use dsp::fft::cfft;
+use dsp::stft::Stft;
+use dsp::window::Window;
+use lab4::golden::{Golden, Mismatch, Tolerance};
+use microfft::Complex32;
+
+fn golden(text: &str) -> Golden {
+    Golden::parse(text).unwrap()
+}
+
+/// Series of one period of x, like 4_8 and 4_9 do it
+fn synthesize(x: &[f32], harmonics: usize, len: usize) -> Vec<f32> {
+    let mut coefficients: Vec<Complex32> = x.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+    cfft::<_, 16>(&mut coefficients[..]).unwrap();
+    Series::new(&coefficients).synthesize(harmonics, len).collect()
+}
+
+#[test]
+fn stft_4_10_matches_the_books_answer_key() {
+    const N: usize = 1024;
+    const WINDOW: usize = 16;
+    let chirp: Vec<f32> = (0..N)
+        .map(|n| {
+            let n = n as f32;
+            (core::f32::consts::PI * n * n / (2.0 * (N as f32 - 1.0))).cos()
+        })
+        .collect();
+
+    let stft = Stft::<WINDOW>::new(Window::Hamming, WINDOW, WINDOW / 2).unwrap();
+    let magnitudes: Vec<f32> = stft
+        .frames(&chirp)
+        .take(126)
+        .flat_map(|frame| frame.iter().map(|c| c.magnitude()).collect::<Vec<_>>())
+        .collect();
+
+    // The key is to 4 decimals and the first frame is within 0.01 of it,
+    // but further in theres up to 0.16 between them, the most at DC and
+    // Nyquist. Neither f32 nor a symmetric window nor moving the frames
+    // explains it, a chirp with a slightly different phase does, so its
+    // 0.2 against peaks of around 8.
+    golden(include_str!("golden/4_10_stft_magnitude.txt")).check(
+        "4_10 stft",
+        &magnitudes,
+        Tolerance::absolute(0.2),
+    );
+}
+
+#[test]
+fn dft_4_1_matches_f64() {
+    const N: usize = 256;
+    let s: Vec<f32> = (0..N)
+        .map(|n| {
+            let n = n as f32;
+            (core::f32::consts::PI / 128.0 * n).sin() + (core::f32::consts::PI / 4.0 * n).sin()
+        })
+        .collect();
+    let dft: Vec<Complex32> = dft::<_, _, N>(&s[..]).unwrap().collect();
+
+    // both sines are right on a bin so theres a peak of 128 and the rest
+    // should be nothing, 256 f32 sums of values up to 2 leave up to 4e-4
+    let tolerance = Tolerance::absolute(1e-3);
+    let re: Vec<f32> = dft.iter().map(|c| c.re).collect();
+    golden(include_str!("golden/4_1_dft_re.txt")).check("4_1 re", &re, tolerance);
+    let im: Vec<f32> = dft.iter().map(|c| c.im).collect();
+    golden(include_str!("golden/4_1_dft_im.txt")).check("4_1 im", &im, tolerance);
+}
+
+#[test]
+fn dtfse_4_8_matches_f64() {
+    let square: Vec<f32> = (0..16).map(|n| if n < 8 { 1.0 } else { 0.0 }).collect();
+
+    // values around 1, a few f32 roundings each
+    let tolerance = Tolerance::absolute(1e-5);
+    golden(include_str!("golden/4_8_y_real_1.txt")).check(
+        "4_8 y_real 1",
+        &synthesize(&square, 1, 16),
+        tolerance,
+    );
+    golden(include_str!("golden/4_8_y_real_3.txt")).check(
+        "4_8 y_real 3",
+        &synthesize(&square, 3, 16),
+        tolerance,
+    );
+    golden(include_str!("golden/4_8_y_fine_8.txt")).check(
+        "4_8 y_fine 8",
+        &synthesize(&square, 8, 128),
+        tolerance,
+    );
+}
+
+#[test]
+fn dtfse_4_9_matches_f64() {
+    let (amplitude, period) = (1.5, 16.0);
+    let triangle: Vec<f32> = (0..16)
+        .map(|n| {
+            let n = n as f32;
+            if n < period / 2.0 {
+                (2.0 * amplitude / (period / 2.0)) * n - amplitude
+            } else {
+                -(2.0 * amplitude / (period / 2.0)) * (n - period / 2.0) + amplitude
+            }
+        })
+        .collect();
+
+    let tolerance = Tolerance::absolute(1e-5);
+    golden(include_str!("golden/4_9_y_real_1.txt")).check(
+        "4_9 y_real 1",
+        &synthesize(&triangle, 1, 16),
+        tolerance,
+    );
+    golden(include_str!("golden/4_9_y_real_3.txt")).check(
+        "4_9 y_real 3",
+        &synthesize(&triangle, 3, 16),
+        tolerance,
+    );
+}
+
+#[test]
+fn parse_skips_comments_and_takes_commas() {
+    let golden = golden("# a comment\n1.0, 2.0 3\n\n  # indented\n-4e-1,5\n");
+    assert_eq!(golden.rows(), &[vec![1.0, 2.0, 3.0], vec![-0.4, 5.0]]);
+    assert_eq!(golden.len(), 5);
+
+    let error = Golden::parse("1 2\n# fine\n3 x\n").unwrap_err();
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn mismatch_points_at_the_first_difference() {
+    let golden = golden("0 1 2\n3 4 5\n6 7 8\n");
+    let tolerance = Tolerance::absolute(0.1);
+
+    let mut found: Vec<f32> = (0..9).map(|x| x as f32 + 0.05).collect();
+    assert_eq!(golden.compare(&found, tolerance), Ok(()));
+
+    found[4] = 4.5;
+    found[7] = 9.0;
+    assert_eq!(
+        golden.compare(&found, tolerance),
+        Err(Mismatch::Value {
+            index: 4,
+            row: 1,
+            column: 1,
+            expected: 4.0,
+            found: 4.5,
+            count: 2,
+            worst: 7,
+            difference: 2.0,
+        })
+    );
+
+    // relative scales with the expected value
+    let loose = Tolerance {
+        relative: 0.3,
+        ..tolerance
+    };
+    assert_eq!(golden.compare(&found, loose), Ok(()));
+
+    assert_eq!(
+        golden.compare(&found[..8], tolerance),
+        Err(Mismatch::Length {
+            expected: 9,
+            found: 8
+        })
+    );
+
+    found[2] = f32::NAN;
+    match golden.compare(&found, Tolerance::absolute(f32::INFINITY)) {
+        Err(Mismatch::Value { index, count, .. }) => assert_eq!((index, count), (2, 1)),
+        other => panic!("NaN allowed: {:?}", other),
+    }
+}
@@ -0,0 +1,176 @@
+use dsp::stft::Stft;
+use dsp::window::Window;
+use image::Rgb;
+use lab4::png::{render, save, Colormap, Options, Range, Ticks, TICK_MARGIN};
+use lab4::spectrogram::{Scale, Sides, Spectrogram};
+
+fn gray(cell: u32) -> Options {
+    Options {
+        colormap: Colormap::Gray,
+        cell,
+        ..Options::default()
+    }
+}
+
+#[test]
+fn dimensions() {
+    let rows = vec![vec![0.0; 5]; 3];
+
+    let image = render(&rows, &gray(1));
+    assert_eq!(image.dimensions(), (5, 3));
+
+    let image = render(&rows, &gray(4));
+    assert_eq!(image.dimensions(), (20, 12));
+
+    let options = Options {
+        ticks: Some(Ticks::default()),
+        ..gray(4)
+    };
+    let image = render(&rows, &options);
+    assert_eq!(image.dimensions(), (20 + TICK_MARGIN, 12 + TICK_MARGIN));
+}
+
+#[test]
+fn pixels() {
+    // top row goes black to white, the bottom row is out of range both ways
+    let rows = vec![vec![0.0, 0.5, 1.0], vec![-1.0, f32::NAN, 2.0]];
+    let options = Options {
+        range: Range::Fixed { min: 0.0, max: 1.0 },
+        ..gray(2)
+    };
+    let image = render(&rows, &options);
+
+    // every pixel in a cell is the same
+    for (x, y, expected) in [
+        (0, 0, 0),
+        (1, 0, 128),
+        (2, 0, 255),
+        (0, 1, 0),
+        (1, 1, 0),
+        (2, 1, 255),
+    ] {
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert_eq!(
+                *image.get_pixel(x * 2 + dx, y * 2 + dy),
+                Rgb([expected; 3]),
+                "cell {} {}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn colormaps() {
+    // the ends and the middle are sampled stops so come out exactly
+    assert_eq!(Colormap::Viridis.colour(0.0), Rgb([68, 1, 84]));
+    assert_eq!(Colormap::Viridis.colour(0.5), Rgb([33, 145, 140]));
+    assert_eq!(Colormap::Viridis.colour(1.0), Rgb([253, 231, 37]));
+    assert_eq!(Colormap::Magma.colour(0.0), Rgb([0, 0, 4]));
+    assert_eq!(Colormap::Magma.colour(1.0), Rgb([252, 253, 191]));
+    // halfway between the first two stops
+    assert_eq!(Colormap::Magma.colour(0.05), Rgb([10, 7, 29]));
+
+    // both get brighter all the way up, which is what makes them readable
+    for colormap in [Colormap::Viridis, Colormap::Magma, Colormap::Gray] {
+        let luma = |t: f32| {
+            let Rgb([r, g, b]) = colormap.colour(t);
+            0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+        };
+        for i in 0..100 {
+            let t = i as f32 / 100.0;
+            assert!(luma(t + 0.01) >= luma(t), "{:?} at {}", colormap, t);
+        }
+    }
+}
+
+#[test]
+fn decibel_clipping() {
+    // silence at the -200 floor would otherwise squash 0 and -20 together at
+    // the top of the map
+    let rows = vec![vec![0.0, -20.0, -40.0, -200.0]];
+
+    let full = render(&rows, &gray(1));
+    assert_eq!(*full.get_pixel(1, 0), Rgb([230, 230, 230]));
+
+    let options = Options {
+        range: Range::Decibels {
+            dynamic_range: 40.0,
+        },
+        ..gray(1)
+    };
+    let clipped = render(&rows, &options);
+    assert_eq!(*clipped.get_pixel(0, 0), Rgb([255; 3]));
+    assert_eq!(*clipped.get_pixel(1, 0), Rgb([128; 3]));
+    assert_eq!(*clipped.get_pixel(2, 0), Rgb([0; 3]));
+    assert_eq!(*clipped.get_pixel(3, 0), Rgb([0; 3]));
+}
+
+#[test]
+fn ticks() {
+    let rows = vec![vec![0.0; 4]; 2];
+    let options = Options {
+        ticks: Some(Ticks {
+            x: vec![1, 10],
+            y: vec![0],
+        }),
+        ..gray(4)
+    };
+    let image = render(&rows, &options);
+    let black = Rgb([0; 3]);
+    let white = Rgb([255; 3]);
+
+    // x tick down the bottom margin in the middle of column 1, the out of
+    // range one is dropped
+    for y in 8..8 + TICK_MARGIN {
+        assert_eq!(*image.get_pixel(TICK_MARGIN + 6, y), black);
+        assert_eq!(*image.get_pixel(TICK_MARGIN + 5, y), white);
+    }
+    // y tick across the left margin in the middle of row 0
+    for x in 0..TICK_MARGIN {
+        assert_eq!(*image.get_pixel(x, 2), black);
+        assert_eq!(*image.get_pixel(x, 6), white);
+    }
+}
+
+#[test]
+fn spectrogram() {
+    // a tone on bin 3 of 16, 18.75Hz at 100Hz
+    let tone: Vec<f32> = (0..64)
+        .map(|n| (2.0 * core::f32::consts::PI * 3.0 * n as f32 / 16.0).cos())
+        .collect();
+    let stft = Stft::<16>::new(Window::Rectangular, 16, 8).unwrap();
+    let spectrogram = Spectrogram::new(
+        &stft,
+        stft.frames(&tone),
+        100.0,
+        Scale::Magnitude,
+        Sides::One,
+    );
+
+    // 7 frames across and 9 bins up, DC on the bottom row so the tone is the
+    // 4th row up
+    let image = spectrogram.to_image(&gray(1));
+    assert_eq!(image.dimensions(), (7, 9));
+    for x in 0..7 {
+        for y in 0..9 {
+            let expected = if y == 8 - 3 { 255 } else { 0 };
+            assert_eq!(*image.get_pixel(x, y), Rgb([expected; 3]));
+        }
+    }
+
+    // frames are 0.08s apart starting at 0.08, bins 6.25Hz apart
+    let ticks = spectrogram.ticks(0.2, 25.0);
+    assert_eq!(ticks.x, [2, 4]);
+    // 0, 25 and 50Hz, top row first
+    assert_eq!(ticks.y, [8, 4, 0]);
+
+    let path = std::env::temp_dir().join("lab4_png_spectrogram.png");
+    spectrogram.save_png(&path, &gray(3)).unwrap();
+    let read = image::open(&path).unwrap().to_rgb8();
+    assert_eq!(read, spectrogram.to_image(&gray(3)));
+
+    save(&path, &[vec![1.0, 2.0]], &gray(1)).unwrap();
+    assert_eq!(image::open(&path).unwrap().to_rgb8().dimensions(), (2, 1));
+}
@@ -0,0 +1,96 @@
+use dsp::stft::Stft;
+use dsp::window::Window;
+use lab4::spectrogram::{Scale, Sides, Spectrogram, FLOOR_DB};
+
+const SAMPLE_RATE: f32 = 100.0;
+
+/// cos right on bin 3 of a 16 point FFT, 18.75Hz at 100Hz
+fn tone() -> Vec<f32> {
+    (0..64)
+        .map(|n| (2.0 * core::f32::consts::PI * 3.0 * n as f32 / 16.0).cos())
+        .collect()
+}
+
+fn spectrogram(signal: &[f32], scale: Scale, sides: Sides) -> Spectrogram {
+    let stft = Stft::<16>::new(Window::Rectangular, 16, 8).unwrap();
+    Spectrogram::new(&stft, stft.frames(signal), SAMPLE_RATE, scale, sides)
+}
+
+#[test]
+fn axes() {
+    let one = spectrogram(&tone(), Scale::Magnitude, Sides::One);
+
+    // 7 frames of 16 every 8 samples, each timed at its middle
+    let times: Vec<f32> = (0..7).map(|m| (8 * m + 8) as f32 / SAMPLE_RATE).collect();
+    assert_eq!(one.times(), &times[..]);
+
+    // 6.25Hz bins from DC to Nyquist
+    let frequencies: Vec<f32> = (0..=8).map(|k| k as f32 * 6.25).collect();
+    assert_eq!(one.frequencies(), &frequencies[..]);
+
+    // two sided starts at -Nyquist and stops a bin short of +Nyquist
+    let two = spectrogram(&tone(), Scale::Magnitude, Sides::Two);
+    let frequencies: Vec<f32> = (-8..8).map(|k| k as f32 * 6.25).collect();
+    assert_eq!(two.frequencies(), &frequencies[..]);
+    assert_eq!(two.times(), one.times());
+}
+
+#[test]
+fn tone_lands_in_its_bin() {
+    let one = spectrogram(&tone(), Scale::Magnitude, Sides::One);
+    let two = spectrogram(&tone(), Scale::Magnitude, Sides::Two);
+
+    assert_eq!(one.values().len(), 7);
+    for frame in one.values() {
+        assert_eq!(frame.len(), 9);
+        for (k, &x) in frame.iter().enumerate() {
+            // half the energy in each of the positive and negative bin
+            let expected = if k == 3 { 8.0 } else { 0.0 };
+            assert!((x - expected).abs() < 1e-4, "bin {} {}", k, x);
+        }
+    }
+
+    // and the negative one shows up two sided, 8 bins either side of DC
+    for (frame, positive) in two.values().iter().zip(one.values()) {
+        assert!((frame[8 - 3] - 8.0).abs() < 1e-4);
+        assert_eq!(&frame[8..], &positive[..8]);
+    }
+}
+
+#[test]
+fn scales() {
+    let power = spectrogram(&tone(), Scale::Power, Sides::One);
+    let db = spectrogram(&tone(), Scale::Decibels, Sides::One);
+    assert_eq!(db.scale(), Scale::Decibels);
+
+    assert!((power.values()[0][3] - 64.0).abs() < 1e-3);
+    // 20 log10 8
+    assert!((db.values()[0][3] - 18.0618).abs() < 1e-3);
+
+    let silence = spectrogram(&[0.0; 64], Scale::Decibels, Sides::One);
+    assert!(silence.values().iter().flatten().all(|&x| x == FLOOR_DB));
+}
+
+#[test]
+fn transpose_and_band() {
+    let s = spectrogram(&tone(), Scale::Magnitude, Sides::Two);
+
+    let t = s.transpose();
+    assert_eq!(t.len(), 16);
+    for (k, row) in t.iter().enumerate() {
+        assert_eq!(row.len(), 7);
+        for (m, &x) in row.iter().enumerate() {
+            assert_eq!(x, s.values()[m][k]);
+        }
+    }
+
+    let band = s.band(10.0, 30.0);
+    assert_eq!(band.frequencies(), &[12.5, 18.75, 25.0]);
+    assert_eq!(band.times(), s.times());
+    for (frame, full) in band.values().iter().zip(s.values()) {
+        assert_eq!(&frame[..], &full[10..13]);
+    }
+
+    // nothing in between bins
+    assert!(s.band(1.0, 2.0).frequencies().is_empty());
+}
@@ -0,0 +1,113 @@
+//! A few bins of the accelerometer spectrum kept up to date every sample with
+//! the sliding DFT. 4_11_stft_accelerometer has to wait for a whole hop of
+//! samples before it has a new frame, here a change shows up in the bins as
+//! soon as the sample carrying it comes in, for a handful of multiplies per
+//! bin instead of an FFT.
+//!
+//! Bins are the Hann windowed DFT of the last N samples, the same as the FFT
+//! of a frame would give, using the modulated sliding DFT so it can run
+//! forever without rounding building up.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_11_sdft_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::complex::ComplexExt;
+use dsp::sdft::{Method, Sdft};
+use dsp::window::Window;
+use hal::{prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use rtt_target::{rprintln, rtt_init_print};
+
+const N: usize = 128;
+
+// bins around 2, 5, 10 and 20Hz
+const BINS: [usize; 4] = [3, 6, 13, 26];
+
+// 10 seconds worth
+const SAMPLES: usize = 1000;
+// print a line every tenth of a second
+const PRINT_EVERY: usize = 10;
+
+// sampling period of 10 milliseconds
+const SAMPLE_RATE: f32 = 100.0;
+
+// lis3dsh sensitivity at its default +-2g full scale, 0.06 mg/digit
+const G_PER_DIGIT: f32 = 0.06e-3;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi::Mode {
+            polarity: spi::Polarity::IdleLow,
+            phase: spi::Phase::CaptureOnFirstTransition,
+        },
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let mut sdft = Sdft::<N, 4>::new(BINS, Window::Hann, Method::Modulated);
+
+    // which frequency each column is
+    for k in BINS.iter() {
+        rprintln!("bin {} {:.2}Hz", k, *k as f32 * SAMPLE_RATE / N as f32);
+    }
+
+    for n in 0..SAMPLES {
+        while !lis3dsh.is_data_ready().unwrap() {}
+        let dat = lis3dsh.accel_raw().unwrap();
+        sdft.push(dat[0] as f32 * G_PER_DIGIT);
+
+        // before the window fills its still mostly the zeros it started with
+        if sdft.is_full() && n % PRINT_EVERY == 0 {
+            let spectrum = sdft.spectrum();
+            rprintln!(
+                "{:.2}s {:.4} {:.4} {:.4} {:.4}",
+                n as f32 / SAMPLE_RATE,
+                spectrum[0].magnitude(),
+                spectrum[1].magnitude(),
+                spectrum[2].magnitude(),
+                spectrum[3].magnitude()
+            );
+        }
+    }
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
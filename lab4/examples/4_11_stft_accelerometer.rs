@@ -5,6 +5,9 @@
 //! signal is divided into subwindows and FFT of each subwindow is calculated by
 //! the STFT function. The result is stored in the XST array.
 //!
+//! Stft frames and windows the signal, each frame is transformed with the
+//! selected FFT backend so `--features cmsis` runs it on arm_cfft_f32.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_11_stft_accelerometer`
 //!
@@ -16,20 +19,19 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cmplx_mag_f32};
-use core::f32::consts::PI;
-use cty::uint32_t;
+use dsp::backend;
+use dsp::complex::ComplexExt;
+use dsp::stft::Stft;
+use dsp::window::Window;
 use hal::{prelude::*, spi, stm32};
-use itertools::Itertools;
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
-use micromath::F32Ext;
+use microfft::Complex32;
 use rtt_target::{rprintln, rtt_init_print};
 
-use cmsis_dsp_sys::arm_cfft_sR_f32_len16 as arm_cfft_sR_f32;
 const WINDOW: usize = 16;
-const WINDOWCOMPLEX: usize = WINDOW * 2;
 
 const N: usize = 1024;
+const NDIV2: usize = N / 2;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -83,35 +85,30 @@ fn main() -> ! {
 
     rprintln!("computing");
 
-    let hamming = (0..WINDOW).map(|m| 0.54 - 0.46 * (2.0 * PI * m as f32 / WINDOW as f32).cos());
-
-    // get 64 input at a time, overlapping 32
-    // windowing is easier to do on slices
-    let overlapping_chirp_windows = Windows {
-        v: &accel,
-        size: WINDOW,
-        inc: WINDOW / 2,
-    };
+    // 16 input at a time, overlapping 8. the backend does the FFT so only the
+    // framing and window come from Stft
+    let stft = Stft::<WINDOW>::new(Window::Hamming, WINDOW, WINDOW / 2).unwrap();
 
-    let mut xst: heapless::Vec<[f32; WINDOW], N> = heapless::Vec::new();
+    let mut xst: heapless::Vec<heapless::Vec<f32, WINDOW>, NDIV2> = heapless::Vec::new();
 
-    let mut mag = [0f32; WINDOW];
+    for m in 0..stft.frame_count(accel.len()) {
+        let frame = &accel[m * stft.hop()..][..stft.window_len()];
 
-    for chirp_win in overlapping_chirp_windows {
-        // 64-0=64 of input to 64-64=0, so input * chirp.rev
-        let mut dtfsecoef: heapless::Vec<f32, WINDOWCOMPLEX> = hamming
-            .clone()
-            .zip(chirp_win.iter().rev())
-            .map(|(v, x)| v * x)
-            .interleave_shortest(core::iter::repeat(0.0))
+        let mut dtfsecoef: heapless::Vec<Complex32, WINDOW> = stft
+            .window()
+            .iter()
+            .zip(frame.iter())
+            .map(|(v, x)| Complex32 { re: v * x, im: 0.0 })
             .collect();
 
-        unsafe {
-            //Finding the FFT of window
-            arm_cfft_f32(&arm_cfft_sR_f32, dtfsecoef.as_mut_ptr(), 0, 1);
-            arm_cmplx_mag_f32(dtfsecoef.as_ptr(), mag.as_mut_ptr(), WINDOW as uint32_t);
-        }
+        //Finding the FFT of window
+        let _ = backend::fft(&mut dtfsecoef).unwrap();
 
+        // Magnitude calculation
+        let mag = dtfsecoef
+            .iter()
+            .map(|complex| complex.magnitude())
+            .collect();
         xst.push(mag).ok();
     }
 
@@ -122,30 +119,3 @@ fn main() -> ! {
         cortex_m::asm::bkpt()
     }
 }
-
-pub struct Windows<'a, T: 'a> {
-    v: &'a [T],
-    size: usize,
-    inc: usize,
-}
-
-impl<'a, T> Iterator for Windows<'a, T> {
-    type Item = &'a [T];
-
-    #[inline]
-    fn next(&mut self) -> Option<&'a [T]> {
-        if self.size > self.v.len() {
-            None
-        } else {
-            let ret = Some(&self.v[..self.size]);
-            self.v = &self.v[self.inc..];
-            ret
-        }
-    }
-}
-
-//C needs access to a sqrt fn, lets use micromath
-#[no_mangle]
-pub extern "C" fn sqrtf(x: f32) -> f32 {
-    x.sqrt()
-}
@@ -4,6 +4,9 @@
 //! array in main.c file. Also we have a digital filter represented with h array
 //! given in FIR_lpf_coefficients.h file.
 //!
+//! All three transforms use the selected FFT backend, microfft unless built
+//! with the `cmsis` or `reference` feature.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_13_fif_calculations`
 
@@ -13,16 +16,14 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cmplx_mult_cmplx_f32};
-use cty::uint32_t;
+use dsp::backend;
+use dsp::fft::Normalization;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use itertools::Itertools;
+use microfft::Complex32;
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 
-use cmsis_dsp_sys::arm_cfft_sR_f32_len512 as arm_cfft_sR_f32;
 const N: usize = 512;
-const NCOMPLEX: usize = N * 2;
 
 const W1: f32 = core::f32::consts::PI / 128.0;
 const W2: f32 = core::f32::consts::PI / 4.0;
@@ -49,53 +50,46 @@ fn main() -> ! {
     // Complex sum of sinusoidal signals
     let s1 = (0..N).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N).map(|val| (W2 * val as f32).sin());
+    let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
 
-    //we wont use complex this time since, but just interleave the zeros for the imaginary part
-    let mut s_complex: heapless::Vec<f32, NCOMPLEX> = s1
-        .zip(s2)
-        .map(|(ess1, ess2)| ess1 + ess2)
-        .interleave_shortest(core::iter::repeat(0.0))
-        .collect();
+    let mut s_complex: heapless::Vec<Complex32, N> =
+        s.map(|f| Complex32 { re: f, im: 0.0 }).collect();
 
     // Complex impulse response of filter
-    let mut df_complex: heapless::Vec<f32, NCOMPLEX> = H
+    let mut df_complex: heapless::Vec<Complex32, N> = H
         .iter()
         .cloned()
-        .interleave_shortest(core::iter::repeat(0.0))
-        .chain(core::iter::repeat(0.0))
-        //fill rest with zeros up to N*2
-        .take(NCOMPLEX)
+        .map(|f| Complex32 { re: f, im: 0.0 })
+        .chain(core::iter::repeat(Complex32 { re: 0.0, im: 0.0 }))
+        //fill rest with zeros up to N
+        .take(N)
         .collect();
 
     // Finding the FFT of the filter
-    unsafe {
-        arm_cfft_f32(&arm_cfft_sR_f32, df_complex.as_mut_ptr(), 0, 1);
-    }
-
-    let mut y_complex = [0f32; N * 2];
+    let _ = backend::fft(&mut df_complex).unwrap();
 
     let time: ClockDuration = dwt.measure(|| {
         // Finding the FFT of the input signal
-        unsafe {
-            arm_cfft_f32(&arm_cfft_sR_f32, s_complex.as_mut_ptr(), 0, 1);
-        }
+        let _ = backend::fft(&mut s_complex).unwrap();
 
         // Filtering in the frequency domain
-        unsafe {
-            arm_cmplx_mult_cmplx_f32(
-                s_complex.as_ptr(),
-                df_complex.as_ptr(),
-                y_complex.as_mut_ptr(),
-                N as uint32_t,
-            );
-        }
+        let mut y_complex: heapless::Vec<Complex32, N> = s_complex
+            .iter()
+            .zip(df_complex.iter())
+            //multiply complex
+            .map(|(s, df)| s * df)
+            .collect();
 
         // Finding the complex result in time domain
-        unsafe {
-            arm_cfft_f32(&arm_cfft_sR_f32, y_complex.as_mut_ptr(), 1, 1);
-        }
+        // inverse transform, scaled by the 1/N the forward one left out and
+        // the input was real so the imaginary parts are only rounding error
+        let _y_freq: heapless::Vec<f32, N> = backend::ifft(&mut y_complex, Normalization::OneOverN)
+            .unwrap()
+            .iter()
+            .map(|c| c.re)
+            .collect();
     });
-    rprintln!("dft ticks: {:?}", time.as_ticks());
+    rprintln!("{} ticks: {:?}", backend::NAME, time.as_ticks());
 
     // signal to probe-run to exit
     loop {
@@ -113,9 +107,3 @@ static H: &[f32] = &[
     0.002912, 0.002698, 0.002499, 0.002313, 0.002141, 0.001981, 0.001833, 0.001695, 0.001567,
     0.001448,
 ];
-
-//C needs access to a sqrt fn, lets use micromath
-#[no_mangle]
-pub extern "C" fn sqrtf(x: f32) -> f32 {
-    x.sqrt()
-}
@@ -14,7 +14,8 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use core::f32::consts::PI;
+use dsp::complex::ComplexExt;
+use dsp::dft::dft;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
@@ -49,16 +50,14 @@ fn main() -> ! {
     let s2 = (0..N).map(|val| (W2 * val as f32).sin());
     let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
 
-    // map it to real, leave im blank well fill in with dft
-    let dtfsecoef = s.map(|f| Complex32 { re: f, im: 0.0 });
+    // real samples go straight in, no need to fill in a blank im
+    let dtfsecoef: heapless::Vec<f32, N> = s.collect();
 
     let time: ClockDuration = dwt.measure(|| {
-        let dft = dft(dtfsecoef.clone());
+        let dft = dft(&dtfsecoef).unwrap();
 
         //Magnitude calculation
-        let _mag: heapless::Vec<f32, N> = dft
-            .map(|complex| (complex.re * complex.re + complex.im * complex.im).sqrt())
-            .collect();
+        let _mag: heapless::Vec<f32, N> = dft.map(|complex| complex.magnitude()).collect();
     });
     rprintln!("ticks: {:?}", time.as_ticks());
 
@@ -67,36 +66,3 @@ fn main() -> ! {
         cortex_m::asm::bkpt()
     }
 }
-
-fn dft<I: Iterator<Item = Complex32> + Clone>(input: I) -> impl Iterator<Item = Complex32> {
-    let size = N as f32;
-    (0..N).map(move |k| {
-        input
-            .clone()
-            .enumerate()
-            .fold((0f32, 0f32), |(mut sum_re, mut sum_im), (n, complex)| {
-                let n = n as f32;
-                sum_re += complex.re * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.im * (2.0 * PI * k as f32 * n / size).sin();
-                sum_im += -complex.im * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.re * (2.0 * PI * k as f32 * n / size).sin();
-
-                (sum_re, sum_im)
-            })
-            .into()
-    })
-}
-
-struct Complex32 {
-    re: f32,
-    im: f32,
-}
-
-impl From<(f32, f32)> for Complex32 {
-    fn from(incoming: (f32, f32)) -> Self {
-        Complex32 {
-            re: incoming.0,
-            im: incoming.1,
-        }
-    }
-}
@@ -19,9 +19,11 @@ use panic_break as _;
 use stm32f4xx_hal as hal;
 
 use cmsis_dsp_sys::arm_cmplx_mag_f32;
-use core::f32::consts::PI;
-use cty::{c_float, uint32_t};
+use cty::uint32_t;
+use dsp::complex::Interleaved;
+use dsp::dft::dft;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
+use microfft::Complex32;
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 
@@ -55,20 +57,20 @@ fn main() -> ! {
     let s2 = (0..N).map(|val| (W2 * val as f32).sin());
     let s = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2);
 
-    // map it to real, leave im blank well fill in with dft
-    let dtfsecoef: heapless::Vec<Complex32, N> = s.map(|f| Complex32 { re: f, im: 0.0 }).collect();
+    // real samples go straight in, no need to fill in a blank im
+    let dtfsecoef: heapless::Vec<f32, N> = s.collect();
 
     let mut mag = [0f32; N];
 
     let time: ClockDuration = dwt.measure(|| unsafe {
-        let dft: heapless::Vec<Complex32, N> = dft(dtfsecoef.into_iter()).collect();
+        let dft: heapless::Vec<Complex32, N> = dft(&dtfsecoef).unwrap().collect();
 
         // Magnitude calculation
-        // a union of two f32 are just two f32 side by side in memory? so this
-        // cast seems to be safe here and lets us keep using Complexf32 which is
-        // far more ergonomic
+        // Complex32 is two f32 side by side in memory so CMSIS can take it as
+        // interleaved floats and we keep using Complex32 which is far more
+        // ergonomic
         arm_cmplx_mag_f32(
-            dft.as_ptr() as *const c_float,
+            dft.as_interleaved().as_ptr(),
             mag.as_mut_ptr(),
             N as uint32_t,
         );
@@ -81,41 +83,9 @@ fn main() -> ! {
     }
 }
 
-fn dft<I: Iterator<Item = Complex32> + Clone>(input: I) -> impl Iterator<Item = Complex32> {
-    let size = N as f32;
-    (0..N).map(move |k| {
-        input
-            .clone()
-            .enumerate()
-            .fold((0f32, 0f32), |(mut sum_re, mut sum_im), (n, complex)| {
-                let n = n as f32;
-                sum_re += complex.re * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.im * (2.0 * PI * k as f32 * n / size).sin();
-                sum_im += -complex.im * (2.0 * PI * k as f32 * n / size).cos()
-                    + complex.re * (2.0 * PI * k as f32 * n / size).sin();
-
-                (sum_re, sum_im)
-            })
-            .into()
-    })
-}
-
-#[derive(Clone)]
-struct Complex32 {
-    re: f32,
-    im: f32,
-}
-
-impl From<(f32, f32)> for Complex32 {
-    fn from(incoming: (f32, f32)) -> Self {
-        Complex32 {
-            re: incoming.0,
-            im: incoming.1,
-        }
-    }
-}
-
-//C needs access to a sqrt fn, lets use micromath
+//C needs access to a sqrt fn, lets use micromath. dsp exports one itself
+//when its cmsis backend is on
+#[cfg(not(feature = "cmsis"))]
 #[no_mangle]
 pub extern "C" fn sqrtf(x: f32) -> f32 {
     x.sqrt()
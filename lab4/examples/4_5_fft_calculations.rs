@@ -7,8 +7,15 @@
 //! signal is calculated with the arm_cmplx_mag_f32 function. The result is
 //! saved in the Mag array.
 //!
+//! When only the two bins of the sinusoids are wanted a Goertzel per bin gets
+//! them without the rest of the FFT, timed below for comparison. W1 is bin 1
+//! and W2 bin 32 of the 256.
+//!
+//! The FFT is microfft unless the `cmsis` or `reference` feature swaps in
+//! arm_cfft_f32 or the direct DFT, the printout says which ran.
+//!
 //! Requires `cargo install probe-run`
-//! `cargo run --release --example 4_5_fft_calculations`
+//! `cargo run --release --example 4_5_fft_calculations --features cmsis`
 
 #![no_std]
 #![no_main]
@@ -16,16 +23,15 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cmplx_mag_f32};
-use cty::uint32_t;
+use dsp::backend;
+use dsp::complex::ComplexExt;
+use dsp::goertzel::Goertzel;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use itertools::Itertools;
+use microfft::Complex32;
 use micromath::F32Ext;
 use rtt_target::{rprintln, rtt_init_print};
 
-use cmsis_dsp_sys::arm_cfft_sR_f32_len256 as arm_cfft_sR_f32;
 const N: usize = 256;
-const NCOMPLEX: usize = N * 2;
 
 const W1: f32 = core::f32::consts::PI / 128.0;
 const W2: f32 = core::f32::consts::PI / 4.0;
@@ -53,33 +59,42 @@ fn main() -> ! {
     // Complex sum of sinusoidal signals
     let s1 = (0..N).map(|val| (W1 * val as f32).sin());
     let s2 = (0..N).map(|val| (W2 * val as f32).sin());
+    let s: heapless::Vec<f32, N> = s1.zip(s2).map(|(ess1, ess2)| ess1 + ess2).collect();
 
-    //we wont use complex this time since, but just interleave the zeros for the imaginary part
-    let mut s: heapless::Vec<f32, NCOMPLEX> = s1
-        .zip(s2)
-        .map(|(ess1, ess2)| ess1 + ess2)
-        .interleave_shortest(core::iter::repeat(0.0))
-        .collect();
-
-    let mut mag = [0f32; N];
+    // map it to real, leave im blank well fill in with dft
+    let mut dtfsecoef: heapless::Vec<Complex32, N> =
+        s.iter().map(|&f| Complex32 { re: f, im: 0.0 }).collect();
 
-    let time: ClockDuration = dwt.measure(|| unsafe {
-        //CFFT calculation
-        arm_cfft_f32(&arm_cfft_sR_f32, s.as_mut_ptr(), 0, 1);
+    let time: ClockDuration = dwt.measure(|| {
+        // Coefficient calculation with whichever FFT backend is selected, in
+        // place. it re-returns our array in case we were going to chain calls,
+        // throw it away
+        let _ = backend::fft(&mut dtfsecoef).unwrap();
 
         // Magnitude calculation
-        arm_cmplx_mag_f32(s.as_ptr(), mag.as_mut_ptr(), N as uint32_t);
+        let _mag: heapless::Vec<f32, N> = dtfsecoef
+            .iter()
+            .map(|complex| complex.magnitude())
+            .collect();
     });
-    rprintln!("ticks: {:?}", time.as_ticks());
+    rprintln!("{} ticks: {:?}", backend::NAME, time.as_ticks());
+
+    // just the two bins we care about
+    let mut bins = [Goertzel::new(1, N), Goertzel::new(32, N)];
+    let time: ClockDuration = dwt.measure(|| {
+        for goertzel in bins.iter_mut() {
+            goertzel.process(&s);
+        }
+    });
+    rprintln!("goertzel ticks: {:?}", time.as_ticks());
+    rprintln!(
+        "goertzel mag: {} {}",
+        bins[0].power().sqrt(),
+        bins[1].power().sqrt()
+    );
 
     // signal to probe-run to exit
     loop {
         cortex_m::asm::bkpt()
     }
 }
-
-//C needs access to a sqrt fn, lets use micromath
-#[no_mangle]
-pub extern "C" fn sqrtf(x: f32) -> f32 {
-    x.sqrt()
-}
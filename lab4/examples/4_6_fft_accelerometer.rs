@@ -6,6 +6,15 @@
 //! the output signal is calculated with the arm_cmplx_mag_f32 function. The
 //! result is saved in the Mag array.
 //!
+//! The samples are real though, so dsp::rfft gets the same N/2+1 bins with half
+//! the memory and roughly half the cycles. Both are timed with the DWT cycle
+//! counter to compare. Finally the strongest peaks of the real spectrum are
+//! interpolated and reported in Hz, and a chirp-Z transform samples the
+//! spectrum 16 times finer across the strongest one.
+//!
+//! Both transforms go through the selected FFT backend, microfft by default
+//! or CMSIS-DSP's arm_cfft_f32 and arm_rfft_fast_f32 with the `cmsis` feature.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_6_fft_accelerometer`
 
@@ -15,17 +24,19 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cmplx_mag_f32};
-use cty::uint32_t;
-use hal::{prelude::*, spi, stm32};
-use itertools::Itertools;
+use dsp::backend;
+use dsp::complex::ComplexExt;
+use dsp::czt::Czt;
+use dsp::peaks::{self, Options, Peak};
+use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, spi, stm32};
 use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
-use micromath::F32Ext;
+use microfft::Complex32;
 use rtt_target::{rprintln, rtt_init_print};
 
-use cmsis_dsp_sys::arm_cfft_sR_f32_len512 as arm_cfft_sR_f32;
 const N: usize = 512;
-const NCOMPLEX: usize = N * 2;
+
+// sampling period of 10 milliseconds
+const SAMPLE_RATE: f32 = 100.0;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -45,6 +56,9 @@ fn main() -> ! {
 
     let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
 
+    // Create a delay abstraction based on DWT cycle counter
+    let dwt = cp.DWT.constrain(cp.DCB, clocks);
+
     let gpioa = dp.GPIOA.split();
     let gpioe = dp.GPIOE.split();
 
@@ -68,36 +82,95 @@ fn main() -> ! {
     lis3dsh.init(&mut delay).unwrap();
 
     // dont love the idea of delaying in an iterator ...
-    let dtfsecoef = (0..N).map(|_| {
-        while !lis3dsh.is_data_ready().unwrap() {}
-        let dat = lis3dsh.accel_raw().unwrap();
-        dat[0] as f32
-    });
+    let samples: heapless::Vec<f32, N> = (0..N)
+        .map(|_| {
+            while !lis3dsh.is_data_ready().unwrap() {}
+            let dat = lis3dsh.accel_raw().unwrap();
+            dat[0] as f32
+        })
+        .collect();
 
-    let mut dtfsecoef: heapless::Vec<f32, NCOMPLEX> = dtfsecoef
-        .interleave_shortest(core::iter::repeat(0.0))
+    let mut dtfsecoef: heapless::Vec<Complex32, N> = samples
+        .iter()
+        .map(|&re| Complex32 { re, im: 0.0 })
         .collect();
 
-    let mut mag = [0f32; N];
+    let mut mag: heapless::Vec<f32, N> = heapless::Vec::new();
 
-    unsafe {
-        //CFFT calculation
-        arm_cfft_f32(&arm_cfft_sR_f32, dtfsecoef.as_mut_ptr(), 0, 1);
+    let cfft_time: ClockDuration = dwt.measure(|| {
+        // Coefficient calculation with the selected backends in place FFT
+        // it re-returns our array in case we were going to chain calls, throw it away
+        let _ = backend::fft(&mut dtfsecoef).unwrap();
 
         // Magnitude calculation
-        arm_cmplx_mag_f32(dtfsecoef.as_ptr(), mag.as_mut_ptr(), N as uint32_t);
-    }
+        mag = dtfsecoef
+            .iter()
+            .map(|complex| complex.magnitude())
+            .collect();
+    });
 
     rprintln!("mag: {:?}", mag);
 
+    // the real fft works in place, on a copy so the chirp-Z still has the
+    // samples
+    let mut input = samples.clone();
+    let mut rmag: heapless::Vec<f32, { N / 2 + 1 }> = heapless::Vec::new();
+
+    let rfft_time: ClockDuration = dwt.measure(|| {
+        // N/2+1 bins from DC to Nyquist
+        let spectrum = backend::rfft(&mut input).unwrap();
+
+        // Magnitude calculation
+        rmag = spectrum.magnitude().collect();
+    });
+
+    rprintln!("rmag: {:?}", rmag);
+
+    // the strongest vibrations in Hz rather than eyeballing rmag. DC is never
+    // a peak so gravity doesnt get in the way
+    let peaks: heapless::Vec<Peak, 3> = peaks::find(&rmag[..], &Options::default());
+    for peak in &peaks {
+        rprintln!(
+            "peak {:.2}Hz magnitude {}",
+            peak.frequency(SAMPLE_RATE / N as f32),
+            peak.magnitude
+        );
+    }
+
+    // two bins either side of the strongest peak in 32 points
+    if let Some(peak) = peaks.first() {
+        let resolution = SAMPLE_RATE / N as f32;
+        let start = peak.frequency(resolution) - 2.0 * resolution;
+        let czt = Czt::<N, 32, { 2 * N }>::frequencies(start, resolution / 8.0, SAMPLE_RATE);
+
+        let mut zoomed: heapless::Vec<f32, 32> = heapless::Vec::new();
+        let czt_time: ClockDuration = dwt.measure(|| {
+            zoomed = czt
+                .transform(&samples)
+                .unwrap()
+                .iter()
+                .map(|c| c.magnitude())
+                .collect();
+        });
+
+        let (k, magnitude) = zoomed
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        rprintln!(
+            "czt peak {:.3}Hz magnitude {}",
+            czt.frequency(k, SAMPLE_RATE),
+            magnitude
+        );
+        rprintln!("czt ticks: {:?}", czt_time.as_ticks());
+    }
+
+    rprintln!("{} cfft ticks: {:?}", backend::NAME, cfft_time.as_ticks());
+    rprintln!("{} rfft ticks: {:?}", backend::NAME, rfft_time.as_ticks());
+
     // signal to probe-run to exit
     loop {
         cortex_m::asm::bkpt()
     }
 }
-
-//C needs access to a sqrt fn, lets use micromath
-#[no_mangle]
-pub extern "C" fn sqrtf(x: f32) -> f32 {
-    x.sqrt()
-}
@@ -0,0 +1,104 @@
+//! Power spectral density of the accelerometer, averaged over blocks as they
+//! come in. A single FFT magnitude like 4_6 prints is as noisy as the signal
+//! and in made up units, Welch's method averages half overlapped Hann windowed
+//! segments into g^2/Hz so the noise floor settles down the longer it runs and
+//! reads the same whatever the FFT length.
+//!
+//! Only one block of samples is ever held, Psd keeps the running sums and the
+//! overlap between blocks itself.
+//!
+//! Requires `cargo install probe-run`
+//! `cargo run --release --example 4_7_psd_accelerometer`
+
+#![no_std]
+#![no_main]
+
+use panic_break as _;
+use stm32f4xx_hal as hal;
+
+use dsp::psd::{Psd, Sides};
+use dsp::window::Window;
+use hal::{prelude::*, spi, stm32};
+use lis3dsh::{accelerometer::RawAccelerometer, Lis3dsh};
+use rtt_target::{rprintln, rtt_init_print};
+
+const N: usize = 256;
+const BLOCKS: usize = 8;
+
+// sampling period of 10 milliseconds
+const SAMPLE_RATE: f32 = 100.0;
+
+// lis3dsh sensitivity at its default +-2g full scale, 0.06 mg/digit
+const G_PER_DIGIT: f32 = 0.06e-3;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!(BlockIfFull, 128);
+
+    let dp = stm32::Peripherals::take().unwrap();
+    let cp = cortex_m::peripheral::Peripherals::take().unwrap();
+
+    // Set up the system clock.
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.mhz()) //discovery board has 8 MHz crystal for HSE
+        .sysclk(168.mhz())
+        .freeze();
+
+    let mut delay = hal::delay::Delay::new(cp.SYST, clocks);
+
+    let gpioa = dp.GPIOA.split();
+    let gpioe = dp.GPIOE.split();
+
+    let sck = gpioa.pa5.into_alternate_af5().internal_pull_up(false);
+    let miso = gpioa.pa6.into_alternate_af5().internal_pull_up(false);
+    let mosi = gpioa.pa7.into_alternate_af5().internal_pull_up(false);
+
+    let spi = spi::Spi::spi1(
+        dp.SPI1,
+        (sck, miso, mosi),
+        spi::Mode {
+            polarity: spi::Polarity::IdleLow,
+            phase: spi::Phase::CaptureOnFirstTransition,
+        },
+        10.mhz().into(),
+        clocks,
+    );
+
+    let chip_select = gpioe.pe3.into_push_pull_output();
+    let mut lis3dsh = Lis3dsh::new_spi(spi, chip_select);
+    lis3dsh.init(&mut delay).unwrap();
+
+    let mut psd = Psd::<N>::welch(Window::Hann, N / 2, SAMPLE_RATE, Sides::One);
+
+    for block in 0..BLOCKS {
+        // dont love the idea of delaying in an iterator ...
+        let samples: heapless::Vec<f32, N> = (0..N)
+            .map(|_| {
+                while !lis3dsh.is_data_ready().unwrap() {}
+                let dat = lis3dsh.accel_raw().unwrap();
+                dat[0] as f32 * G_PER_DIGIT
+            })
+            .collect();
+
+        let added = psd.push(&samples);
+        rprintln!(
+            "block {} added {} segments, {} so far",
+            block,
+            added,
+            psd.segments()
+        );
+    }
+
+    // g^2/Hz from DC to 50Hz
+    for (f, p) in psd.frequencies().zip(psd.psd()) {
+        rprintln!("{:.2}Hz {:e}", f, p);
+    }
+
+    // signal to probe-run to exit
+    loop {
+        cortex_m::asm::bkpt()
+    }
+}
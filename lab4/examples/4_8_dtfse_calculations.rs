@@ -5,6 +5,9 @@
 //! real form because original signal has only real parts in this example. The
 //! result is kept in the y_real array.
 //!
+//! The coefficients come from the selected FFT backend, `--features cmsis`
+//! for arm_cfft_f32 rather than microfft.
+//!
 //! Requires `cargo install probe-run`
 //! `cargo run --release --example 4_8_dtfse_calculations`
 
@@ -14,14 +17,12 @@
 use panic_break as _;
 use stm32f4xx_hal as hal;
 
-use cmsis_dsp_sys::{arm_cfft_f32, arm_cos_f32};
-use core::f32::consts::PI;
-use cty::c_float;
+use dsp::backend;
+use dsp::dtfse::Series;
 use hal::{dwt::ClockDuration, dwt::DwtExt, prelude::*, stm32};
-use micromath::F32Ext;
+use microfft::Complex32;
 use rtt_target::{rprintln, rtt_init_print};
 
-use cmsis_dsp_sys::arm_cfft_sR_f32_len16 as arm_cfft_sR_f32;
 const N: usize = 16;
 
 #[cortex_m_rt::entry]
@@ -44,54 +45,38 @@ fn main() -> ! {
     let dwt = cp.DWT.constrain(cp.DCB, clocks);
 
     // square signal
-    let square = (0..N).map(|n| if n < N / 2 { 1.0 } else { 0.0 });
+    let square: heapless::Vec<f32, N> = (0..N).map(|n| if n < N / 2 { 1.0 } else { 0.0 }).collect();
 
     // map it to real, leave im blank well fill in with cfft
-    let mut dtfsecoef: heapless::Vec<Complex32, N> =
-        square.map(|f| Complex32 { re: f, im: 0.0 }).collect();
-
-    //Coefficient calculation with CFFT function
-    unsafe {
-        arm_cfft_f32(
-            &arm_cfft_sR_f32,
-            dtfsecoef.as_mut_ptr() as *mut c_float,
-            0,
-            1,
-        );
-    }
+    let mut dtfsecoef: heapless::Vec<Complex32, N> = square
+        .iter()
+        .map(|&f| Complex32 { re: f, im: 0.0 })
+        .collect();
+
+    // Coefficient calculation with the selected backends in place FFT
+    // it re-returns our array in case we were going to chain calls, throw it away
+    let _ = backend::fft(&mut dtfsecoef).unwrap();
+
+    let series = Series::new(&dtfsecoef);
 
     let time: ClockDuration = dwt.measure(|| {
-        let _y_real: heapless::Vec<f32, N> = dtfse(dtfsecoef.iter().cloned(), 15).collect();
+        let _y_real: heapless::Vec<f32, N> = series.synthesize(series.max_harmonics(), N).collect();
     });
     rprintln!("ticks: {:?}", time.as_ticks());
 
+    // how close each number of harmonics gets, and how far it rings past the
+    // square between samples
+    for report in series.convergence(&square, 4 * N) {
+        rprintln!(
+            "harmonics: {} mse: {} overshoot: {}",
+            report.harmonics,
+            report.mse,
+            report.overshoot
+        );
+    }
+
     // signal to probe-run to exit
     loop {
         cortex_m::asm::bkpt()
     }
 }
-
-fn dtfse<I: Iterator<Item = Complex32> + Clone>(
-    coeff: I,
-    k_var: usize,
-) -> impl Iterator<Item = f32> {
-    let size = N as f32;
-    (0..N).map(move |n| {
-        coeff
-            .clone()
-            .take(k_var + 1)
-            .enumerate()
-            .map(|(k, complex)| {
-                let a = (complex.re * complex.re + complex.im * complex.im).sqrt();
-                let p = complex.im.atan2(complex.re);
-                unsafe { a * arm_cos_f32((2.0 * PI * k as f32 * n as f32 / size) + p) / size }
-            })
-            .sum::<f32>()
-    })
-}
-
-#[derive(Clone)]
-struct Complex32 {
-    re: f32,
-    im: f32,
-}